@@ -0,0 +1,437 @@
+use crate::config::{KeyBindings, Keys};
+
+/// Something a key can do
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Action {
+    Quit,
+    Help,
+    NextPanel,
+    Back,
+    FocusLog,
+    Zoom,
+    GrowJobs,
+    ShrinkJobs,
+    PinLog,
+    PinSplit,
+    SyncScroll,
+    Down,
+    Up,
+    Top,
+    Bottom,
+    PageDown,
+    PageUp,
+    HalfPageDown,
+    HalfPageUp,
+    ScrollLeft,
+    ScrollRight,
+    Refresh,
+    ToggleLogs,
+    NextTab,
+    Mark,
+    MarkAll,
+    VisualMark,
+    CopyIds,
+    CancelJob,
+    HoldJob,
+    ReleaseJob,
+    EditJob,
+    SignalJob,
+    SubmitJob,
+    ResubmitJob,
+    OpenShell,
+    OpenPager,
+    OpenEditor,
+    Search,
+    SearchBackward,
+    SearchNext,
+    SearchPrev,
+    NextError,
+    PrevError,
+    ToggleWrap,
+    SetMark,
+    JumpMark,
+    GotoPercent,
+    CommandLine,
+    GotoJob,
+}
+
+pub const ALL_ACTIONS: [Action; 50] = [
+    Action::Quit,
+    Action::Help,
+    Action::NextPanel,
+    Action::Back,
+    Action::FocusLog,
+    Action::Zoom,
+    Action::GrowJobs,
+    Action::ShrinkJobs,
+    Action::PinLog,
+    Action::PinSplit,
+    Action::SyncScroll,
+    Action::Down,
+    Action::Up,
+    Action::Top,
+    Action::Bottom,
+    Action::PageDown,
+    Action::PageUp,
+    Action::HalfPageDown,
+    Action::HalfPageUp,
+    Action::ScrollLeft,
+    Action::ScrollRight,
+    Action::Refresh,
+    Action::ToggleLogs,
+    Action::NextTab,
+    Action::Mark,
+    Action::MarkAll,
+    Action::VisualMark,
+    Action::CopyIds,
+    Action::CancelJob,
+    Action::HoldJob,
+    Action::ReleaseJob,
+    Action::EditJob,
+    Action::SignalJob,
+    Action::SubmitJob,
+    Action::ResubmitJob,
+    Action::OpenShell,
+    Action::OpenPager,
+    Action::OpenEditor,
+    Action::Search,
+    Action::SearchBackward,
+    Action::SearchNext,
+    Action::SearchPrev,
+    Action::NextError,
+    Action::PrevError,
+    Action::ToggleWrap,
+    Action::SetMark,
+    Action::JumpMark,
+    Action::GotoPercent,
+    Action::CommandLine,
+    Action::GotoJob,
+];
+
+impl Action {
+    /// Name of the action under `[keybindings]`
+    pub fn config_name(self) -> &'static str {
+        match self {
+            Action::Quit => "quit",
+            Action::Zoom => "zoom",
+            Action::GrowJobs => "grow_jobs",
+            Action::ShrinkJobs => "shrink_jobs",
+            Action::PinLog => "pin_log",
+            Action::PinSplit => "pin_split",
+            Action::SyncScroll => "sync_scroll",
+            Action::Down => "down",
+            Action::Up => "up",
+            Action::Top => "top",
+            Action::Bottom => "bottom",
+            Action::ScrollLeft => "scroll_left",
+            Action::ScrollRight => "scroll_right",
+            Action::Refresh => "refresh",
+            Action::ToggleLogs => "toggle_logs",
+            Action::NextTab => "next_tab",
+            Action::Mark => "mark",
+            Action::MarkAll => "mark_all",
+            Action::VisualMark => "visual_mark",
+            Action::CopyIds => "copy_ids",
+            Action::CancelJob => "cancel_job",
+            Action::HoldJob => "hold_job",
+            Action::ReleaseJob => "release_job",
+            Action::EditJob => "edit_job",
+            Action::SignalJob => "signal_job",
+            Action::SubmitJob => "submit_job",
+            Action::ResubmitJob => "resubmit_job",
+            Action::OpenShell => "open_shell",
+            Action::OpenPager => "open_pager",
+            Action::OpenEditor => "open_editor",
+            Action::Search => "search",
+            Action::SearchBackward => "search_backward",
+            Action::SearchNext => "search_next",
+            Action::SearchPrev => "search_prev",
+            Action::NextError => "next_error",
+            Action::PrevError => "prev_error",
+            Action::ToggleWrap => "toggle_wrap",
+            Action::Help => "help",
+            Action::NextPanel => "next_panel",
+            Action::Back => "back",
+            Action::FocusLog => "focus_log",
+            Action::PageDown => "page_down",
+            Action::PageUp => "page_up",
+            Action::HalfPageDown => "half_page_down",
+            Action::HalfPageUp => "half_page_up",
+            Action::SetMark => "set_mark",
+            Action::JumpMark => "jump_mark",
+            Action::GotoPercent => "goto_percent",
+            Action::CommandLine => "command_line",
+            Action::GotoJob => "goto_job",
+        }
+    }
+
+    pub fn from_config_name(name: &str) -> Option<Action> {
+        ALL_ACTIONS
+            .iter()
+            .copied()
+            .find(|a| a.config_name() == name)
+    }
+
+    /// The keys configured under `[keybindings]`
+    pub fn binding(self, kb: &KeyBindings) -> &Keys {
+        match self {
+            Action::Quit => &kb.quit,
+            Action::Zoom => &kb.zoom,
+            Action::GrowJobs => &kb.grow_jobs,
+            Action::ShrinkJobs => &kb.shrink_jobs,
+            Action::PinLog => &kb.pin_log,
+            Action::PinSplit => &kb.pin_split,
+            Action::SyncScroll => &kb.sync_scroll,
+            Action::Down => &kb.down,
+            Action::Up => &kb.up,
+            Action::Top => &kb.top,
+            Action::Bottom => &kb.bottom,
+            Action::ScrollLeft => &kb.scroll_left,
+            Action::ScrollRight => &kb.scroll_right,
+            Action::Refresh => &kb.refresh,
+            Action::ToggleLogs => &kb.toggle_logs,
+            Action::NextTab => &kb.next_tab,
+            Action::Mark => &kb.mark,
+            Action::MarkAll => &kb.mark_all,
+            Action::VisualMark => &kb.visual_mark,
+            Action::CopyIds => &kb.copy_ids,
+            Action::CancelJob => &kb.cancel_job,
+            Action::HoldJob => &kb.hold_job,
+            Action::ReleaseJob => &kb.release_job,
+            Action::EditJob => &kb.edit_job,
+            Action::SignalJob => &kb.signal_job,
+            Action::SubmitJob => &kb.submit_job,
+            Action::ResubmitJob => &kb.resubmit_job,
+            Action::OpenShell => &kb.open_shell,
+            Action::OpenPager => &kb.open_pager,
+            Action::OpenEditor => &kb.open_editor,
+            Action::Search => &kb.search,
+            Action::SearchBackward => &kb.search_backward,
+            Action::SearchNext => &kb.search_next,
+            Action::SearchPrev => &kb.search_prev,
+            Action::NextError => &kb.next_error,
+            Action::PrevError => &kb.prev_error,
+            Action::ToggleWrap => &kb.toggle_wrap,
+            Action::Help => &kb.help,
+            Action::NextPanel => &kb.next_panel,
+            Action::Back => &kb.back,
+            Action::FocusLog => &kb.focus_log,
+            Action::PageDown => &kb.page_down,
+            Action::PageUp => &kb.page_up,
+            Action::HalfPageDown => &kb.half_page_down,
+            Action::HalfPageUp => &kb.half_page_up,
+            Action::SetMark => &kb.set_mark,
+            Action::JumpMark => &kb.jump_mark,
+            Action::GotoPercent => &kb.goto_percent,
+            Action::CommandLine => &kb.command_line,
+            Action::GotoJob => &kb.goto_job,
+        }
+    }
+}
+
+/// Where a binding applies
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Panel {
+    /// Every panel
+    Global,
+    Jobs,
+    Details,
+    /// Either log pane on the Log tab
+    Log,
+    Script,
+    Metrics,
+}
+
+impl Panel {
+    pub const ALL: [Panel; 6] = [
+        Panel::Global,
+        Panel::Jobs,
+        Panel::Details,
+        Panel::Log,
+        Panel::Script,
+        Panel::Metrics,
+    ];
+
+    /// Table name of the panel's overrides, `[keybindings.<name>]`
+    pub fn config_name(self) -> &'static str {
+        match self {
+            Panel::Global => "",
+            Panel::Jobs => "jobs",
+            Panel::Details => "details",
+            Panel::Log => "log",
+            Panel::Script => "script",
+            Panel::Metrics => "metrics",
+        }
+    }
+
+    pub fn title(self) -> &'static str {
+        match self {
+            Panel::Global => "Anywhere",
+            Panel::Jobs => "Job list",
+            Panel::Details => "Details",
+            Panel::Log => "Log",
+            Panel::Script => "Script tab",
+            Panel::Metrics => "Metrics tab",
+        }
+    }
+}
+
+/// One action as offered in one panel
+pub struct Entry {
+    pub panel: Panel,
+    pub action: Action,
+    /// Keys always bound in this panel on top of the configured ones, even with a per-panel
+    /// override (arrows, Home and End)
+    pub keys: &'static [&'static str],
+    pub description: &'static str,
+    /// Status bar label; entries without one are only listed in the help screen.
+    /// Neighbouring entries with the same label share a hint ("j/k navigate").
+    pub hint: Option<&'static str>,
+}
+
+const fn entry(
+    panel: Panel,
+    action: Action,
+    keys: &'static [&'static str],
+    description: &'static str,
+    hint: Option<&'static str>,
+) -> Entry {
+    Entry {
+        panel,
+        action,
+        keys,
+        description,
+        hint,
+    }
+}
+
+use Action as A;
+use Panel as P;
+
+/// Entries of the job list's right-click menu
+pub const JOB_MENU: &[(Action, &str)] = &[
+    (A::FocusLog, "Open log"),
+    (A::Zoom, "Zoom"),
+    (A::PinLog, "Pin log"),
+    (A::Mark, "Mark / unmark"),
+    (A::CopyIds, "Copy IDs"),
+    (A::EditJob, "Edit limits"),
+    (A::HoldJob, "Hold"),
+    (A::ReleaseJob, "Release"),
+    (A::SignalJob, "Signal"),
+    (A::CancelJob, "Cancel"),
+    (A::ResubmitJob, "Resubmit"),
+    (A::OpenShell, "Shell"),
+    (A::OpenPager, "Open in $PAGER"),
+    (A::OpenEditor, "Open in $EDITOR"),
+];
+
+/// Every binding, in the order the help screen and status bar show them
+#[rustfmt::skip]
+pub const REGISTRY: &[Entry] = &[
+    entry(P::Global, A::Help, &[], "Show this help", Some("help")),
+    entry(P::Global, A::CommandLine, &[], "Command line: any action by name, filter, sort, set, goto, theme", Some("command")),
+    entry(P::Global, A::GotoJob, &[], "Go to a job by ID or ID prefix (also finished jobs, via sacct)", Some("go to job")),
+    entry(P::Global, A::NextPanel, &[], "Focus the next panel", Some("panels")),
+    entry(P::Global, A::Back, &[], "Back to the job list (or cancel a visual selection)", None),
+    entry(P::Global, A::Zoom, &[], "Zoom the focused panel to the full screen (again to restore)", Some("zoom")),
+    entry(P::Global, A::ShrinkJobs, &[], "Shrink the job list", Some("resize")),
+    entry(P::Global, A::GrowJobs, &[], "Grow the job list", Some("resize")),
+    entry(P::Global, A::PinLog, &[], "Pin the selected job's log into a second pane (again to unpin)", Some("pin")),
+    entry(P::Global, A::PinSplit, &[], "Show the pinned log beside or below", Some("beside/below")),
+    entry(P::Global, A::SyncScroll, &[], "Scroll both logs together", Some("sync scroll")),
+    entry(P::Global, A::Quit, &[], "Quit", Some("quit")),
+    // Job list
+    entry(P::Jobs, A::Down, &["<Down>"], "Select the next job", Some("navigate")),
+    entry(P::Jobs, A::Up, &["<Up>"], "Select the previous job", Some("navigate")),
+    entry(P::Jobs, A::Top, &["<Home>"], "Select the first job", Some("top/bottom")),
+    entry(P::Jobs, A::Bottom, &["<End>"], "Select the last job", Some("top/bottom")),
+    entry(P::Jobs, A::FocusLog, &[], "Focus the log", None),
+    entry(P::Jobs, A::Help, &["?"], "Show this help", None),
+    entry(P::Jobs, A::ToggleLogs, &[], "Cycle stdout / stderr / both interleaved", Some("toggle")),
+    entry(P::Jobs, A::Refresh, &[], "Refresh now", Some("refresh")),
+    entry(P::Jobs, A::HalfPageDown, &[], "Scroll the log down half a page", Some("scroll log")),
+    entry(P::Jobs, A::HalfPageUp, &[], "Scroll the log up half a page", Some("scroll log")),
+    entry(P::Jobs, A::Mark, &[], "Mark / unmark the selected job", Some("mark")),
+    entry(P::Jobs, A::VisualMark, &[], "Start / finish a visual range selection", Some("mark")),
+    entry(P::Jobs, A::MarkAll, &[], "Mark all jobs (again to clear)", None),
+    entry(P::Jobs, A::CancelJob, &[], "Cancel marked (or selected) jobs", Some("cancel")),
+    entry(P::Jobs, A::HoldJob, &[], "Hold marked (or selected) jobs", Some("hold/release")),
+    entry(P::Jobs, A::ReleaseJob, &[], "Release marked (or selected) jobs", Some("hold/release")),
+    entry(P::Jobs, A::CopyIds, &[], "Copy marked (or selected) job IDs", Some("copy IDs")),
+    entry(P::Jobs, A::EditJob, &[], "Edit the selected job's limits", Some("edit")),
+    entry(P::Jobs, A::SignalJob, &[], "Send a signal to marked (or selected) jobs", Some("signal")),
+    entry(P::Jobs, A::SubmitJob, &[], "Submit a new job (sbatch form)", Some("new/resubmit")),
+    entry(P::Jobs, A::ResubmitJob, &[], "Resubmit the selected job from its batch script", Some("new/resubmit")),
+    entry(P::Jobs, A::NextTab, &[], "Cycle Log, Script and Metrics tabs", Some("tabs")),
+    entry(P::Jobs, A::OpenShell, &[], "Open a shell on the selected running job", Some("shell")),
+    entry(P::Jobs, A::OpenPager, &[], "Open the full log in $PAGER", None),
+    entry(P::Jobs, A::OpenEditor, &[], "Open the full log in $EDITOR", None),
+    // Details
+    entry(P::Details, A::Down, &["<Down>"], "Select the next job", Some("navigate")),
+    entry(P::Details, A::Up, &["<Up>"], "Select the previous job", Some("navigate")),
+    entry(P::Details, A::Help, &["?"], "Show this help", None),
+    entry(P::Details, A::Refresh, &[], "Refresh now", Some("refresh")),
+    // Log
+    entry(P::Log, A::Down, &["<Down>"], "Scroll down", Some("scroll")),
+    entry(P::Log, A::Up, &["<Up>"], "Scroll up", Some("scroll")),
+    entry(P::Log, A::Top, &["<Home>"], "Jump to the start of the file (with a count, to that line)", Some("top/bottom")),
+    entry(P::Log, A::Bottom, &["<End>"], "Jump to the end of the file (with a count, to that line)", Some("top/bottom")),
+    entry(P::Log, A::GotoPercent, &[], "With a count, jump that far into the file (50%)", None),
+    entry(P::Log, A::PageDown, &[], "Page down", None),
+    entry(P::Log, A::PageUp, &[], "Page up", None),
+    entry(P::Log, A::HalfPageDown, &[], "Half-page down", Some("half-page")),
+    entry(P::Log, A::HalfPageUp, &[], "Half-page up", Some("half-page")),
+    entry(P::Log, A::Search, &[], "Regex search forward", Some("search")),
+    entry(P::Log, A::SearchBackward, &[], "Regex search backward", Some("search")),
+    entry(P::Log, A::SearchNext, &[], "Next match", Some("next/prev")),
+    entry(P::Log, A::SearchPrev, &[], "Previous match", Some("next/prev")),
+    entry(P::Log, A::SetMark, &[], "Mark the top line with the next letter (ma)", Some("mark")),
+    entry(P::Log, A::JumpMark, &[], "Jump to a marked line ('a)", None),
+    entry(P::Log, A::PrevError, &[], "Previous error line", Some("errors")),
+    entry(P::Log, A::NextError, &[], "Next error line", Some("errors")),
+    entry(P::Log, A::ToggleLogs, &[], "Cycle stdout / stderr / both interleaved", Some("toggle")),
+    entry(P::Log, A::ToggleWrap, &[], "Toggle line wrapping", Some("wrap")),
+    entry(P::Log, A::ScrollLeft, &["<Left>"], "Scroll unwrapped lines left", None),
+    entry(P::Log, A::ScrollRight, &["<Right>"], "Scroll unwrapped lines right", None),
+    entry(P::Log, A::NextTab, &[], "Cycle Log, Script and Metrics tabs", None),
+    entry(P::Log, A::OpenPager, &[], "Open the full log in $PAGER", Some("pager/editor")),
+    entry(P::Log, A::OpenEditor, &[], "Open the full log in $EDITOR", Some("pager/editor")),
+    entry(P::Log, A::Refresh, &[], "Refresh now", None),
+    // Script tab
+    entry(P::Script, A::Down, &["<Down>"], "Scroll down", Some("scroll")),
+    entry(P::Script, A::Up, &["<Up>"], "Scroll up", Some("scroll")),
+    entry(P::Script, A::Top, &["<Home>"], "Jump to the top", Some("top/bottom")),
+    entry(P::Script, A::Bottom, &["<End>"], "Jump to the bottom", Some("top/bottom")),
+    entry(P::Script, A::PageDown, &[], "Page down", None),
+    entry(P::Script, A::PageUp, &[], "Page up", None),
+    entry(P::Script, A::NextTab, &[], "Cycle Log, Script and Metrics tabs", Some("tabs")),
+    // Metrics tab
+    entry(P::Metrics, A::Down, &["<Down>"], "Chart the next metric", Some("metric")),
+    entry(P::Metrics, A::Up, &["<Up>"], "Chart the previous metric", Some("metric")),
+    entry(P::Metrics, A::ToggleLogs, &[], "Read metrics from stdout / stderr / both", Some("toggle")),
+    entry(P::Metrics, A::NextTab, &[], "Cycle Log, Script and Metrics tabs", Some("tabs")),
+];
+
+/// Entries offered in `panel`, its own first, then the global ones
+pub fn entries_for(panel: Panel) -> impl Iterator<Item = &'static Entry> {
+    let own = REGISTRY.iter().filter(move |e| e.panel == panel);
+    let global = REGISTRY
+        .iter()
+        .filter(move |e| e.panel == Panel::Global && panel != Panel::Global);
+    own.chain(global)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn config_names_round_trip() {
+        for action in ALL_ACTIONS {
+            assert_eq!(Action::from_config_name(action.config_name()), Some(action));
+        }
+        assert_eq!(Action::from_config_name("nope"), None);
+        assert!(REGISTRY.iter().all(|e| ALL_ACTIONS.contains(&e.action)));
+    }
+}
@@ -1,16 +1,295 @@
+use std::collections::{HashMap, HashSet};
 use std::io::Write;
-use std::time::Instant;
+use std::path::PathBuf;
+use std::sync::mpsc::{Receiver, TryRecvError};
+use std::time::{Duration, Instant};
 
 use ratatui::layout::Rect;
 use ratatui::widgets::TableState;
+use regex::Regex;
 
-use crate::config::Config;
-use crate::slurm::{Job, fetch_jobs, fetch_job_details, read_log_file};
+use crate::action::{Action, JOB_MENU, Panel};
+use crate::command::{self, Command, JobFilter, JobSort};
+use crate::config::{Config, SETTINGS};
+use crate::keymap::{KeyPress, Keymap, Lookup};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+use crate::slurm::{
+    EDITABLE_FIELDS, Job, JobState, LikelyCause, LineIndex, LogAnalyzer, LogLocation, LogWindow,
+    MergedLog, MetricExtractor, MetricsScan, PAGE_LINES, Progress, ProgressRequest, ProgressUpdate,
+    SEARCH_SCAN_BYTES, Scan, SignalTarget, SubmitSpec, cancel_jobs, fetch_batch_script,
+    fetch_job_details, fetch_job_fields, fetch_jobs, fetch_partitions, find_after, find_before,
+    hold_jobs, latest_progress, locate_log_file, lookup_sacct_job, open_merged_logs, poll_progress,
+    read_log_file, release_jobs, resubmit_spec, script_with_directives, shell_command, shell_quote,
+    signal_jobs, signal_label, update_job, validate_field, write_temp_script,
+};
+use crate::ui::{Form, FormField, FormOutcome, THEMES, Theme, wrapped_rows};
+
+/// Bounds of the job list's share of the screen, so neither side disappears
+const MIN_JOBS_PERCENT: u16 = 10;
+const MAX_JOBS_PERCENT: u16 = 90;
+/// Largest count prefix, so `99999999j` can't overflow line arithmetic
+const MAX_COUNT: usize = 1_000_000;
+/// Longest gap between the clicks of a double-click
+const DOUBLE_CLICK: Duration = Duration::from_millis(400);
+/// Refreshes to wait for a submitted job to show up in squeue before giving up on selecting it
+const PENDING_SELECT_REFRESHES: u8 = 5;
 
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum FocusPanel {
     Jobs,
     Log,
+    /// The second log pane, when a job is pinned
+    Pinned,
+    Details,
+}
+
+/// Which output stream the log panel shows
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum LogMode {
+    #[default]
+    Stdout,
+    Stderr,
+    /// Both streams interleaved in approximate write order
+    Merged,
+}
+
+impl LogMode {
+    pub fn label(self) -> &'static str {
+        match self {
+            LogMode::Stdout => "stdout",
+            LogMode::Stderr => "stderr",
+            LogMode::Merged => "stdout+stderr",
+        }
+    }
+
+    pub fn next(self) -> Self {
+        match self {
+            LogMode::Stdout => LogMode::Stderr,
+            LogMode::Stderr => LogMode::Merged,
+            LogMode::Merged => LogMode::Stdout,
+        }
+    }
+}
+
+/// What the lower-right preview panel shows
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PreviewTab {
+    Log,
+    /// Read-only batch script from `scontrol write batch_script`
+    Script,
+    /// Training metrics extracted from the log, plotted over steps
+    Metrics,
+}
+
+/// A destructive action waiting for a y/n answer in the status bar
+#[derive(Debug, Clone)]
+pub enum PendingAction {
+    Cancel(Vec<String>),
+    /// Submit an edited copy of a job's batch script
+    Resubmit {
+        job_id: String,
+        script_path: String,
+        workdir: String,
+    },
+}
+
+impl PendingAction {
+    /// Delete the temp script a resubmit was edited in; sbatch keeps its own copy
+    fn remove_script(&self) {
+        if let PendingAction::Resubmit { script_path, .. } = self {
+            let _ = std::fs::remove_file(script_path);
+        }
+    }
+}
+
+/// A program to run in the foreground with the TUI suspended
+#[derive(Debug, Clone)]
+pub struct ExternalCommand {
+    pub program: String,
+    pub args: Vec<String>,
+    /// Prompt to show once the program exits successfully
+    pub on_success: Option<PendingAction>,
+}
+
+/// Popup that captures all keys while open
+#[derive(Debug, Clone)]
+pub enum Modal {
+    /// scontrol update form for a single job
+    EditJob { job_id: String, form: Form },
+    /// Signal picker for the marked (or selected) jobs
+    Signal { job_ids: Vec<String>, form: Form },
+    /// New sbatch submission
+    Submit {
+        form: Form,
+        /// Command line that last passed `sbatch --test-only`; Enter submits it unchanged
+        tested: Option<String>,
+    },
+}
+
+impl Modal {
+    pub fn form(&self) -> &Form {
+        match self {
+            Modal::EditJob { form, .. }
+            | Modal::Signal { form, .. }
+            | Modal::Submit { form, .. } => form,
+        }
+    }
+
+    fn form_mut(&mut self) -> &mut Form {
+        match self {
+            Modal::EditJob { form, .. }
+            | Modal::Signal { form, .. }
+            | Modal::Submit { form, .. } => form,
+        }
+    }
+}
+
+/// Text typed at the `:` command line
+#[derive(Debug, Clone, Default)]
+pub struct CommandInput {
+    pub text: String,
+    /// Tab completions of the text, cycled with Tab / Shift+Tab
+    pub completions: Vec<String>,
+    pub completion: Option<usize>,
+    /// Entry shown while browsing the history with Up / Down
+    history_pos: Option<usize>,
+    /// What was typed before browsing, brought back by Down past the newest entry
+    draft: String,
+}
+
+/// Right-click menu of job actions
+#[derive(Debug, Clone)]
+pub struct ContextMenu {
+    /// Where it was opened
+    pub col: u16,
+    pub row: u16,
+    pub selected: usize,
+    /// Where it was last drawn, for clicks
+    pub area: Rect,
+}
+
+/// Search query being typed after `/` or `?`
+#[derive(Debug, Clone)]
+pub struct SearchInput {
+    pub query: String,
+    pub backward: bool,
+    /// Compiled query, or the regex error to show while typing
+    pub regex: Result<Regex, String>,
+    /// Scroll position to restore if the search is abandoned
+    origin_scroll: usize,
+}
+
+/// Last confirmed log search, repeated with n/N
+#[derive(Debug, Clone)]
+pub struct LogSearch {
+    pub regex: Regex,
+    /// Direction of the original `/` (forward) or `?` (backward)
+    pub backward: bool,
+    /// Byte offset of the line holding the current match
+    pub current: Option<u64>,
+}
+
+/// Window start, end, line count and pattern a match list was computed for
+type MatchCacheKey = (u64, u64, usize, String);
+
+/// A log pane's view state. The main pane's is `App::log`; a pinned pane keeps its own and
+/// has it swapped in while it's drawn or handles input (see `with_pinned`), so paging,
+/// sticky-bottom, search and error jumps work the same in both.
+#[derive(Default)]
+pub struct LogState {
+    /// Loaded part of the current log file
+    pub window: Option<LogWindow>,
+    pub error: Option<String>,
+    pub mode: LogMode,
+    /// Source of each line in `window` while showing the merged view
+    merged: Option<MergedLog>,
+    /// Vertical scroll offset (index of the top line)
+    pub scroll: usize,
+    /// In wrap mode, rows of the top line scrolled past, keyed by that line's offset
+    row: Option<(u64, usize)>,
+    /// Columns scrolled right when lines aren't wrapped
+    pub hscroll: usize,
+    /// Where the pane was last drawn, for mouse hit testing and page sizes
+    pub area: Rect,
+    pub search: Option<LogSearch>,
+    /// Matching line indices in `window`, keyed by the window and pattern they were computed for
+    search_cache: Option<(MatchCacheKey, Vec<usize>)>,
+    error_cache: Option<(MatchCacheKey, Vec<usize>)>,
+    /// Byte offset of the error line last jumped to
+    error_cursor: Option<u64>,
+    /// Which job_id + mode we last loaded log content for
+    last_key: Option<String>,
+    /// job_id + mode of the content currently in `window`
+    loaded_key: Option<String>,
+    /// Line starts of the file last jumped in by line number
+    line_index: LineIndex,
+}
+
+/// A `goto` whose job wasn't listed, waiting on sacct
+struct GotoLookup {
+    id: String,
+    focus_log: bool,
+    result: Receiver<Result<Option<Job>, String>>,
+}
+
+/// A job's log pinned in a second pane for side-by-side comparison
+pub struct PinnedLog {
+    /// Copy taken when pinned, so a run that has left the queue can still be compared
+    pub job: Job,
+    /// This pane's state, or the main pane's while this one is swapped in
+    state: LogState,
+}
+
+/// Log scroll movements, mirrored to the other pane in sync-scroll mode
+#[derive(Debug, Clone, Copy)]
+pub enum Scroll {
+    Down(usize),
+    Up(usize),
+    Top,
+    Bottom,
+}
+
+/// Indices of window lines matching `re`, recomputed only when the window or pattern changes
+fn matching_lines<'a>(
+    cache: &'a mut Option<(MatchCacheKey, Vec<usize>)>,
+    window: &LogWindow,
+    re: &Regex,
+) -> &'a [usize] {
+    let key = (
+        window.start(),
+        window.end,
+        window.lines.len(),
+        re.as_str().to_string(),
+    );
+    if cache.as_ref().is_none_or(|(k, _)| *k != key) {
+        let matches = window
+            .lines
+            .iter()
+            .enumerate()
+            .filter(|(_, l)| re.is_match(l))
+            .map(|(i, _)| i)
+            .collect();
+        *cache = Some((key, matches));
+    }
+    cache.as_ref().map(|(_, m)| m.as_slice()).unwrap_or(&[])
+}
+
+/// Transient status bar message (action results, Slurm errors)
+#[derive(Debug, Clone)]
+pub struct Flash {
+    pub text: String,
+    pub is_error: bool,
+    pub until: Instant,
+}
+
+/// A submitted job to select once squeue lists it
+#[derive(Debug, Clone)]
+struct PendingSelect {
+    job_id: String,
+    /// Job selected at submission; moving off it means the user has moved on
+    from: Option<String>,
+    refreshes_left: u8,
 }
 
 pub struct App {
@@ -18,56 +297,216 @@ pub struct App {
     pub jobs: Vec<Job>,
     pub table_state: TableState,
     pub should_quit: bool,
-    pub log_preview: Option<String>,
-    pub log_error: Option<String>,
-    /// true = show stderr, false = show stdout
-    pub show_stderr: bool,
-    /// Vertical scroll offset for the log preview panel
-    pub log_scroll: u16,
-    /// Total number of lines in the current log preview
-    pub log_line_count: usize,
+    /// The log pane's view state (the pinned pane's while it's swapped in)
+    pub log: LogState,
+    /// Wrap long log lines instead of cutting them at the panel edge
+    pub wrap_logs: bool,
     /// Which panel currently has focus
     pub focus: FocusPanel,
     /// Stored rects for mouse hit testing (set during draw)
     pub job_list_area: Rect,
-    pub log_area: Rect,
     pub details_area: Rect,
+    /// Everything above the status bar, split between the panels
+    pub main_area: Rect,
+    /// Job list share of `main_area` in percent, starting from `[layout] jobs_percent`
+    pub jobs_percent: u16,
+    /// The divider next to the job list is being dragged with the mouse
+    pub dragging_divider: bool,
+    /// The terminal is too narrow for side-by-side panels; one is shown at a time
+    pub compact: bool,
     /// Show "Copied!" feedback until this instant
     pub copy_feedback_until: Option<Instant>,
+    /// Job IDs marked for bulk actions (kept by ID across refreshes)
+    pub marked: HashSet<String>,
+    /// Job ID where the current visual range selection started
+    pub visual_anchor: Option<String>,
+    /// Action waiting for confirmation
+    pub pending_confirm: Option<PendingAction>,
+    /// Result of the last action, shown in the status bar
+    pub flash: Option<Flash>,
+    /// Open popup form, if any
+    pub modal: Option<Modal>,
+    /// Program the event loop should run with the terminal handed over
+    pub external: Option<ExternalCommand>,
+    pub preview_tab: PreviewTab,
+    /// Batch script for the selected job: (job_id, script or error)
+    pub script: Option<(String, Result<String, String>)>,
+    pub script_scroll: u16,
+    /// Search prompt while typing a pattern
+    pub search_input: Option<SearchInput>,
+    /// Error signatures marked in the log and used for failure summaries
+    pub analyzer: LogAnalyzer,
+    /// (job_id, cause) for the last failed job analyzed
+    failure_cause: Option<(String, Option<LikelyCause>)>,
+    /// Latest tqdm/Lightning bars per job, from the last read of its log
+    pub progress: HashMap<String, Vec<Progress>>,
+    /// (job_id, window end) the progress was last parsed at
+    progress_key: Option<(String, u64)>,
+    /// Background read of the listed jobs' log tails for the progress column, while running
+    progress_poll: Option<Receiver<Vec<ProgressUpdate>>>,
+    /// When that read was last started
+    progress_polled_at: Option<Instant>,
+    pub metric_extractor: MetricExtractor,
+    /// Metrics of the shown log, keyed like `LogState::loaded_key`
+    pub metrics: Option<(String, MetricsScan)>,
+    /// Metric plotted in the Metrics tab
+    pub metrics_selected: usize,
+    /// Job to select as soon as it shows up in squeue (e.g. just submitted)
+    pending_select: Option<PendingSelect>,
     /// Track which job_id we last fetched scontrol details for
     last_detail_job_id: Option<String>,
-    /// Track which job_id + mode we last loaded log content for
-    last_log_key: Option<String>,
+    /// Second log pane shown next to the selected job's log
+    pub pinned: Option<PinnedLog>,
+    /// The pinned pane's state is currently swapped into `log`
+    pinned_swapped: bool,
+    /// Stack the pinned pane below the main log instead of beside it
+    pub pinned_stacked: bool,
+    /// Scroll both log panes together
+    pub sync_scroll: bool,
+    /// The focused panel fills the screen (above the status bar)
+    pub zoomed: bool,
+    /// Scroll offset of the key help overlay while it's open
+    pub help_scroll: Option<usize>,
+    /// Key sequences bound to actions, built from `[keybindings]`
+    pub keymap: Keymap,
+    /// Keys typed so far of a multi-key binding ("g" of "gg")
+    pub pending_keys: Vec<KeyPress>,
+    /// Count typed before a log key (the 5 of `5j`)
+    pub count: Option<usize>,
+    /// Set or jump-to mark waiting for its letter
+    pub awaiting_mark: Option<Action>,
+    /// Log marks by letter, per log key (job and stream), as line offsets
+    log_marks: HashMap<String, HashMap<char, u64>>,
+    /// The `:` command line while it's open
+    pub command_input: Option<CommandInput>,
+    /// Earlier command lines, oldest first, saved across sessions
+    command_history: Vec<String>,
+    /// Where the command history is saved; `None` keeps it for this session only
+    history_file: Option<PathBuf>,
+    /// Jobs hidden from the list by `:filter`, and the order set by `:sort`
+    pub job_filter: Option<JobFilter>,
+    pub job_sort: Option<JobSort>,
+    /// Jobs squeue listed before filtering
+    pub total_jobs: usize,
+    /// Every job squeue listed, before `:filter`
+    unfiltered: Vec<Job>,
+    /// Job looked up in sacct by `goto`, kept in the list while it's selected
+    looked_up: Option<String>,
+    /// A `goto` waiting on sacct
+    goto_lookup: Option<GotoLookup>,
+    /// Job actions menu opened by a right-click
+    pub context_menu: Option<ContextMenu>,
+    /// Time and job ID of the last left click, for spotting double-clicks (the ID, so a
+    /// refresh that shifts the rows between the clicks doesn't open another job)
+    last_click: Option<(Instant, String)>,
 }
 
 impl App {
     pub fn new(config: Config) -> Self {
+        let mut app = Self::without_jobs(config, command::history_path());
+        app.refresh_jobs();
+        app
+    }
+
+    /// An app that hasn't run squeue yet, reading and saving its command history at `history_file`
+    pub fn without_jobs(config: Config, history_file: Option<PathBuf>) -> Self {
         let mut table_state = TableState::default();
         table_state.select(Some(0));
+        let (analyzer, mut pattern_errors) =
+            LogAnalyzer::new(&config.analyzer.patterns, config.analyzer.builtin);
+        let (metric_extractor, metric_errors) =
+            MetricExtractor::new(&config.metrics.patterns, config.metrics.builtin);
+        pattern_errors.extend(metric_errors);
+        let (keymap, key_errors) = Keymap::new(&config.keybindings);
+        pattern_errors.extend(key_errors);
+        if Theme::named(&config.display.theme).is_none() {
+            pattern_errors.push(format!(
+                "Unknown theme '{}' (one of {})",
+                config.display.theme,
+                THEMES.join(", ")
+            ));
+        }
 
+        let wrap_logs = config.display.wrap_logs;
+        let jobs_percent = config
+            .layout
+            .jobs_percent
+            .clamp(MIN_JOBS_PERCENT, MAX_JOBS_PERCENT);
         let mut app = Self {
             config,
             jobs: vec![],
             table_state,
             should_quit: false,
-            log_preview: None,
-            log_error: None,
-            show_stderr: false, // default: show stdout like turm
-            log_scroll: 0,
-            log_line_count: 0,
+            log: LogState::default(), // default: show stdout like turm
             focus: FocusPanel::Jobs,
             job_list_area: Rect::default(),
-            log_area: Rect::default(),
             details_area: Rect::default(),
+            main_area: Rect::default(),
+            jobs_percent,
+            dragging_divider: false,
+            compact: false,
             copy_feedback_until: None,
+            marked: HashSet::new(),
+            visual_anchor: None,
+            pending_confirm: None,
+            flash: None,
+            modal: None,
+            pending_select: None,
+            external: None,
+            preview_tab: PreviewTab::Log,
+            script: None,
+            script_scroll: 0,
+            search_input: None,
+            analyzer,
+            failure_cause: None,
+            progress: HashMap::new(),
+            progress_key: None,
+            progress_poll: None,
+            progress_polled_at: None,
+            metric_extractor,
+            metrics: None,
+            metrics_selected: 0,
             last_detail_job_id: None,
-            last_log_key: None,
+            wrap_logs,
+            pinned: None,
+            pinned_swapped: false,
+            pinned_stacked: false,
+            sync_scroll: false,
+            zoomed: false,
+            help_scroll: None,
+            keymap,
+            pending_keys: Vec::new(),
+            count: None,
+            awaiting_mark: None,
+            log_marks: HashMap::new(),
+            command_input: None,
+            command_history: history_file
+                .as_deref()
+                .map(command::load_history)
+                .unwrap_or_default(),
+            history_file,
+            job_filter: None,
+            job_sort: None,
+            total_jobs: 0,
+            unfiltered: Vec::new(),
+            looked_up: None,
+            goto_lookup: None,
+            context_menu: None,
+            last_click: None,
         };
-        app.refresh_jobs();
+        if !pattern_errors.is_empty() {
+            app.flash_message(pattern_errors.join("; "), true);
+        }
         app
     }
 
     pub fn refresh_jobs(&mut self) {
+        let fetched = fetch_jobs(&self.config);
+        self.update_jobs(fetched);
+    }
+
+    /// Replace the job list with a fresh squeue listing, keeping details, marks and the selection
+    fn update_jobs(&mut self, fetched: Vec<Job>) {
         // Collect previously-fetched scontrol details so we can transfer them
         let old_details: Vec<(String, Option<String>, Option<String>, String)> = self.jobs.iter()
             .filter(|j| j.stderr.is_some())
@@ -80,8 +519,29 @@ impl App {
             .collect();
 
         let prev_job_id = self.selected_job().map(|j| j.job_id.clone());
+        // A job from sacct stays only while it's selected
+        let looked_up = self
+            .looked_up
+            .take()
+            .filter(|id| prev_job_id.as_ref() == Some(id))
+            .and_then(|id| self.jobs.iter().find(|j| j.job_id == id).cloned());
 
-        self.jobs = fetch_jobs(&self.config);
+        self.total_jobs = fetched.len();
+        self.unfiltered = fetched.clone();
+        self.jobs = fetched;
+        if let Some(ref filter) = self.job_filter {
+            self.jobs.retain(|j| filter.matches(j));
+        }
+        if let Some(sort) = self.job_sort {
+            sort.sort(&mut self.jobs);
+        }
+        if let Some(job) = looked_up
+            && !self.jobs.iter().any(|j| j.job_id == job.job_id)
+            && self.job_filter.as_ref().is_none_or(|f| f.matches(&job))
+        {
+            self.looked_up = Some(job.job_id.clone());
+            self.jobs.push(job);
+        }
 
         // Transfer scontrol details to new job structs (avoid re-fetching)
         for job in &mut self.jobs {
@@ -97,25 +557,63 @@ impl App {
             }
         }
 
-        // Try to preserve selection by matching job ID (like turm)
-        if let Some(ref prev_id) = prev_job_id {
-            if let Some(new_idx) = self.jobs.iter().position(|j| j.job_id == *prev_id) {
-                self.table_state.select(Some(new_idx));
-                // Same job still selected. Keep details cache, but force a log reload
-                // so output updates over time and retries after transient read failures.
-                self.last_log_key = None;
-                return;
+        // The pinned log re-reads on every refresh too, even after its job left the queue
+        if let Some(pinned) = self.pinned.as_mut() {
+            pinned.state.last_key = None;
+        }
+
+        // Drop marks for jobs that left the queue; ones only hidden by the filter keep theirs
+        let listed = &self.unfiltered;
+        self.marked
+            .retain(|id| listed.iter().any(|j| j.job_id == *id));
+        let jobs = &self.jobs;
+        self.progress
+            .retain(|id, _| jobs.iter().any(|j| j.job_id == *id));
+        let pinned_id = self.pinned.as_ref().map(|p| p.job.job_id.as_str());
+        self.log_marks.retain(|key, _| {
+            let id = key.rsplit_once(':').map_or(key.as_str(), |(id, _)| id);
+            pinned_id == Some(id) || jobs.iter().any(|j| j.job_id == id)
+        });
+        if let Some(ref anchor) = self.visual_anchor
+            && !jobs.iter().any(|j| j.job_id == *anchor)
+        {
+            self.visual_anchor = None;
+        }
+
+        // A freshly submitted job takes over the selection once squeue lists it, unless the
+        // user moved on meanwhile or it never shows up (failed at once, or left out by squeue_args)
+        let mut prev_job_id = prev_job_id;
+        if let Some(mut pending) = self.pending_select.take()
+            && pending.from == prev_job_id
+        {
+            if self.jobs.iter().any(|j| j.job_id == pending.job_id) {
+                prev_job_id = Some(pending.job_id);
+            } else if pending.refreshes_left > 0 {
+                pending.refreshes_left -= 1;
+                self.pending_select = Some(pending);
             }
         }
 
+        // Try to preserve selection by matching job ID (like turm)
+        if let Some(ref prev_id) = prev_job_id
+            && let Some(new_idx) = self.jobs.iter().position(|j| j.job_id == *prev_id)
+        {
+            self.table_state.select(Some(new_idx));
+            // Same job still selected. Keep details cache, but force a log reload
+            // so output updates over time and retries after transient read failures.
+            self.log.last_key = None;
+            return;
+        }
+
         // Job disappeared or no previous selection — clamp index
-        if let Some(selected) = self.table_state.selected() {
-            if selected >= self.jobs.len() && !self.jobs.is_empty() {
-                self.table_state.select(Some(self.jobs.len() - 1));
-            }
+        if let Some(selected) = self.table_state.selected()
+            && selected >= self.jobs.len()
+            && !self.jobs.is_empty()
+        {
+            self.table_state.select(Some(self.jobs.len() - 1));
         }
         self.last_detail_job_id = None;
-        self.last_log_key = None;
+        self.log.last_key = None;
     }
 
     /// Fetch stderr/stdout paths for the selected job if not already loaded
@@ -127,6 +625,7 @@ impl App {
 
         if self.last_detail_job_id.as_deref() == Some(&selected_id) {
             self.ensure_log_loaded();
+            self.record_progress();
             return;
         }
 
@@ -139,85 +638,310 @@ impl App {
             .map(|j| j.tres.trim().is_empty() || j.tres == "N/A")
             .unwrap_or(false);
 
-        if !already_has_details || needs_tres_backfill {
-            if let Some(details) = fetch_job_details(&selected_id) {
-                if let Some(idx) = self.table_state.selected() {
-                    if let Some(job) = self.jobs.get_mut(idx) {
-                        job.stderr = Some(details.stderr);
-                        job.stdout = Some(details.stdout);
-                        if (job.tres.trim().is_empty() || job.tres == "N/A")
-                            && details.tres.is_some()
-                        {
-                            job.tres = details.tres.unwrap_or_default();
-                        }
-                    }
-                }
+        if (!already_has_details || needs_tres_backfill)
+            && let Some(details) = fetch_job_details(&selected_id)
+            && let Some(idx) = self.table_state.selected()
+            && let Some(job) = self.jobs.get_mut(idx)
+        {
+            job.stderr = Some(details.stderr);
+            job.stdout = Some(details.stdout);
+            if (job.tres.trim().is_empty() || job.tres == "N/A") && details.tres.is_some() {
+                job.tres = details.tres.unwrap_or_default();
             }
         }
 
         self.last_detail_job_id = Some(selected_id);
         self.ensure_log_loaded();
+        self.record_progress();
+        self.ensure_failure_cause();
+    }
+
+    /// Number of lines in the loaded log window
+    pub fn log_line_count(&self) -> usize {
+        self.log.window.as_ref().map(|w| w.lines.len()).unwrap_or(0)
+    }
+
+    /// Cells available for log text inside the pane's borders
+    pub fn log_text_width(&self) -> usize {
+        self.log.area.width.saturating_sub(2).max(1) as usize
+    }
+
+    /// Screen rows line `idx` of the window takes (more than one only when wrapping)
+    pub fn log_line_rows(&self, idx: usize) -> usize {
+        match self.log.window.as_ref().and_then(|w| w.lines.get(idx)) {
+            Some(line) if self.wrap_logs => wrapped_rows(line, self.log_text_width()),
+            _ => 1,
+        }
+    }
+
+    /// Rows of the top line scrolled past (always 0 without wrapping)
+    pub fn log_top_row(&self) -> usize {
+        match (self.log.row, self.log.window.as_ref()) {
+            (Some((offset, row)), Some(window))
+                if self.wrap_logs && window.offsets.get(self.log.scroll) == Some(&offset) =>
+            {
+                row.min(self.log_line_rows(self.log.scroll) - 1)
+            }
+            _ => 0,
+        }
+    }
+
+    /// Scroll to row `row` of window line `line`
+    fn set_log_position(&mut self, line: usize, row: usize) {
+        self.log.scroll = line;
+        self.log.row = self
+            .log
+            .window
+            .as_ref()
+            .and_then(|w| w.offsets.get(line))
+            .map(|&o| (o, row));
+    }
+
+    /// Furthest (line, row) position that still fills the viewport
+    fn max_log_position(&self) -> (usize, usize) {
+        let mut remaining = self.log_viewport_lines();
+        let mut idx = self.log_line_count();
+        if !self.wrap_logs {
+            return (idx.saturating_sub(remaining), 0);
+        }
+        while idx > 0 {
+            let rows = self.log_line_rows(idx - 1);
+            if rows >= remaining {
+                return (idx - 1, rows - remaining);
+            }
+            remaining -= rows;
+            idx -= 1;
+        }
+        (0, 0)
+    }
+
+    /// Largest scroll offset that still fills the viewport
+    fn max_log_scroll(&self) -> usize {
+        self.max_log_position().0
     }
 
     /// Whether the log is currently scrolled to the bottom (or close enough)
     pub fn is_at_bottom(&self) -> bool {
-        let viewport_lines = self.log_area.height.saturating_sub(2);
-        let max_scroll = (self.log_line_count as u16).saturating_sub(viewport_lines);
-        self.log_scroll >= max_scroll
+        (self.log.scroll, self.log_top_row()) >= self.max_log_position()
+    }
+
+    /// Index of the last window line at least partly on screen
+    pub fn last_visible_log_line(&self) -> usize {
+        let mut rows = self.log_viewport_lines() + self.log_top_row();
+        let mut idx = self.log.scroll;
+        while idx + 1 < self.log_line_count() {
+            let line_rows = self.log_line_rows(idx);
+            if line_rows >= rows {
+                break;
+            }
+            rows -= line_rows;
+            idx += 1;
+        }
+        idx
+    }
+
+    /// Whether the view is following the end of the file (sticky bottom)
+    fn is_following(&self) -> bool {
+        self.is_at_bottom() && self.log.window.as_ref().is_none_or(|w| w.at_end())
+    }
+
+    /// Whether the shown job writes stdout and stderr to the same file
+    pub fn logs_shared(&self) -> bool {
+        self.log_job().is_some_and(|j| {
+            j.stdout.as_ref().is_some_and(|p| !p.is_empty()) && j.stdout == j.stderr
+        })
     }
 
-    /// Load the log content for the selected job (stdout or stderr based on mode)
-    fn ensure_log_loaded(&mut self) {
-        let log_key = match self.selected_job() {
-            Some(j) => format!("{}:{}", j.job_id, if self.show_stderr { "err" } else { "out" }),
+    /// The log mode actually loaded: merging a file with itself just shows it once
+    fn effective_log_mode(&self) -> LogMode {
+        match self.log.mode {
+            LogMode::Merged if self.logs_shared() => LogMode::Stdout,
+            mode => mode,
+        }
+    }
+
+    /// Caveat about what the log panel shows, for its title
+    pub fn log_mode_note(&self) -> Option<&'static str> {
+        if self.log.mode != LogMode::Stdout && self.logs_shared() {
+            Some("stdout and stderr share this file")
+        } else if self.log.mode == LogMode::Merged
+            && self.log.merged.as_ref().is_some_and(|m| !m.is_live())
+        {
+            Some("read over SSH, stderr after stdout")
+        } else {
+            None
+        }
+    }
+
+    /// Whether each line of the log window came from stderr, when showing the merged view
+    pub fn merged_sources(&self) -> Option<&[bool]> {
+        self.log.merged.as_ref().map(|m| m.from_stderr.as_slice())
+    }
+
+    /// Load the log content for the selected job (stdout, stderr or both based on mode)
+    pub fn ensure_log_loaded(&mut self) {
+        let mode = self.effective_log_mode();
+        let log_key = match self.log_job() {
+            Some(j) => format!(
+                "{}:{}",
+                j.job_id,
+                match mode {
+                    LogMode::Stdout => "out",
+                    LogMode::Stderr => "err",
+                    LogMode::Merged => "both",
+                }
+            ),
             None => return,
         };
 
-        if self.last_log_key.as_deref() == Some(&log_key) {
+        if self.log.last_key.as_deref() == Some(&log_key) {
             return; // same job, same mode — no reload needed
         }
 
-        // Remember if we were at the bottom before loading (for sticky-bottom)
-        let was_at_bottom = self.is_at_bottom() || self.log_preview.is_none();
+        // Remember if we were following the end before loading (for sticky-bottom)
+        let was_following = self.is_following();
+        let same_log = self.log.loaded_key.as_deref() == Some(&log_key);
+        self.log.last_key = Some(log_key.clone());
 
-        let (path, nodelist) = {
-            let job = match self.selected_job() {
-                Some(j) => j,
-                None => return,
-            };
-            let path = if self.show_stderr {
-                job.stderr.clone()
+        if mode == LogMode::Merged {
+            self.load_merged_log(log_key, same_log, was_following);
+            return;
+        }
+        self.log.merged = None;
+
+        // Same local file: pick up new lines in place instead of re-reading the tail,
+        // so a user paging through older output keeps their place
+        if same_log
+            && let Some(window) = self.log.window.as_mut()
+            && window.is_seekable()
+        {
+            let result = if was_following {
+                window.refresh_tail(PAGE_LINES)
             } else {
-                job.stdout.clone()
+                // Only note the new size so scrolling down can page further
+                std::fs::metadata(window.path.as_deref().unwrap_or_default())
+                    .map(|m| window.file_size = m.len())
+                    .map(|_| 0)
             };
-            match path {
-                Some(p) if !p.is_empty() => (p, job.nodelist.clone()),
-                _ => {
-                    self.log_error = Some("No path available".into());
-                    self.log_preview = None;
-                    self.last_log_key = Some(log_key);
-                    return;
+            match result {
+                Ok(dropped) => {
+                    self.log.scroll = self.log.scroll.saturating_sub(dropped);
+                    self.log.error = None;
+                    if was_following {
+                        self.scroll_log_bottom();
+                    }
                 }
+                Err(e) => self.log.error = Some(e.to_string()),
+            }
+            return;
+        }
+
+        let (path, nodelist) = match self.current_log_path() {
+            Some(p) => p,
+            None => {
+                self.log.error = Some("No path available".into());
+                self.log.window = None;
+                self.log.loaded_key = None;
+                return;
             }
         };
 
-        match read_log_file(&path, &nodelist, &self.config, 500) {
-            Ok(content) => {
-                self.log_line_count = content.lines().count();
-                self.log_preview = Some(content);
-                self.log_error = None;
-                if was_at_bottom {
+        match read_log_file(&path, &nodelist, &self.config, PAGE_LINES) {
+            Ok(window) => {
+                self.log.window = Some(window);
+                self.log.error = None;
+                if !same_log {
+                    self.log.error_cursor = None;
+                    if let Some(search) = self.log.search.as_mut() {
+                        search.current = None;
+                    }
+                }
+                // A different log starts at its end (like tail -f); a re-read snapshot
+                // of the same log keeps the user's position unless they were following
+                if !same_log || was_following {
+                    self.scroll_log_bottom();
+                }
+                self.log.loaded_key = Some(log_key);
+            }
+            Err(e) => {
+                self.log.window = None;
+                self.log.error = Some(e.clone());
+                self.log.scroll = 0;
+                self.log.loaded_key = None;
+            }
+        }
+    }
+
+    /// Pick up new lines of the merged view in place, or (re)open both files
+    fn load_merged_log(&mut self, log_key: String, same_log: bool, was_following: bool) {
+        if same_log
+            && let (Some(merged), Some(window)) =
+                (self.log.merged.as_mut(), self.log.window.as_mut())
+            && merged.is_live()
+        {
+            match merged.update(window) {
+                Ok(dropped) => {
+                    self.log.scroll = self.log.scroll.saturating_sub(dropped);
+                    self.log.error = None;
+                    if was_following {
+                        self.scroll_log_bottom();
+                    }
+                }
+                Err(e) => self.log.error = Some(e.to_string()),
+            }
+            return;
+        }
+
+        let opened = match self.log_job() {
+            Some(Job {
+                stdout: Some(out),
+                stderr: Some(err),
+                nodelist,
+                ..
+            }) if !out.is_empty() && !err.is_empty() => {
+                open_merged_logs(out, err, nodelist, &self.config, PAGE_LINES)
+            }
+            _ => Err("No path available".into()),
+        };
+        match opened {
+            Ok((merged, window)) => {
+                self.log.merged = Some(merged);
+                self.log.window = Some(window);
+                self.log.error = None;
+                if !same_log {
+                    self.log.error_cursor = None;
+                    if let Some(search) = self.log.search.as_mut() {
+                        search.current = None;
+                    }
+                }
+                if !same_log || was_following {
                     self.scroll_log_bottom();
                 }
+                self.log.loaded_key = Some(log_key);
             }
             Err(e) => {
-                self.log_preview = None;
-                self.log_error = Some(e.clone());
-                self.log_line_count = 0;
-                self.log_scroll = 0;
+                self.log.merged = None;
+                self.log.window = None;
+                self.log.error = Some(e);
+                self.log.scroll = 0;
+                self.log.loaded_key = None;
             }
         }
-        self.last_log_key = Some(log_key);
+    }
+
+    /// Path of the log currently shown (stdout or stderr) and the node it lives on.
+    /// The merged view opens stdout.
+    pub fn current_log_path(&self) -> Option<(String, String)> {
+        let job = self.log_job()?;
+        let path = if self.log.mode == LogMode::Stderr {
+            job.stderr.as_ref()
+        } else {
+            job.stdout.as_ref()
+        };
+        match path {
+            Some(p) if !p.is_empty() => Some((p.clone(), job.nodelist.clone())),
+            _ => None,
+        }
     }
 
     pub fn selected_job(&self) -> Option<&Job> {
@@ -226,118 +950,2077 @@ impl App {
             .and_then(|i| self.jobs.get(i))
     }
 
+    /// Job whose log the log functions act on: the pinned job while its pane is swapped in
+    pub fn log_job(&self) -> Option<&Job> {
+        if self.pinned_swapped {
+            self.pinned.as_ref().map(|p| &p.job)
+        } else {
+            self.selected_job()
+        }
+    }
+
+    /// Move focus to the next panel on screen
     pub fn cycle_focus(&mut self) {
-        self.focus = match self.focus {
-            FocusPanel::Jobs => FocusPanel::Log,
-            FocusPanel::Log => FocusPanel::Jobs,
-        };
+        loop {
+            self.focus = match self.focus {
+                FocusPanel::Jobs => FocusPanel::Log,
+                FocusPanel::Log if self.pinned_visible() => FocusPanel::Pinned,
+                FocusPanel::Log | FocusPanel::Pinned => FocusPanel::Details,
+                FocusPanel::Details => FocusPanel::Jobs,
+            };
+            if self.panel_shown(self.focus) {
+                break;
+            }
+        }
     }
 
-    pub fn focus_jobs(&mut self) {
-        self.focus = FocusPanel::Jobs;
+    /// Which registry panel's keys apply to the focused panel
+    pub fn key_panel(&self) -> Panel {
+        match self.focus {
+            FocusPanel::Jobs => Panel::Jobs,
+            FocusPanel::Details => Panel::Details,
+            FocusPanel::Log | FocusPanel::Pinned => match self.preview_tab {
+                PreviewTab::Log => Panel::Log,
+                PreviewTab::Script => Panel::Script,
+                PreviewTab::Metrics => Panel::Metrics,
+            },
+        }
     }
 
-    pub fn toggle_log_mode(&mut self) {
-        self.show_stderr = !self.show_stderr;
-        self.last_log_key = None; // force reload
-        self.log_scroll = 0;
+    /// Whether `panel` is part of the layout (the job list always is)
+    pub fn panel_shown(&self, panel: FocusPanel) -> bool {
+        match panel {
+            FocusPanel::Jobs => true,
+            FocusPanel::Details => {
+                self.config.layout.show_details && self.config.display.show_details
+            }
+            FocusPanel::Log => self.config.layout.show_log,
+            FocusPanel::Pinned => self.config.layout.show_log && self.pinned_visible(),
+        }
     }
 
-    pub fn scroll_log_down(&mut self, amount: u16) {
-        let viewport_lines = self.log_area.height.saturating_sub(2);
-        let max_scroll = (self.log_line_count as u16).saturating_sub(viewport_lines);
-        self.log_scroll = (self.log_scroll + amount).min(max_scroll);
+    /// Grow (positive) or shrink the job list's share of the screen
+    pub fn resize_jobs(&mut self, delta: i16) {
+        let percent = (self.jobs_percent as i16 + delta)
+            .clamp(MIN_JOBS_PERCENT as i16, MAX_JOBS_PERCENT as i16);
+        self.jobs_percent = percent as u16;
     }
 
-    pub fn scroll_log_up(&mut self, amount: u16) {
-        self.log_scroll = self.log_scroll.saturating_sub(amount);
+    /// Whether (col, row) is on the border between the job list and the other panels
+    pub fn on_divider(&self, col: u16, row: u16) -> bool {
+        let jobs = self.job_list_area;
+        if self.zoomed_panel().is_some() {
+            return false;
+        }
+        if self.config.layout.is_vertical() {
+            let edge = jobs.y + jobs.height;
+            (edge.saturating_sub(1)..=edge).contains(&row)
+                && col >= jobs.x
+                && col < jobs.x + jobs.width
+        } else {
+            let edge = jobs.x + jobs.width;
+            (edge.saturating_sub(1)..=edge).contains(&col)
+                && row >= jobs.y
+                && row < jobs.y + jobs.height
+        }
     }
 
-    pub fn scroll_log_top(&mut self) {
-        self.log_scroll = 0;
+    /// Move the divider to follow a mouse drag
+    pub fn drag_divider(&mut self, col: u16, row: u16) {
+        let area = self.main_area;
+        let (pos, start, len) = if self.config.layout.is_vertical() {
+            (row, area.y, area.height)
+        } else {
+            (col, area.x, area.width)
+        };
+        if len == 0 {
+            return;
+        }
+        let percent = (pos.saturating_sub(start) as u32 * 100 / len as u32) as u16;
+        self.jobs_percent = percent.clamp(MIN_JOBS_PERCENT, MAX_JOBS_PERCENT);
     }
 
-    pub fn scroll_log_bottom(&mut self) {
-        // Subtract viewport height (log_area height minus 2 for borders)
-        // so the last line appears at the bottom of the panel, not the top
-        let viewport_lines = self.log_area.height.saturating_sub(2);
-        self.log_scroll = (self.log_line_count as u16).saturating_sub(viewport_lines);
+    /// Feed a key press to the focused panel's keymap. Returns the action once a binding is
+    /// complete; a key that can't continue the pending sequence starts a new one.
+    pub fn feed_key(&mut self, key: KeyEvent) -> Option<Action> {
+        let key = KeyPress::from_event(key);
+        let panel = self.key_panel();
+        self.pending_keys.push(key);
+        match self.keymap.lookup(panel, &self.pending_keys) {
+            Lookup::Action(action) => {
+                self.pending_keys.clear();
+                Some(action)
+            }
+            Lookup::Pending => None,
+            Lookup::None if self.pending_keys.len() > 1 => {
+                self.pending_keys.clear();
+                self.feed_key_press(panel, key)
+            }
+            Lookup::None => {
+                self.pending_keys.clear();
+                None
+            }
+        }
     }
 
-    pub fn next_job(&mut self) {
-        if self.jobs.is_empty() { return; }
-        let i = match self.table_state.selected() {
-            Some(i) => if i >= self.jobs.len() - 1 { 0 } else { i + 1 },
-            None => 0,
+    fn feed_key_press(&mut self, panel: Panel, key: KeyPress) -> Option<Action> {
+        match self.keymap.lookup(panel, &[key]) {
+            Lookup::Action(action) => Some(action),
+            Lookup::Pending => {
+                self.pending_keys.push(key);
+                None
+            }
+            Lookup::None => None,
+        }
+    }
+
+    /// Take a digit as part of a count in the log or script, unless the digit is bound there.
+    /// A leading 0 is never a count.
+    pub fn push_count_digit(&mut self, key: KeyEvent) -> bool {
+        let digit = match key.code {
+            KeyCode::Char(c) if key.modifiers.difference(KeyModifiers::SHIFT).is_empty() => {
+                c.to_digit(10)
+            }
+            _ => None,
         };
-        self.table_state.select(Some(i));
+        let Some(digit) = digit else { return false };
+        let panel = self.key_panel();
+        if !matches!(panel, Panel::Log | Panel::Script)
+            || !self.pending_keys.is_empty()
+            || (digit == 0 && self.count.is_none())
+            || self.keymap.lookup(panel, &[KeyPress::from_event(key)]) != Lookup::None
+        {
+            return false;
+        }
+        let count = self
+            .count
+            .unwrap_or(0)
+            .saturating_mul(10)
+            .saturating_add(digit as usize);
+        self.count = Some(count.min(MAX_COUNT));
+        true
     }
 
-    pub fn previous_job(&mut self) {
-        if self.jobs.is_empty() { return; }
-        let i = match self.table_state.selected() {
-            Some(i) => if i == 0 { self.jobs.len() - 1 } else { i - 1 },
-            None => 0,
+    pub fn toggle_help(&mut self) {
+        self.help_scroll = match self.help_scroll {
+            Some(_) => None,
+            None => Some(0),
         };
-        self.table_state.select(Some(i));
     }
 
-    pub fn select_first(&mut self) {
-        if !self.jobs.is_empty() {
-            self.table_state.select(Some(0));
+    /// Scroll the help overlay; clamped to its length when drawn
+    pub fn scroll_help(&mut self, delta: isize) {
+        if let Some(ref mut scroll) = self.help_scroll {
+            *scroll = scroll.saturating_add_signed(delta);
         }
     }
 
-    pub fn select_last(&mut self) {
-        if !self.jobs.is_empty() {
-            self.table_state.select(Some(self.jobs.len() - 1));
+    pub fn toggle_zoom(&mut self) {
+        self.zoomed = !self.zoomed;
+    }
+
+    /// The panel filling the screen (always the focused one in compact mode, where zoom
+    /// hides the tab row instead); both log panes zoom together
+    pub fn zoomed_panel(&self) -> Option<FocusPanel> {
+        (self.zoomed || self.compact).then_some(self.focus)
+    }
+
+    /// Whether the pinned pane is on screen (it's only shown with the Log tab)
+    pub fn pinned_visible(&self) -> bool {
+        self.pinned.is_some() && self.preview_tab == PreviewTab::Log
+    }
+
+    /// Area of the pinned pane for mouse hit testing
+    pub fn pinned_area(&self) -> Option<Rect> {
+        match self.pinned {
+            Some(ref p) if self.pinned_visible() && !self.pinned_swapped => Some(p.state.area),
+            _ => None,
         }
     }
 
-    /// Copy text to system clipboard via OSC 52 escape sequence (works in iTerm2, most modern terminals)
-    pub fn copy_to_clipboard(&mut self, text: &str) {
-        let encoded = base64_encode(text.as_bytes());
-        let osc = format!("\x1b]52;c;{}\x07", encoded);
-        let _ = std::io::stdout().write_all(osc.as_bytes());
-        let _ = std::io::stdout().flush();
-        self.copy_feedback_until = Some(Instant::now() + std::time::Duration::from_secs(2));
+    /// Whether the log pane currently swapped in has focus
+    pub fn log_pane_focused(&self) -> bool {
+        self.focus
+            == if self.pinned_swapped {
+                FocusPanel::Pinned
+            } else {
+                FocusPanel::Log
+            }
     }
 
-    /// Handle a click in the details panel — copy stderr/stdout path if on those rows
-    pub fn handle_details_click(&mut self, _col: u16, row: u16) {
-        let inner_y = self.details_area.y + 1; // skip top border
-        let stderr_row = inner_y + 6;
-        let stdout_row = inner_y + 7;
+    /// Log mode of the focused log pane
+    pub fn focused_log_mode(&self) -> LogMode {
+        match self.pinned {
+            Some(ref p) if self.focus == FocusPanel::Pinned && !self.pinned_swapped => p.state.mode,
+            _ => self.log.mode,
+        }
+    }
 
-        if let Some(job) = self.selected_job() {
-            if row == stderr_row {
-                if let Some(ref path) = job.stderr {
-                    let path = path.clone();
-                    self.copy_to_clipboard(&path);
-                }
-            } else if row == stdout_row {
-                if let Some(ref path) = job.stdout {
-                    let path = path.clone();
-                    self.copy_to_clipboard(&path);
-                }
+    /// Whether the pinned pane's state is the one in `log`
+    pub fn is_pinned_pane(&self) -> bool {
+        self.pinned_swapped
+    }
+
+    /// Pin the selected job's log into a second pane, or unpin it if it's already pinned
+    pub fn toggle_pin(&mut self) {
+        let job = match self.selected_job() {
+            Some(j) => j.clone(),
+            None => return,
+        };
+        if self
+            .pinned
+            .as_ref()
+            .is_some_and(|p| p.job.job_id == job.job_id)
+        {
+            self.pinned = None;
+            if self.focus == FocusPanel::Pinned {
+                self.focus = FocusPanel::Log;
             }
+            self.flash_message(format!("Unpinned job {}", job.job_id), false);
+            return;
         }
+        self.flash_message(format!("Pinned job {}'s log", job.job_id), false);
+        self.pinned = Some(PinnedLog {
+            job,
+            state: LogState {
+                mode: self.log.mode,
+                ..LogState::default()
+            },
+        });
+        self.preview_tab = PreviewTab::Log;
     }
 
-    /// Whether we should show copy feedback right now
-    pub fn showing_copy_feedback(&self) -> bool {
-        self.copy_feedback_until
-            .map(|t| Instant::now() < t)
-            .unwrap_or(false)
+    pub fn toggle_pinned_stacked(&mut self) {
+        self.pinned_stacked = !self.pinned_stacked;
     }
-}
 
-fn base64_encode(input: &[u8]) -> String {
-    const CHARS: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
-    let mut result = String::with_capacity((input.len() + 2) / 3 * 4);
-    for chunk in input.chunks(3) {
-        let b0 = chunk[0] as u32;
-        let b1 = chunk.get(1).copied().unwrap_or(0) as u32;
+    pub fn toggle_sync_scroll(&mut self) {
+        self.sync_scroll = !self.sync_scroll;
+        let state = if self.sync_scroll { "on" } else { "off" };
+        self.flash_message(format!("Synchronized scrolling {}", state), false);
+    }
+
+    /// Run `f` with the pinned pane's log state swapped in for the main pane's
+    pub fn with_pinned<R>(&mut self, f: impl FnOnce(&mut App) -> R) -> Option<R> {
+        self.pinned.as_ref()?;
+        self.swap_panes();
+        let result = f(self);
+        self.swap_panes();
+        Some(result)
+    }
+
+    /// Exchange `log` with the pinned pane's stored state
+    fn swap_panes(&mut self) {
+        if let Some(pinned) = self.pinned.as_mut() {
+            std::mem::swap(&mut self.log, &mut pinned.state);
+            self.pinned_swapped = !self.pinned_swapped;
+        }
+    }
+
+    /// Scroll the log pane, and the other one too in sync-scroll mode
+    pub fn scroll_log(&mut self, motion: Scroll) {
+        self.apply_scroll(motion);
+        if self.sync_scroll && self.pinned_visible() {
+            self.swap_panes();
+            self.apply_scroll(motion);
+            self.swap_panes();
+        }
+    }
+
+    fn apply_scroll(&mut self, motion: Scroll) {
+        match motion {
+            Scroll::Down(n) => self.scroll_log_down(n),
+            Scroll::Up(n) => self.scroll_log_up(n),
+            Scroll::Top => self.scroll_log_top(),
+            Scroll::Bottom => self.scroll_log_bottom(),
+        }
+    }
+
+    /// Keep the scroll offset within the log now that the viewport size is known
+    pub fn clamp_log_scroll(&mut self) {
+        let max = self.max_log_position();
+        if (self.log.scroll, self.log_top_row()) > max {
+            self.set_log_position(max.0, max.1);
+        }
+    }
+
+    /// Switch between wrapping long lines and scrolling them horizontally
+    pub fn toggle_wrap(&mut self) {
+        self.wrap_logs = !self.wrap_logs;
+        self.log.row = None;
+        self.log.hscroll = 0;
+        self.clamp_log_scroll();
+        let state = if self.wrap_logs { "on" } else { "off" };
+        self.flash_message(format!("Line wrapping {}", state), false);
+    }
+
+    /// Scroll long lines sideways (no-wrap mode); clamped to the widest visible line when drawn
+    pub fn scroll_log_right(&mut self, amount: usize) {
+        if !self.wrap_logs {
+            self.log.hscroll += amount;
+        }
+    }
+
+    pub fn scroll_log_left(&mut self, amount: usize) {
+        self.log.hscroll = self.log.hscroll.saturating_sub(amount);
+    }
+
+    pub fn focus_jobs(&mut self) {
+        self.focus = FocusPanel::Jobs;
+    }
+
+    pub fn toggle_log_mode(&mut self) {
+        self.log.mode = self.log.mode.next();
+        self.log.last_key = None; // force reload
+        self.log.scroll = 0;
+    }
+
+    /// Report a failed page read without discarding what's already loaded
+    fn page_error(&mut self, e: std::io::Error) {
+        self.flash_message(format!("Log read failed: {}", e), true);
+    }
+
+    pub fn scroll_log_down(&mut self, amount: usize) {
+        // Page in newer lines when scrolling past the end of a window mid-file
+        if self.log.scroll + amount > self.max_log_scroll()
+            && let Some(window) = self.log.window.as_mut()
+            && !window.at_end()
+        {
+            match window.load_later(PAGE_LINES) {
+                Ok((_, dropped)) => self.log.scroll = self.log.scroll.saturating_sub(dropped),
+                Err(e) => self.page_error(e),
+            }
+        }
+        if !self.wrap_logs {
+            self.log.scroll = (self.log.scroll + amount).min(self.max_log_scroll());
+            return;
+        }
+        // Wrapped lines scroll by screen row
+        let max = self.max_log_position();
+        let (mut line, mut row) = (self.log.scroll, self.log_top_row());
+        for _ in 0..amount {
+            if (line, row) >= max {
+                break;
+            }
+            if row + 1 < self.log_line_rows(line) {
+                row += 1;
+            } else {
+                line += 1;
+                row = 0;
+            }
+        }
+        self.set_log_position(line, row);
+    }
+
+    pub fn scroll_log_up(&mut self, amount: usize) {
+        // Page in older lines when scrolling above the top of the window
+        if amount > self.log.scroll
+            && let Some(window) = self.log.window.as_mut()
+            && !window.at_start()
+        {
+            match window.load_earlier(PAGE_LINES) {
+                Ok(added) => self.log.scroll += added,
+                Err(e) => self.page_error(e),
+            }
+        }
+        if !self.wrap_logs {
+            self.log.scroll = self.log.scroll.saturating_sub(amount);
+            return;
+        }
+        let (mut line, mut row) = (self.log.scroll, self.log_top_row());
+        for _ in 0..amount {
+            if row > 0 {
+                row -= 1;
+            } else if line > 0 {
+                line -= 1;
+                row = self.log_line_rows(line) - 1;
+            } else {
+                break;
+            }
+        }
+        self.set_log_position(line, row);
+    }
+
+    /// Jump to the first line of the file
+    pub fn scroll_log_top(&mut self) {
+        if self.log.window.as_ref().is_some_and(|w| !w.at_start()) {
+            self.jump_log_to_fraction(0.0);
+        }
+        self.log.scroll = 0;
+    }
+
+    /// Replace the window with the last page of the file
+    fn load_log_tail(&mut self) {
+        let path = match self.log.window.as_ref().and_then(|w| w.path.clone()) {
+            Some(p) => p,
+            None => return,
+        };
+        match LogWindow::tail(&path, PAGE_LINES) {
+            Ok(tail) => self.log.window = Some(tail),
+            Err(e) => self.page_error(e),
+        }
+    }
+
+    /// Jump to the last line of the file
+    pub fn scroll_log_bottom(&mut self) {
+        if self.log.window.as_ref().is_some_and(|w| !w.at_end()) {
+            self.load_log_tail();
+        }
+        // Subtract viewport height (log area height minus 2 for borders)
+        // so the last line appears at the bottom of the panel, not the top
+        let (line, row) = self.max_log_position();
+        self.set_log_position(line, row);
+    }
+
+    /// Re-position the log window at a fraction (0.0–1.0) of the file by byte offset.
+    /// Seeks directly, so it's instant regardless of file size.
+    pub fn jump_log_to_fraction(&mut self, fraction: f64) {
+        let path = match self.log.window.as_ref().and_then(|w| w.path.clone()) {
+            Some(p) => p,
+            None => return,
+        };
+        if fraction >= 1.0 {
+            self.load_log_tail();
+            self.scroll_log_bottom();
+            return;
+        }
+        match LogWindow::at_fraction(&path, fraction, PAGE_LINES) {
+            Ok(window) => {
+                self.log.window = Some(window);
+                self.log.scroll = 0;
+            }
+            Err(e) => self.page_error(e),
+        }
+    }
+
+    fn log_viewport_lines(&self) -> usize {
+        self.log.area.height.saturating_sub(2) as usize
+    }
+
+    /// Lines scrolled by a page of the log or script (the viewport less one line of overlap)
+    pub fn page_lines(&self) -> usize {
+        self.log_viewport_lines().saturating_sub(1).max(1)
+    }
+
+    /// Go to 1-based line `line` of the file. Seeks there in a local file; snapshots and the
+    /// merged view count from the first loaded line.
+    pub fn goto_log_line(&mut self, line: usize) {
+        let Some(window) = self.log.window.as_ref() else {
+            return;
+        };
+        let line = line.max(1);
+        let idx = match (window.first_line, window.path.clone()) {
+            (Some(base), _) if line >= base && line - base < window.lines.len() => line - base,
+            (_, Some(path)) => {
+                match LogWindow::at_line(&path, line, PAGE_LINES, &mut self.log.line_index) {
+                    Ok(window) => {
+                        let idx = line.saturating_sub(window.first_line.unwrap_or(1));
+                        self.log.window = Some(window);
+                        idx
+                    }
+                    Err(e) => return self.page_error(e),
+                }
+            }
+            (_, None) => line - 1,
+        };
+        self.scroll_log_to_line(idx.min(self.log_line_count().saturating_sub(1)));
+    }
+
+    /// Go `percent` of the way into the file, by size like the scrollbar
+    pub fn goto_log_percent(&mut self, percent: usize) {
+        let Some(window) = self.log.window.as_ref() else {
+            return;
+        };
+        let fraction = percent.min(100) as f64 / 100.0;
+        if window.is_seekable() && !window.is_complete() {
+            self.jump_log_to_fraction(fraction);
+            return;
+        }
+        let idx = (window.lines.len() as f64 * fraction) as usize;
+        self.scroll_log_to_line(idx.min(self.log_line_count().saturating_sub(1)));
+    }
+
+    /// Remember the top line of the log under `letter`
+    pub fn set_log_mark(&mut self, letter: char) {
+        let offset = self
+            .log
+            .window
+            .as_ref()
+            .and_then(|w| w.offsets.get(self.log.scroll).copied());
+        let (Some(key), Some(offset)) = (self.log.loaded_key.clone(), offset) else {
+            return;
+        };
+        self.log_marks
+            .entry(key)
+            .or_default()
+            .insert(letter, offset);
+        self.flash_message(format!("Mark '{}' set", letter), false);
+    }
+
+    /// Bring a marked line back to the top, re-reading that part of the file if needed
+    pub fn jump_log_mark(&mut self, letter: char) {
+        let offset = self
+            .log
+            .loaded_key
+            .as_ref()
+            .and_then(|k| self.log_marks.get(k)?.get(&letter).copied());
+        let Some(offset) = offset else {
+            return self.flash_message(format!("Mark '{}' not set", letter), true);
+        };
+        let Some(window) = self.log.window.as_ref() else {
+            return;
+        };
+        if window.index_of(offset).is_none() {
+            match window.path.clone() {
+                Some(path) => match LogWindow::around(&path, offset, PAGE_LINES) {
+                    Ok(window) => self.log.window = Some(window),
+                    Err(e) => return self.page_error(e),
+                },
+                None => {
+                    return self
+                        .flash_message(format!("Mark '{}' is no longer loaded", letter), true);
+                }
+            }
+        }
+        if let Some(idx) = self.log.window.as_ref().and_then(|w| w.index_of(offset)) {
+            self.set_log_position(idx, 0);
+            self.clamp_log_scroll();
+        }
+    }
+
+    /// Scroll so line `idx` of the window sits in the upper third of the viewport
+    fn scroll_log_to_line(&mut self, idx: usize) {
+        if self.wrap_logs {
+            self.set_log_position(idx, 0);
+            self.scroll_log_up(self.log_viewport_lines() / 3);
+            self.clamp_log_scroll();
+            return;
+        }
+        self.log.scroll = idx
+            .saturating_sub(self.log_viewport_lines() / 3)
+            .min(self.max_log_scroll());
+    }
+
+    /// Colors for the configured theme
+    pub fn theme(&self) -> Theme {
+        Theme::named(&self.config.display.theme).unwrap_or_default()
+    }
+
+    /// Open the command line with `text` already typed
+    pub fn open_command_line(&mut self, text: &str) {
+        self.command_input = Some(CommandInput {
+            text: text.to_string(),
+            ..CommandInput::default()
+        });
+    }
+
+    /// Edit the command line. Returns the action to run when a command names one.
+    pub fn handle_command_key(&mut self, key: KeyEvent) -> Option<Action> {
+        let input = self.command_input.as_mut()?;
+        match key.code {
+            KeyCode::Esc => self.command_input = None,
+            KeyCode::Enter => {
+                let text = self.command_input.take()?.text;
+                return self.run_command_line(text.trim());
+            }
+            // Backspace on an empty line closes it, like vim
+            KeyCode::Backspace if input.text.is_empty() => self.command_input = None,
+            KeyCode::Backspace => {
+                input.text.pop();
+                input.completions.clear();
+            }
+            KeyCode::Tab | KeyCode::BackTab => {
+                if input.completions.is_empty() {
+                    input.completions = command::complete(&input.text, &self.jobs);
+                    input.completion = None;
+                }
+                let count = input.completions.len();
+                if count == 0 {
+                    return None;
+                }
+                let next = match (input.completion, key.code) {
+                    (None, KeyCode::BackTab) => count - 1,
+                    (None, _) => 0,
+                    (Some(i), KeyCode::BackTab) => (i + count - 1) % count,
+                    (Some(i), _) => (i + 1) % count,
+                };
+                input.completion = Some(next);
+                input.text = input.completions[next].clone();
+                // A single match is final; Tab again completes its arguments
+                if count == 1 {
+                    input.completions.clear();
+                }
+            }
+            KeyCode::Up | KeyCode::Down => {
+                let len = self.command_history.len();
+                let pos = match (input.history_pos, key.code) {
+                    (None, KeyCode::Up) if len > 0 => {
+                        input.draft = input.text.clone();
+                        Some(len - 1)
+                    }
+                    (Some(p), KeyCode::Up) => Some(p.saturating_sub(1)),
+                    (Some(p), KeyCode::Down) if p + 1 < len => Some(p + 1),
+                    (Some(_), KeyCode::Down) => None,
+                    // Not browsing: nothing to move to
+                    _ => return None,
+                };
+                input.history_pos = pos;
+                input.text = match pos {
+                    Some(p) => self.command_history[p].clone(),
+                    None => std::mem::take(&mut input.draft),
+                };
+                input.completions.clear();
+            }
+            KeyCode::Char(c) if !key.modifiers.contains(KeyModifiers::CONTROL) => {
+                input.text.push(c);
+                input.completions.clear();
+            }
+            _ => {}
+        }
+        None
+    }
+
+    fn run_command_line(&mut self, line: &str) -> Option<Action> {
+        if line.is_empty() {
+            return None;
+        }
+        let cmd = match command::parse(line) {
+            Ok(cmd) => cmd,
+            Err(e) => {
+                self.flash_message(e, true);
+                return None;
+            }
+        };
+        // Only lines that parse are worth recalling
+        self.command_history.retain(|l| l != line);
+        self.command_history.push(line.to_string());
+        if let Some(path) = &self.history_file
+            && let Err(e) = command::save_history(path, &self.command_history)
+        {
+            self.flash_message(format!("Can't save command history: {}", e), true);
+        }
+        self.run_command(cmd)
+    }
+
+    fn run_command(&mut self, cmd: Command) -> Option<Action> {
+        match cmd {
+            Command::Action(action) => return Some(action),
+            Command::Filter(filter) => {
+                let text = filter
+                    .as_ref()
+                    .map_or("Filter cleared".to_string(), |f| format!("Filter: {}", f));
+                self.job_filter = filter;
+                self.refresh_jobs();
+                self.flash_message(text, false);
+            }
+            Command::Sort(sort) => {
+                let text = sort.map_or("Sorted as squeue lists jobs".to_string(), |s| {
+                    format!("Sorted by {}", s)
+                });
+                self.job_sort = sort;
+                self.refresh_jobs();
+                self.flash_message(text, false);
+            }
+            Command::Set(key, None) => match self.config.get(&key) {
+                Some(value) => self.flash_message(format!("{} = {}", key, value), false),
+                None => self.flash_message(
+                    format!("Unknown setting '{}' (one of {})", key, SETTINGS.join(", ")),
+                    true,
+                ),
+            },
+            Command::Set(key, Some(value)) => match self.config.set(&key, &value) {
+                Ok(()) => {
+                    if self.wrap_logs != self.config.display.wrap_logs {
+                        self.toggle_wrap();
+                    }
+                    if key.ends_with("all_users") || key.ends_with("squeue_args") {
+                        self.refresh_jobs();
+                    }
+                    if !self.panel_shown(self.focus) {
+                        self.focus = FocusPanel::Jobs;
+                    }
+                    self.flash_message(format!("{} = {}", key, value), false);
+                }
+                Err(e) => self.flash_message(e, true),
+            },
+            Command::Goto(id) => {
+                self.goto_job(&id, false);
+            }
+            Command::Theme(name) => match self.config.set("theme", &name) {
+                Ok(()) => self.flash_message(format!("Theme {}", name), false),
+                Err(e) => self.flash_message(e, true),
+            },
+        }
+        None
+    }
+
+    /// Select a job by exact ID, or the first whose ID starts with `id`. A job squeue doesn't
+    /// list is looked up in sacct in the background (see `collect_goto`) and added to the
+    /// list while it stays selected. `focus_log` focuses its log once it's selected.
+    pub fn goto_job(&mut self, id: &str, focus_log: bool) {
+        let id = id.trim();
+        if id.is_empty() {
+            return self.flash_message("No job ID given".to_string(), true);
+        }
+        if self.select_job_id(id) {
+            if focus_log {
+                self.focus = FocusPanel::Log;
+            }
+            return;
+        }
+        if let Some(job) = self.unfiltered.iter().find(|j| j.job_id.starts_with(id)) {
+            let text = format!(
+                "Job {} is hidden by the filter (:filter clears it)",
+                job.job_id
+            );
+            return self.flash_message(text, true);
+        }
+        self.flash_message(format!("Looking up job {} in sacct…", id), false);
+        self.goto_lookup = Some(GotoLookup {
+            id: id.to_string(),
+            focus_log,
+            result: lookup_sacct_job(id),
+        });
+    }
+
+    /// Select `job_id` once squeue lists it, if the selection hasn't moved by then
+    fn select_when_listed(&mut self, job_id: String) {
+        let from = self.selected_job().map(|j| j.job_id.clone());
+        self.pending_select = Some(PendingSelect {
+            job_id,
+            from,
+            refreshes_left: PENDING_SELECT_REFRESHES,
+        });
+    }
+
+    /// Select the listed job `id`, or the first whose ID starts with it. Returns whether one was.
+    fn select_job_id(&mut self, id: &str) -> bool {
+        if let Some(idx) = self.jobs.iter().position(|j| j.job_id == id) {
+            self.table_state.select(Some(idx));
+            return true;
+        }
+        let matches: Vec<usize> = (0..self.jobs.len())
+            .filter(|&i| self.jobs[i].job_id.starts_with(id))
+            .collect();
+        let Some(&idx) = matches.first() else {
+            return false;
+        };
+        self.table_state.select(Some(idx));
+        if matches.len() > 1 {
+            let text = format!(
+                "{} jobs start with {}; selected {}",
+                matches.len(),
+                id,
+                self.jobs[idx].job_id
+            );
+            self.flash_message(text, false);
+        }
+        true
+    }
+
+    /// Take in the sacct lookup of a `goto` once it's done
+    pub fn collect_goto(&mut self) {
+        let result = match self.goto_lookup.as_ref().map(|l| l.result.try_recv()) {
+            Some(Ok(result)) => result,
+            Some(Err(TryRecvError::Empty)) | None => return,
+            Some(Err(TryRecvError::Disconnected)) => Err("the lookup stopped".to_string()),
+        };
+        if let Some(lookup) = self.goto_lookup.take() {
+            self.finish_goto(&lookup.id, lookup.focus_log, result);
+        }
+    }
+
+    fn finish_goto(&mut self, id: &str, focus_log: bool, result: Result<Option<Job>, String>) {
+        let job = match result {
+            Ok(Some(job)) => job,
+            Ok(None) => return self.flash_message(format!("No job {}", id), true),
+            Err(e) => {
+                return self.flash_message(
+                    format!("Job {} isn't in the list and sacct failed: {}", id, e),
+                    true,
+                );
+            }
+        };
+        if self.job_filter.as_ref().is_some_and(|f| !f.matches(&job)) {
+            let text = format!(
+                "Job {} is hidden by the filter (:filter clears it)",
+                job.job_id
+            );
+            return self.flash_message(text, true);
+        }
+        // squeue may have listed it while sacct was running
+        match self.jobs.iter().position(|j| j.job_id == job.job_id) {
+            Some(idx) => self.table_state.select(Some(idx)),
+            None => {
+                self.flash_message(
+                    format!(
+                        "Job {} isn't in the queue; showing it from sacct",
+                        job.job_id
+                    ),
+                    false,
+                );
+                self.jobs
+                    .retain(|j| Some(&j.job_id) != self.looked_up.as_ref());
+                self.looked_up = Some(job.job_id.clone());
+                self.jobs.push(job);
+                self.table_state.select(Some(self.jobs.len() - 1));
+            }
+        }
+        if focus_log {
+            self.focus = FocusPanel::Log;
+        }
+    }
+
+    /// Open the search prompt (`/` forward, `?` backward)
+    pub fn start_search(&mut self, backward: bool) {
+        self.search_input = Some(SearchInput {
+            query: String::new(),
+            backward,
+            regex: Err(String::new()),
+            origin_scroll: self.log.scroll,
+        });
+    }
+
+    /// Regex whose matches are highlighted: the one being typed (in the focused pane), else the last search
+    pub fn highlight_regex(&self) -> Option<&Regex> {
+        match self.search_input {
+            Some(ref input) if self.log_pane_focused() => input.regex.as_ref().ok(),
+            _ => self.log.search.as_ref().map(|s| &s.regex),
+        }
+    }
+
+    /// Indices of window lines matching the highlighted regex (cached per window and pattern)
+    pub fn search_matches(&mut self) -> &[usize] {
+        let re = self.highlight_regex().cloned();
+        match (re, self.log.window.as_ref()) {
+            (Some(re), Some(window)) => matching_lines(&mut self.log.search_cache, window, &re),
+            _ => &[],
+        }
+    }
+
+    /// Window index of the current match, if it's loaded
+    pub fn current_match_index(&self) -> Option<usize> {
+        let offset = self.log.search.as_ref()?.current?;
+        self.log.window.as_ref()?.index_of(offset)
+    }
+
+    pub fn handle_search_key(&mut self, key: KeyEvent) {
+        let input = match self.search_input.as_mut() {
+            Some(i) => i,
+            None => return,
+        };
+        match key.code {
+            KeyCode::Esc => {
+                self.log.scroll = input.origin_scroll;
+                self.search_input = None;
+                return;
+            }
+            KeyCode::Enter => {
+                let input = self.search_input.take().unwrap();
+                match input.regex {
+                    Ok(regex) => {
+                        self.log.search = Some(LogSearch {
+                            regex,
+                            backward: input.backward,
+                            current: None,
+                        });
+                        self.log.scroll = input.origin_scroll;
+                        self.search_next(false);
+                    }
+                    Err(e) if !input.query.is_empty() => self.flash_message(e, true),
+                    Err(_) => {}
+                }
+                return;
+            }
+            KeyCode::Backspace => {
+                if input.query.pop().is_none() {
+                    self.log.scroll = input.origin_scroll;
+                    self.search_input = None;
+                    return;
+                }
+            }
+            KeyCode::Char('u') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                input.query.clear()
+            }
+            KeyCode::Char(c) if !key.modifiers.contains(KeyModifiers::CONTROL) => {
+                input.query.push(c)
+            }
+            _ => return,
+        }
+        input.regex = if input.query.is_empty() {
+            Err(String::new())
+        } else {
+            Regex::new(&input.query)
+                .map_err(|e| e.to_string().lines().last().unwrap_or_default().to_string())
+        };
+
+        // Incremental: preview the first match in the loaded window from where the search started
+        let (origin, backward) = (input.origin_scroll, input.backward);
+        self.log.scroll = origin;
+        let viewport = self.log_viewport_lines();
+        let matches = self.search_matches();
+        let hit = if backward {
+            matches
+                .iter()
+                .rev()
+                .find(|&&i| i < origin + viewport)
+                .copied()
+        } else {
+            matches.iter().find(|&&i| i >= origin).copied()
+        };
+        if let Some(idx) = hit {
+            self.reveal_log_line(idx);
+        }
+    }
+
+    /// Jump to the next match of the last search (`reverse` flips its direction, like vim's N).
+    /// Continues into parts of the file that aren't loaded when the window has none left.
+    pub fn search_next(&mut self, reverse: bool) {
+        let (regex, backward) = match self.log.search {
+            Some(ref s) => (s.regex.clone(), s.backward != reverse),
+            None => return,
+        };
+        let current = self.current_match_index();
+        let matches = self.search_matches().to_vec();
+        let label = format!("/{}", regex.as_str());
+        if let Some(idx) = self.jump_to_match(&regex, &matches, current, backward, &label) {
+            let offset = self
+                .log
+                .window
+                .as_ref()
+                .and_then(|w| w.offsets.get(idx).copied());
+            if let Some(search) = self.log.search.as_mut() {
+                search.current = offset;
+            }
+        }
+    }
+
+    /// Move to the next line in `matches` after `current` (or the viewport when there's no
+    /// current line). If the window has none left, scan the file on disk and re-center the
+    /// window on the hit. Returns the window index landed on.
+    fn jump_to_match(
+        &mut self,
+        regex: &Regex,
+        matches: &[usize],
+        current: Option<usize>,
+        backward: bool,
+        label: &str,
+    ) -> Option<usize> {
+        let top = self.log.scroll;
+        let bottom = self.last_visible_log_line();
+        let hit = match (backward, current) {
+            (false, Some(c)) => matches.iter().find(|&&i| i > c),
+            (false, None) => matches.iter().find(|&&i| i >= top),
+            (true, Some(c)) => matches.iter().rev().find(|&&i| i < c),
+            (true, None) => matches.iter().rev().find(|&&i| i <= bottom),
+        }
+        .copied();
+
+        if let Some(idx) = hit {
+            self.reveal_log_line(idx);
+            return Some(idx);
+        }
+
+        let window = self.log.window.as_ref()?;
+        let path = match (&window.path, backward, window.at_start(), window.at_end()) {
+            (Some(p), true, false, _) | (Some(p), false, _, false) => p.clone(),
+            _ => {
+                let edge = if backward { "TOP" } else { "BOTTOM" };
+                self.flash_message(format!("Hit {} without match for {}", edge, label), true);
+                return None;
+            }
+        };
+        // Each press reads a bounded stretch of the file so a miss in a huge log can't hang
+        // the UI; the window moves to where the scan stopped and the next press goes on from there
+        let found = if backward {
+            find_before(&path, window.start(), regex, SEARCH_SCAN_BYTES)
+        } else {
+            find_after(&path, window.end, regex, SEARCH_SCAN_BYTES)
+        };
+        match found {
+            Ok(Scan::Found(offset)) => match LogWindow::around(&path, offset, PAGE_LINES) {
+                Ok(window) => {
+                    let idx = window.index_of(offset).unwrap_or(0);
+                    self.log.window = Some(window);
+                    self.reveal_log_line(idx);
+                    Some(idx)
+                }
+                Err(e) => {
+                    self.page_error(e);
+                    None
+                }
+            },
+            Ok(Scan::NotFound) => {
+                self.flash_message(format!("No more matches for {}", label), true);
+                None
+            }
+            Ok(Scan::Stopped(offset)) => {
+                match LogWindow::around(&path, offset, PAGE_LINES) {
+                    Ok(window) => {
+                        let percent = offset * 100 / window.file_size.max(1);
+                        let idx = window.index_of(offset).unwrap_or(0);
+                        self.log.window = Some(window);
+                        self.scroll_log_to_line(idx);
+                        self.flash_message(
+                            format!(
+                                "No match for {} up to {}% of the file, again to keep searching",
+                                label, percent
+                            ),
+                            false,
+                        );
+                    }
+                    Err(e) => self.page_error(e),
+                }
+                None
+            }
+            Err(e) => {
+                self.page_error(e);
+                None
+            }
+        }
+    }
+
+    /// Scroll just enough to bring window line `idx` into view
+    fn reveal_log_line(&mut self, idx: usize) {
+        if idx < self.log.scroll || idx > self.last_visible_log_line() {
+            self.scroll_log_to_line(idx);
+        }
+    }
+
+    /// Indices of window lines matching an error signature
+    pub fn error_lines(&mut self) -> &[usize] {
+        match self.log.window.as_ref() {
+            Some(window) => {
+                matching_lines(&mut self.log.error_cache, window, self.analyzer.regex())
+            }
+            None => &[],
+        }
+    }
+
+    /// Window index of the error line last jumped to, if it's loaded
+    pub fn current_error_index(&self) -> Option<usize> {
+        self.log.window.as_ref()?.index_of(self.log.error_cursor?)
+    }
+
+    /// Jump to the next (or previous) line matching an error signature
+    pub fn jump_to_error(&mut self, backward: bool) {
+        let current = self.current_error_index();
+        let matches = self.error_lines().to_vec();
+        let regex = self.analyzer.regex().clone();
+        if let Some(idx) =
+            self.jump_to_match(&regex, &matches, current, backward, "error signatures")
+        {
+            self.log.error_cursor = self
+                .log
+                .window
+                .as_ref()
+                .and_then(|w| w.offsets.get(idx).copied());
+        }
+    }
+
+    /// Parse progress bars from the end of the loaded log when it has new content
+    fn record_progress(&mut self) {
+        let (job_id, window) = match (self.selected_job(), self.log.window.as_ref()) {
+            (Some(j), Some(w)) if w.at_end() => (j.job_id.clone(), w),
+            _ => return,
+        };
+        let key = (job_id.clone(), window.end);
+        if self.progress_key.as_ref() == Some(&key) {
+            return;
+        }
+        let bars = latest_progress(&window.lines);
+        self.progress_key = Some(key);
+        // A log without bars (e.g. stdout when tqdm writes to stderr) keeps what was found before
+        if !bars.is_empty() {
+            self.progress.insert(job_id, bars);
+        }
+    }
+
+    /// Start reading the progress of the running jobs on screen in the background, at most
+    /// once per refresh interval and never while the last read is still going
+    pub fn poll_progress(&mut self) {
+        if !self.config.display.progress_column || self.progress_poll.is_some() {
+            return;
+        }
+        let interval = Duration::from_secs(self.config.general.refresh_interval.max(1));
+        if self
+            .progress_polled_at
+            .is_some_and(|at| at.elapsed() < interval)
+        {
+            return;
+        }
+        // Rows below the border and header
+        let rows = self.job_list_area.height.saturating_sub(3) as usize;
+        let requests: Vec<ProgressRequest> = self
+            .jobs
+            .iter()
+            .skip(self.table_state.offset())
+            .take(rows)
+            .filter(|j| j.state == JobState::Running)
+            .map(|j| ProgressRequest {
+                job_id: j.job_id.clone(),
+                stdout: j.stdout.clone(),
+            })
+            .collect();
+        if requests.is_empty() {
+            return;
+        }
+        self.progress_polled_at = Some(Instant::now());
+        self.progress_poll = Some(poll_progress(
+            requests,
+            self.config.remote.path_mappings.clone(),
+        ));
+    }
+
+    /// Take in the background progress read once it's done
+    pub fn collect_progress(&mut self) {
+        let updates = match self.progress_poll.as_ref().map(|rx| rx.try_recv()) {
+            Some(Ok(updates)) => updates,
+            Some(Err(TryRecvError::Empty)) | None => return,
+            Some(Err(TryRecvError::Disconnected)) => {
+                self.progress_poll = None;
+                return;
+            }
+        };
+        self.progress_poll = None;
+        for update in updates {
+            // Keep the paths scontrol gave, so the job doesn't need looking up again
+            if let Some(details) = update.details
+                && let Some(job) = self.jobs.iter_mut().find(|j| j.job_id == update.job_id)
+                && job.stderr.is_none()
+            {
+                job.stderr = Some(details.stderr);
+                job.stdout = Some(details.stdout);
+            }
+            if !update.bars.is_empty() && self.jobs.iter().any(|j| j.job_id == update.job_id) {
+                self.progress.insert(update.job_id, update.bars);
+            }
+        }
+    }
+
+    /// Progress bars of the selected job
+    pub fn selected_progress(&self) -> &[Progress] {
+        self.selected_job()
+            .and_then(|j| self.progress.get(&j.job_id))
+            .map(Vec::as_slice)
+            .unwrap_or(&[])
+    }
+
+    /// Load (once per job) the likely failure cause from the tails of the job's stderr and stdout
+    fn ensure_failure_cause(&mut self) {
+        let job = match self.selected_job() {
+            Some(j) if j.state.is_failure() => j.clone(),
+            _ => return,
+        };
+        if self
+            .failure_cause
+            .as_ref()
+            .is_some_and(|(id, _)| *id == job.job_id)
+        {
+            return;
+        }
+        let mut paths: Vec<String> = [&job.stderr, &job.stdout]
+            .into_iter()
+            .flatten()
+            .filter(|p| !p.is_empty())
+            .cloned()
+            .collect();
+        paths.dedup();
+        let cause = paths.iter().find_map(|path| {
+            let window = read_log_file(path, &job.nodelist, &self.config, PAGE_LINES).ok()?;
+            self.analyzer.likely_cause(&window.lines)
+        });
+        self.failure_cause = Some((job.job_id, cause));
+    }
+
+    /// Likely cause of the selected job's failure, if it failed and a signature matched
+    pub fn likely_cause(&self) -> Option<&LikelyCause> {
+        let job = self.selected_job()?;
+        match self.failure_cause {
+            Some((ref id, ref cause)) if *id == job.job_id => cause.as_ref(),
+            _ => None,
+        }
+    }
+
+    /// "match 3/41" for the log title; counts cover the loaded part of the file
+    pub fn search_status(&mut self) -> Option<String> {
+        self.log.search.as_ref()?;
+        let current = self.current_match_index();
+        let partial = self.log.window.as_ref().is_some_and(|w| !w.is_complete());
+        let matches = self.search_matches();
+        let total = format!("{}{}", matches.len(), if partial { "+" } else { "" });
+        Some(
+            match current.and_then(|c| matches.iter().position(|&i| i == c)) {
+                Some(pos) => format!("match {}/{}", pos + 1, total),
+                None => format!("{} matches", total),
+            },
+        )
+    }
+
+    pub fn next_job(&mut self) {
+        if self.jobs.is_empty() { return; }
+        let i = match self.table_state.selected() {
+            Some(i) => if i >= self.jobs.len() - 1 { 0 } else { i + 1 },
+            None => 0,
+        };
+        self.table_state.select(Some(i));
+    }
+
+    pub fn previous_job(&mut self) {
+        if self.jobs.is_empty() { return; }
+        let i = match self.table_state.selected() {
+            Some(i) => if i == 0 { self.jobs.len() - 1 } else { i - 1 },
+            None => 0,
+        };
+        self.table_state.select(Some(i));
+    }
+
+    /// Job under a point of the job table, below its border and header row
+    pub fn job_at(&self, col: u16, row: u16) -> Option<usize> {
+        let area = self.job_list_area;
+        let inner_top = area.y + 2;
+        if col <= area.x
+            || col + 1 >= area.x + area.width
+            || row < inner_top
+            || row + 1 >= area.y + area.height
+        {
+            return None;
+        }
+        let idx = self.table_state.offset() + (row - inner_top) as usize;
+        (idx < self.jobs.len()).then_some(idx)
+    }
+
+    /// Select the clicked job; a second click on it soon after opens its log zoomed
+    pub fn click_job(&mut self, col: u16, row: u16) {
+        self.focus = FocusPanel::Jobs;
+        let Some(idx) = self.job_at(col, row) else {
+            return;
+        };
+        self.table_state.select(Some(idx));
+        let job_id = self.jobs[idx].job_id.clone();
+        let now = Instant::now();
+        let double = self
+            .last_click
+            .as_ref()
+            .is_some_and(|(at, last)| *last == job_id && now.duration_since(*at) <= DOUBLE_CLICK);
+        if double {
+            self.last_click = None;
+            self.preview_tab = PreviewTab::Log;
+            self.focus = FocusPanel::Log;
+            self.zoomed = true;
+        } else {
+            self.last_click = Some((now, job_id));
+        }
+    }
+
+    /// Move the selection by the wheel, stopping at either end
+    pub fn scroll_jobs(&mut self, delta: isize) {
+        if self.jobs.is_empty() {
+            return;
+        }
+        let current = self.table_state.selected().unwrap_or(0);
+        let next = current
+            .saturating_add_signed(delta)
+            .min(self.jobs.len() - 1);
+        self.table_state.select(Some(next));
+    }
+
+    /// Open the job actions menu at the cursor for the job under it
+    pub fn open_context_menu(&mut self, col: u16, row: u16) {
+        let Some(idx) = self.job_at(col, row) else {
+            return;
+        };
+        self.focus = FocusPanel::Jobs;
+        self.table_state.select(Some(idx));
+        self.context_menu = Some(ContextMenu {
+            col,
+            row,
+            selected: 0,
+            area: Rect::default(),
+        });
+    }
+
+    pub fn move_menu(&mut self, delta: isize) {
+        if let Some(menu) = self.context_menu.as_mut() {
+            let len = JOB_MENU.len() as isize;
+            menu.selected = (menu.selected as isize + delta).rem_euclid(len) as usize;
+        }
+    }
+
+    /// Close the menu, returning the highlighted action
+    pub fn choose_menu_item(&mut self) -> Option<Action> {
+        let menu = self.context_menu.take()?;
+        JOB_MENU.get(menu.selected).map(|(action, _)| *action)
+    }
+
+    /// Menu entry under a point, if the click landed on one
+    pub fn menu_item_at(&self, col: u16, row: u16) -> Option<usize> {
+        let area = self.context_menu.as_ref()?.area;
+        if col <= area.x
+            || col + 1 >= area.x + area.width
+            || row <= area.y
+            || row + 1 >= area.y + area.height
+        {
+            return None;
+        }
+        Some((row - area.y - 1) as usize).filter(|&i| i < JOB_MENU.len())
+    }
+
+    pub fn select_first(&mut self) {
+        if !self.jobs.is_empty() {
+            self.table_state.select(Some(0));
+        }
+    }
+
+    pub fn select_last(&mut self) {
+        if !self.jobs.is_empty() {
+            self.table_state.select(Some(self.jobs.len() - 1));
+        }
+    }
+
+    pub fn is_marked(&self, job_id: &str) -> bool {
+        self.marked.contains(job_id)
+    }
+
+    /// Toggle the mark on the selected job and move to the next one
+    pub fn toggle_mark(&mut self) {
+        let id = match self.selected_job() {
+            Some(j) => j.job_id.clone(),
+            None => return,
+        };
+        if !self.marked.remove(&id) {
+            self.marked.insert(id);
+        }
+        self.next_job();
+    }
+
+    /// Mark every listed job, or clear all marks if everything is already marked
+    pub fn toggle_mark_all(&mut self) {
+        if !self.jobs.is_empty() && self.jobs.iter().all(|j| self.marked.contains(&j.job_id)) {
+            self.marked.clear();
+        } else {
+            self.marked
+                .extend(self.jobs.iter().map(|j| j.job_id.clone()));
+        }
+    }
+
+    /// Start a visual range at the selected job, or mark the range if one is active
+    pub fn toggle_visual_mark(&mut self) {
+        if self.visual_anchor.is_some() {
+            if let Some((start, end)) = self.visual_range() {
+                let ids: Vec<String> = self.jobs[start..=end]
+                    .iter()
+                    .map(|j| j.job_id.clone())
+                    .collect();
+                self.marked.extend(ids);
+            }
+            self.visual_anchor = None;
+        } else {
+            self.visual_anchor = self.selected_job().map(|j| j.job_id.clone());
+        }
+    }
+
+    /// Inclusive index range between the visual anchor and the selection
+    pub fn visual_range(&self) -> Option<(usize, usize)> {
+        let anchor_id = self.visual_anchor.as_ref()?;
+        let anchor = self.jobs.iter().position(|j| j.job_id == *anchor_id)?;
+        let cursor = self.table_state.selected()?;
+        Some((anchor.min(cursor), anchor.max(cursor)))
+    }
+
+    /// Leave visual mode without marking, returns whether it was active
+    pub fn cancel_visual(&mut self) -> bool {
+        self.visual_anchor.take().is_some()
+    }
+
+    /// Job IDs an action applies to: the marked set in list order, else the selected job
+    pub fn target_job_ids(&self) -> Vec<String> {
+        if self.marked.is_empty() {
+            return self
+                .selected_job()
+                .map(|j| vec![j.job_id.clone()])
+                .unwrap_or_default();
+        }
+        self.jobs
+            .iter()
+            .filter(|j| self.marked.contains(&j.job_id))
+            .map(|j| j.job_id.clone())
+            .collect()
+    }
+
+    pub fn copy_target_ids(&mut self) {
+        let ids = self.target_job_ids();
+        if !ids.is_empty() {
+            self.copy_to_clipboard(&ids.join(" "));
+        }
+    }
+
+    /// Ask for confirmation before cancelling the target jobs
+    pub fn request_cancel(&mut self) {
+        let ids = self.target_job_ids();
+        if !ids.is_empty() {
+            self.pending_confirm = Some(PendingAction::Cancel(ids));
+        }
+    }
+
+    /// Answer the pending confirmation prompt
+    pub fn resolve_confirm(&mut self, accepted: bool) {
+        let action = match self.pending_confirm.take() {
+            Some(a) => a,
+            None => return,
+        };
+        if !accepted {
+            action.remove_script();
+            return;
+        }
+        match action {
+            PendingAction::Cancel(ids) => {
+                let result = cancel_jobs(&ids);
+                self.report(result, format!("Cancelled {}", describe_jobs(&ids)));
+            }
+            PendingAction::Resubmit {
+                job_id,
+                script_path,
+                workdir,
+            } => {
+                let spec = SubmitSpec {
+                    script: script_path,
+                    workdir,
+                    ..Default::default()
+                };
+                let submitted = spec.submit();
+                let _ = std::fs::remove_file(&spec.script);
+                match submitted {
+                    Ok(new_id) => {
+                        self.flash_message(
+                            format!("Resubmitted job {} as {}", job_id, new_id),
+                            false,
+                        );
+                        self.select_when_listed(new_id);
+                    }
+                    Err(e) => self.flash_message(e, true),
+                }
+            }
+        }
+        self.refresh_jobs();
+    }
+
+    /// Called by the event loop once an external program has exited
+    pub fn external_finished(&mut self, cmd: ExternalCommand, result: Result<(), String>) {
+        match result {
+            Ok(()) => self.pending_confirm = cmd.on_success,
+            Err(e) => {
+                if let Some(action) = &cmd.on_success {
+                    action.remove_script();
+                }
+                self.flash_message(e, true);
+            }
+        }
+        // Whatever happened outside may have changed the queue or the log
+        self.log.last_key = None;
+    }
+
+    /// Write the selected job's script with its submission parameters to a
+    /// temp file and open it in $EDITOR; saving and confirming submits the copy
+    pub fn start_resubmit(&mut self) {
+        let job_id = match self.selected_job() {
+            Some(j) => j.job_id.clone(),
+            None => return,
+        };
+        let prepared = fetch_batch_script(&job_id).and_then(|script| {
+            let fields = fetch_job_fields(&job_id)?;
+            let spec = resubmit_spec(&job_id, &fields);
+            let contents = script_with_directives(&script, &spec, &job_id);
+            let path = write_temp_script(&format!("ylurm-resubmit-{}", job_id), &contents)?;
+            Ok((path.display().to_string(), spec.workdir))
+        });
+
+        match prepared {
+            Ok((script_path, workdir)) => {
+                let (program, mut args) = editor_command();
+                args.push(script_path.clone());
+                self.external = Some(ExternalCommand {
+                    program,
+                    args,
+                    on_success: Some(PendingAction::Resubmit {
+                        job_id,
+                        script_path,
+                        workdir,
+                    }),
+                });
+            }
+            Err(e) => self.flash_message(e, true),
+        }
+    }
+
+    /// Open an interactive shell on the selected running job's allocation
+    pub fn start_shell(&mut self) {
+        let (job_id, partition, running) = match self.selected_job() {
+            Some(j) => (
+                j.job_id.clone(),
+                j.partition.clone(),
+                j.state == JobState::Running,
+            ),
+            None => return,
+        };
+        if !running {
+            self.flash_message(format!("Job {} is not running", job_id), true);
+            return;
+        }
+
+        let method = self
+            .config
+            .actions
+            .partition_shell
+            .get(&partition)
+            .unwrap_or(&self.config.actions.shell_method)
+            .clone();
+        // Only ssh needs the batch host, which means one more scontrol call
+        let batch_host = if method == "ssh" {
+            match fetch_job_fields(&job_id) {
+                Ok(fields) => fields.get("BatchHost").cloned().unwrap_or_default(),
+                Err(e) => {
+                    self.flash_message(e, true);
+                    return;
+                }
+            }
+        } else {
+            String::new()
+        };
+        let shell = std::env::var("SHELL").unwrap_or_else(|_| "/bin/bash".to_string());
+
+        match shell_command(&method, &job_id, &batch_host, &shell) {
+            Ok((program, args)) => {
+                self.external = Some(ExternalCommand {
+                    program,
+                    args,
+                    on_success: None,
+                });
+            }
+            Err(e) => self.flash_message(e, true),
+        }
+    }
+
+    /// Open the whole current log in $PAGER (`less +F` if unset) or the editor.
+    /// Files only reachable over SSH are opened with `ssh -t <node> <program> <path>`.
+    pub fn open_log_external(&mut self, use_editor: bool) {
+        let (path, nodelist) = match self.current_log_path() {
+            Some(p) => p,
+            None => {
+                self.flash_message("No log path for this job yet".into(), true);
+                return;
+            }
+        };
+        let (program, mut args) = if use_editor {
+            editor_command()
+        } else {
+            pager_command()
+        };
+
+        let cmd = match locate_log_file(&path, &nodelist, &self.config) {
+            Ok(LogLocation::Local(local)) => {
+                args.push(local);
+                ExternalCommand {
+                    program,
+                    args,
+                    on_success: None,
+                }
+            }
+            Ok(LogLocation::Remote { node, path }) => {
+                let mut remote = vec![program];
+                remote.extend(args);
+                remote.push(path);
+                ExternalCommand {
+                    program: "ssh".to_string(),
+                    args: vec!["-t".to_string(), node, remote_command_line(&remote)],
+                    on_success: None,
+                }
+            }
+            Err(e) => {
+                self.flash_message(e, true);
+                return;
+            }
+        };
+        self.external = Some(cmd);
+    }
+
+    pub fn next_preview_tab(&mut self) {
+        self.preview_tab = match self.preview_tab {
+            PreviewTab::Log => PreviewTab::Script,
+            PreviewTab::Script => PreviewTab::Metrics,
+            PreviewTab::Metrics => PreviewTab::Log,
+        };
+    }
+
+    /// Scan the shown log for metrics: incrementally for a local file,
+    /// or from the loaded lines when the log was read over SSH
+    pub fn ensure_metrics_loaded(&mut self) {
+        let (log_key, window) = match (self.log.loaded_key.as_ref(), self.log.window.as_ref()) {
+            (Some(k), Some(w)) => (k, w),
+            _ => {
+                self.metrics = None;
+                return;
+            }
+        };
+        // A snapshot is re-read on refresh, so rebuild whenever it changes
+        let key = if window.is_seekable() {
+            log_key.clone()
+        } else {
+            format!("{}@{}", log_key, window.end)
+        };
+        if self.metrics.as_ref().is_none_or(|(k, _)| *k != key) {
+            let scan = match window.path {
+                Some(ref path) => MetricsScan::new(Some(path.clone())),
+                None => MetricsScan::from_lines(&window.lines, &self.metric_extractor),
+            };
+            if !self
+                .metrics
+                .as_ref()
+                .is_some_and(|(k, _)| k.starts_with(log_key.as_str()))
+            {
+                self.metrics_selected = 0;
+            }
+            self.metrics = Some((key, scan));
+        }
+        if let Some((_, ref mut scan)) = self.metrics
+            && let Err(e) = scan.update(&self.metric_extractor)
+        {
+            self.metrics = None;
+            self.flash_message(format!("Metrics scan failed: {}", e), true);
+        }
+    }
+
+    pub fn select_next_metric(&mut self) {
+        let count = self
+            .metrics
+            .as_ref()
+            .map(|(_, m)| m.series.len())
+            .unwrap_or(0);
+        if count > 0 {
+            self.metrics_selected = (self.metrics_selected + 1) % count;
+        }
+    }
+
+    pub fn select_previous_metric(&mut self) {
+        let count = self
+            .metrics
+            .as_ref()
+            .map(|(_, m)| m.series.len())
+            .unwrap_or(0);
+        if count > 0 {
+            self.metrics_selected = (self.metrics_selected + count - 1) % count;
+        }
+    }
+
+    /// Fetch the batch script for the Script tab if the selection changed
+    pub fn ensure_script_loaded(&mut self) {
+        let job_id = match self.selected_job() {
+            Some(j) => j.job_id.clone(),
+            None => return,
+        };
+        if self.script.as_ref().is_some_and(|(id, _)| *id == job_id) {
+            return;
+        }
+        self.script = Some((job_id.clone(), fetch_batch_script(&job_id)));
+        self.script_scroll = 0;
+    }
+
+    pub fn script_line_count(&self) -> usize {
+        match self.script {
+            Some((_, Ok(ref text))) => text.lines().count(),
+            _ => 0,
+        }
+    }
+
+    pub fn scroll_script_down(&mut self, amount: u16) {
+        let viewport_lines = self.log.area.height.saturating_sub(2);
+        let max_scroll = (self.script_line_count() as u16).saturating_sub(viewport_lines);
+        self.script_scroll = self.script_scroll.saturating_add(amount).min(max_scroll);
+    }
+
+    pub fn scroll_script_up(&mut self, amount: u16) {
+        self.script_scroll = self.script_scroll.saturating_sub(amount);
+    }
+
+    pub fn scroll_script_bottom(&mut self) {
+        let viewport_lines = self.log.area.height.saturating_sub(2);
+        self.script_scroll = (self.script_line_count() as u16).saturating_sub(viewport_lines);
+    }
+
+    pub fn hold_targets(&mut self) {
+        let ids = self.target_job_ids();
+        if ids.is_empty() {
+            return;
+        }
+        let result = hold_jobs(&ids);
+        self.report(result, format!("Held {}", describe_jobs(&ids)));
+        self.refresh_jobs();
+    }
+
+    pub fn release_targets(&mut self) {
+        let ids = self.target_job_ids();
+        if ids.is_empty() {
+            return;
+        }
+        let result = release_jobs(&ids);
+        self.report(result, format!("Released {}", describe_jobs(&ids)));
+        self.refresh_jobs();
+    }
+
+    /// Open the scontrol update form for the selected job
+    pub fn open_edit_form(&mut self) {
+        let job_id = match self.selected_job() {
+            Some(j) => j.job_id.clone(),
+            None => return,
+        };
+        let fields = match fetch_job_fields(&job_id) {
+            Ok(f) => f,
+            Err(e) => {
+                self.flash_message(e, true);
+                return;
+            }
+        };
+
+        let form_fields = EDITABLE_FIELDS
+            .iter()
+            .map(|key| {
+                let value = fields.get(*key).map(String::as_str).unwrap_or("");
+                // scontrol prints "(null)" for unset optional fields
+                let value = if value == "(null)" { "" } else { value };
+                FormField::text(key, value)
+            })
+            .collect();
+        let form = Form::new(&format!("Edit job {}", job_id), form_fields);
+        self.modal = Some(Modal::EditJob { job_id, form });
+    }
+
+    /// Route a key press to the open modal
+    pub fn handle_modal_key(&mut self, key: KeyEvent) {
+        // Ctrl+T asks Slurm when a new submission would start
+        if matches!(self.modal, Some(Modal::Submit { .. }))
+            && key.code == KeyCode::Char('t')
+            && key.modifiers.contains(KeyModifiers::CONTROL)
+        {
+            self.test_submission();
+            return;
+        }
+
+        let outcome = match self.modal {
+            Some(ref mut modal) => modal.form_mut().handle_key(key),
+            None => return,
+        };
+        match outcome {
+            FormOutcome::Continue => {
+                if let Some(Modal::Submit { ref mut form, .. }) = self.modal {
+                    form.footer = vec![submit_spec(form).command_line()];
+                }
+            }
+            FormOutcome::Cancel => self.modal = None,
+            FormOutcome::Submit => self.submit_modal(),
+        }
+    }
+
+    fn submit_modal(&mut self) {
+        match self.modal {
+            Some(Modal::EditJob { .. }) => self.submit_edit(),
+            Some(Modal::Signal { .. }) => self.submit_signal(),
+            Some(Modal::Submit { .. }) => self.submit_new_job(),
+            None => {}
+        }
+    }
+
+    fn submit_edit(&mut self) {
+        let Some(Modal::EditJob {
+            ref job_id,
+            ref mut form,
+        }) = self.modal
+        else {
+            return;
+        };
+
+        let changes = form.changed();
+        if changes.is_empty() {
+            self.modal = None;
+            return;
+        }
+        if let Some(err) = changes.iter().find_map(|(k, v)| validate_field(k, v).err()) {
+            form.error = Some(err);
+            return;
+        }
+
+        // Keep the form open on rejection so the user can fix the value
+        match update_job(job_id, &changes) {
+            Ok(()) => {
+                let names: Vec<&str> = changes.iter().map(|(k, _)| k.as_str()).collect();
+                let msg = format!("Updated job {}: {}", job_id, names.join(", "));
+                self.modal = None;
+                self.flash_message(msg, false);
+                self.refresh_jobs();
+            }
+            Err(e) => form.error = Some(e),
+        }
+    }
+
+    /// Open the signal picker for the marked (or selected) jobs
+    pub fn open_signal_form(&mut self) {
+        let job_ids = self.target_job_ids();
+        if job_ids.is_empty() {
+            return;
+        }
+
+        let mut signals = self.config.actions.signals.clone();
+        if !signals.iter().any(|s| s == "custom") {
+            signals.push("custom".to_string());
+        }
+        let targets = SignalTarget::ALL
+            .iter()
+            .map(|t| t.as_str().to_string())
+            .collect();
+
+        let fields = vec![
+            FormField::choice("signal", &signals[0], signals.clone()),
+            FormField::text("custom", "").with_label("custom signal"),
+            FormField::choice("target", SignalTarget::Batch.as_str(), targets),
+            FormField::text("step", "0").with_label("step id"),
+        ];
+        let form = Form::new(&format!("Signal {}", describe_jobs(&job_ids)), fields)
+            .with_hints("Enter send  Esc cancel  ←→ choose  ↑↓ field");
+        self.modal = Some(Modal::Signal { job_ids, form });
+    }
+
+    fn submit_signal(&mut self) {
+        let Some(Modal::Signal {
+            ref job_ids,
+            ref mut form,
+        }) = self.modal
+        else {
+            return;
+        };
+
+        let signal = match form.value("signal") {
+            "custom" => form.value("custom").to_string(),
+            s => s.to_string(),
+        };
+        let target = form.value("target").parse().unwrap_or(SignalTarget::Batch);
+
+        match signal_jobs(job_ids, &signal, target, form.value("step")) {
+            Ok(()) => {
+                let msg = format!(
+                    "Sent {} ({}) to {}",
+                    signal_label(&signal),
+                    target.as_str(),
+                    describe_jobs(job_ids)
+                );
+                self.modal = None;
+                self.flash_message(msg, false);
+            }
+            Err(e) => form.error = Some(e),
+        }
+    }
+
+    /// Open the sbatch submission form
+    pub fn open_submit_form(&mut self) {
+        let partitions = fetch_partitions();
+        let workdir = std::env::current_dir()
+            .map(|p| p.display().to_string())
+            .unwrap_or_default();
+
+        let partition = match partitions.first() {
+            Some(p) => FormField::choice("partition", p, partitions.clone()),
+            // sinfo unavailable: let the user type it
+            None => FormField::text("partition", ""),
+        };
+        let fields = vec![
+            partition,
+            FormField::text("gpus", "0").with_label("GPUs"),
+            FormField::text("cpus", "1").with_label("CPUs per task"),
+            FormField::text("mem", "").with_label("memory"),
+            FormField::text("time", "1:00:00").with_label("time limit"),
+            FormField::text("name", "").with_label("job name"),
+            FormField::text("output", "slurm-%j.out").with_label("output"),
+            FormField::text("error", "").with_label("error"),
+            FormField::text("workdir", &workdir).with_label("working dir"),
+            FormField::text("script", "").with_label("script"),
+        ];
+        let mut form = Form::new("Submit job (sbatch)", fields)
+            .with_hints("Enter test, then submit  ^T test-only  Esc cancel  ↑↓ field");
+        form.footer = vec![submit_spec(&form).command_line()];
+        self.modal = Some(Modal::Submit { form, tested: None });
+    }
+
+    /// Run `sbatch --test-only` and show Slurm's estimated start under the command line
+    fn test_submission(&mut self) {
+        let Some(Modal::Submit {
+            ref mut form,
+            ref mut tested,
+        }) = self.modal
+        else {
+            return;
+        };
+
+        let spec = submit_spec(form);
+        match spec.test_only() {
+            Ok(estimate) => {
+                form.footer = vec![
+                    spec.command_line(),
+                    estimate,
+                    "Enter again to submit".to_string(),
+                ];
+                form.error = None;
+                *tested = Some(spec.command_line());
+            }
+            Err(e) => {
+                form.error = Some(e);
+                *tested = None;
+            }
+        }
+    }
+
+    /// Submit once the same command line passed `--test-only`; test it first otherwise
+    fn submit_new_job(&mut self) {
+        let Some(Modal::Submit {
+            ref mut form,
+            ref tested,
+        }) = self.modal
+        else {
+            return;
+        };
+
+        let spec = submit_spec(form);
+        if tested.as_deref() != Some(spec.command_line().as_str()) {
+            return self.test_submission();
+        }
+        match spec.submit() {
+            Ok(job_id) => {
+                self.modal = None;
+                self.flash_message(format!("Submitted job {}", job_id), false);
+                self.select_when_listed(job_id);
+                self.refresh_jobs();
+            }
+            Err(e) => form.error = Some(e),
+        }
+    }
+
+    /// Show an action's outcome in the status bar
+    pub fn report(&mut self, result: Result<(), String>, success: String) {
+        match result {
+            Ok(()) => self.flash_message(success, false),
+            Err(e) => self.flash_message(e, true),
+        }
+    }
+
+    pub fn flash_message(&mut self, text: String, is_error: bool) {
+        // Errors stay up longer so Slurm's message can actually be read
+        let secs = if is_error { 6 } else { 3 };
+        self.flash = Some(Flash {
+            text,
+            is_error,
+            until: Instant::now() + Duration::from_secs(secs),
+        });
+    }
+
+    /// The status bar message to show right now, if any
+    pub fn active_flash(&self) -> Option<&Flash> {
+        self.flash.as_ref().filter(|f| Instant::now() < f.until)
+    }
+
+    /// Copy text to system clipboard via OSC 52 escape sequence (works in iTerm2, most modern terminals)
+    pub fn copy_to_clipboard(&mut self, text: &str) {
+        let encoded = base64_encode(text.as_bytes());
+        let osc = format!("\x1b]52;c;{}\x07", encoded);
+        let _ = std::io::stdout().write_all(osc.as_bytes());
+        let _ = std::io::stdout().flush();
+        self.copy_feedback_until = Some(Instant::now() + Duration::from_secs(2));
+    }
+
+    /// Handle a click in the details panel — copy stderr/stdout path if on those rows
+    pub fn handle_details_click(&mut self, _col: u16, row: u16) {
+        let inner_y = self.details_area.y + 1; // skip top border
+        let stderr_row = inner_y + 6;
+        let stdout_row = inner_y + 7;
+
+        if let Some(job) = self.selected_job() {
+            if row == stderr_row {
+                if let Some(ref path) = job.stderr {
+                    let path = path.clone();
+                    self.copy_to_clipboard(&path);
+                }
+            } else if row == stdout_row
+                && let Some(ref path) = job.stdout
+            {
+                let path = path.clone();
+                self.copy_to_clipboard(&path);
+            }
+        }
+    }
+
+    /// Whether we should show copy feedback right now
+    pub fn showing_copy_feedback(&self) -> bool {
+        self.copy_feedback_until
+            .map(|t| Instant::now() < t)
+            .unwrap_or(false)
+    }
+}
+
+/// $VISUAL / $EDITOR split into program and arguments (e.g. "code -w"), vi if unset
+pub fn editor_command() -> (String, Vec<String>) {
+    let editor = std::env::var("VISUAL")
+        .or_else(|_| std::env::var("EDITOR"))
+        .unwrap_or_else(|_| "vi".to_string());
+    let mut parts = editor.split_whitespace().map(String::from);
+    let program = parts.next().unwrap_or_else(|| "vi".to_string());
+    (program, parts.collect())
+}
+
+/// $PAGER split into program and arguments, `less +F` (follow mode) if unset
+pub fn pager_command() -> (String, Vec<String>) {
+    let pager = std::env::var("PAGER").unwrap_or_else(|_| "less +F".to_string());
+    let mut parts = pager.split_whitespace().map(String::from);
+    let program = parts.next().unwrap_or_else(|| "less".to_string());
+    (program, parts.collect())
+}
+
+/// Words joined into the one command line ssh hands to the remote shell, each quoted to
+/// survive it intact
+fn remote_command_line(words: &[String]) -> String {
+    words
+        .iter()
+        .map(|w| shell_quote(w))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Build an sbatch spec from the submission form's fields
+fn submit_spec(form: &Form) -> SubmitSpec {
+    SubmitSpec {
+        partition: form.value("partition").to_string(),
+        gpus: form.value("gpus").to_string(),
+        cpus: form.value("cpus").to_string(),
+        mem: form.value("mem").to_string(),
+        time: form.value("time").to_string(),
+        name: form.value("name").to_string(),
+        output: form.value("output").to_string(),
+        error: form.value("error").to_string(),
+        workdir: form.value("workdir").to_string(),
+        script: form.value("script").to_string(),
+        extra: vec![],
+    }
+}
+
+/// "job 123" or "3 jobs" for status messages
+pub fn describe_jobs(ids: &[String]) -> String {
+    match ids {
+        [id] => format!("job {}", id),
+        _ => format!("{} jobs", ids.len()),
+    }
+}
+
+fn base64_encode(input: &[u8]) -> String {
+    const CHARS: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut result = String::with_capacity(input.len().div_ceil(3) * 4);
+    for chunk in input.chunks(3) {
+        let b0 = chunk[0] as u32;
+        let b1 = chunk.get(1).copied().unwrap_or(0) as u32;
         let b2 = chunk.get(2).copied().unwrap_or(0) as u32;
         let n = (b0 << 16) | (b1 << 8) | b2;
         result.push(CHARS[((n >> 18) & 63) as usize] as char);
@@ -355,3 +3038,590 @@ fn base64_encode(input: &[u8]) -> String {
     }
     result
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// An app listing `jobs`, as if squeue had returned them
+    fn app_with_jobs(jobs: Vec<Job>) -> App {
+        let mut app = App::without_jobs(Config::default(), None);
+        app.update_jobs(jobs);
+        app.table_state.select(Some(0));
+        app
+    }
+
+    fn app_with(ids: &[&str]) -> App {
+        app_with_jobs(ids.iter().map(|id| Job::test(id)).collect())
+    }
+
+    /// A job whose stdout and stderr are a temp file of numbered lines
+    fn job_with_log(id: &str, lines: usize) -> Job {
+        let path =
+            std::env::temp_dir().join(format!("ylurm_test_app_{}_{}.out", std::process::id(), id));
+        let text: String = (1..=lines)
+            .map(|i| format!("job {} line {}\n", id, i))
+            .collect();
+        std::fs::write(&path, text).unwrap();
+        let path = path.display().to_string();
+        Job {
+            stdout: Some(path.clone()),
+            stderr: Some(path),
+            ..Job::test(id)
+        }
+    }
+
+    #[test]
+    fn toggle_mark_moves_down() {
+        let mut app = app_with(&["1", "2", "3"]);
+        app.toggle_mark();
+        assert!(app.is_marked("1"));
+        assert_eq!(app.table_state.selected(), Some(1));
+        app.previous_job();
+        app.toggle_mark();
+        assert!(!app.is_marked("1"));
+    }
+
+    #[test]
+    fn visual_range_follows_the_cursor() {
+        let mut app = app_with(&["1", "2", "3", "4"]);
+        app.table_state.select(Some(2));
+        app.toggle_visual_mark();
+        assert_eq!(app.visual_range(), Some((2, 2)));
+        app.select_first();
+        assert_eq!(app.visual_range(), Some((0, 2)));
+        app.toggle_visual_mark();
+        assert_eq!(app.visual_range(), None);
+        assert_eq!(app.target_job_ids(), ["1", "2", "3"]);
+    }
+
+    #[test]
+    fn targets_are_marked_jobs_in_list_order() {
+        let mut app = app_with(&["1", "2", "3"]);
+        app.table_state.select(Some(1));
+        assert_eq!(app.target_job_ids(), ["2"]);
+        app.marked.extend(["3".to_string(), "1".to_string()]);
+        assert_eq!(app.target_job_ids(), ["1", "3"]);
+    }
+
+    #[test]
+    fn marks_survive_refresh_until_jobs_leave() {
+        let mut app = app_with(&["1", "2", "3"]);
+        app.marked.extend(["1".to_string(), "3".to_string()]);
+        app.table_state.select(Some(2));
+        app.toggle_visual_mark();
+        app.update_jobs(vec![Job::test("3"), Job::test("4")]);
+        assert_eq!(app.target_job_ids(), ["3"]);
+        assert!(!app.is_marked("1"));
+        assert_eq!(app.selected_job().map(|j| j.job_id.as_str()), Some("3"));
+        assert_eq!(app.visual_range(), Some((0, 0)));
+        app.update_jobs(vec![Job::test("4")]);
+        assert!(app.marked.is_empty());
+        assert_eq!(app.visual_anchor, None);
+    }
+
+    #[test]
+    fn submitted_job_is_selected_only_while_it_is_worth_waiting_for() {
+        let listing = |ids: &[&str]| ids.iter().map(|id| Job::test(id)).collect::<Vec<_>>();
+        let mut app = app_with(&["1", "2"]);
+        app.select_when_listed("3".to_string());
+        app.update_jobs(listing(&["1", "2"]));
+        app.update_jobs(listing(&["1", "2", "3"]));
+        assert_eq!(selected_id(&app), Some("3"));
+
+        // Moving the selection while waiting cancels the jump
+        app.select_first();
+        app.select_when_listed("4".to_string());
+        app.next_job();
+        app.update_jobs(listing(&["1", "2", "3", "4"]));
+        assert_eq!(selected_id(&app), Some("2"));
+
+        // A job squeue never lists is given up on
+        app.select_when_listed("5".to_string());
+        for _ in 0..=PENDING_SELECT_REFRESHES {
+            app.update_jobs(listing(&["1", "2"]));
+        }
+        assert!(app.pending_select.is_none());
+        app.update_jobs(listing(&["1", "2", "5"]));
+        assert_eq!(selected_id(&app), Some("2"));
+    }
+
+    #[test]
+    fn declined_or_failed_resubmit_removes_its_script() {
+        let script = |name: &str| {
+            let path = std::env::temp_dir().join(format!(
+                "ylurm_test_resubmit_{}_{}.sh",
+                std::process::id(),
+                name
+            ));
+            std::fs::write(&path, "#!/bin/bash\n").unwrap();
+            let script_path = path.display().to_string();
+            (
+                path,
+                PendingAction::Resubmit {
+                    job_id: "1".to_string(),
+                    script_path,
+                    workdir: String::new(),
+                },
+            )
+        };
+        let mut app = app_with(&["1"]);
+        let (declined, action) = script("declined");
+        app.pending_confirm = Some(action);
+        app.resolve_confirm(false);
+        assert!(!declined.exists());
+
+        let (failed, action) = script("failed");
+        let editor = ExternalCommand {
+            program: "vi".to_string(),
+            args: vec![],
+            on_success: Some(action),
+        };
+        app.external_finished(editor, Err("vi exited with 1".to_string()));
+        assert!(app.pending_confirm.is_none());
+        assert!(!failed.exists());
+    }
+
+    #[test]
+    fn remote_command_line_quotes_every_word() {
+        let words = ["less", "+F", "-P$HOME;ls", "/logs/my run.out"].map(String::from);
+        assert_eq!(
+            remote_command_line(&words),
+            "less +F '-P$HOME;ls' '/logs/my run.out'"
+        );
+    }
+
+    #[test]
+    fn counts_are_typed_in_the_log_and_script_only() {
+        let mut app = app_with(&["1"]);
+        let digit = |c| KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE);
+        assert!(!app.push_count_digit(digit('5')));
+        app.focus = FocusPanel::Log;
+        assert!(!app.push_count_digit(digit('0')));
+        assert!(app.push_count_digit(digit('1')));
+        assert!(app.push_count_digit(digit('2')));
+        assert!(app.push_count_digit(digit('0')));
+        assert_eq!(app.count, Some(120));
+        assert!(!app.push_count_digit(KeyEvent::new(KeyCode::Char('3'), KeyModifiers::CONTROL)));
+        for _ in 0..10 {
+            app.push_count_digit(digit('9'));
+        }
+        assert_eq!(app.count, Some(MAX_COUNT));
+
+        app.count = None;
+        app.preview_tab = PreviewTab::Script;
+        assert!(app.push_count_digit(digit('3')));
+        assert_eq!(app.count, Some(3));
+        app.count = None;
+        app.preview_tab = PreviewTab::Metrics;
+        assert!(!app.push_count_digit(digit('3')));
+    }
+
+    #[test]
+    fn log_marks_come_back_after_paging_away() {
+        let mut app = app_with_jobs(vec![job_with_log("1", 3000)]);
+        app.log.area = Rect::new(0, 0, 80, 22);
+        app.ensure_log_loaded();
+        let top_line = |app: &App| {
+            app.log
+                .window
+                .as_ref()
+                .map(|w| w.lines[app.log.scroll].clone())
+        };
+
+        app.goto_log_line(2600);
+        app.set_log_mark('a');
+        let marked = top_line(&app);
+        assert!(
+            marked
+                .as_ref()
+                .is_some_and(|l| l.starts_with("job 1 line 25"))
+        );
+        // Line 1 is outside the tail that was loaded, so the window is replaced
+        app.goto_log_line(1);
+        assert_eq!(top_line(&app).as_deref(), Some("job 1 line 1"));
+        app.jump_log_mark('a');
+        assert_eq!(top_line(&app), marked);
+
+        app.jump_log_mark('b');
+        assert!(app.flash.as_ref().is_some_and(|f| f.is_error));
+        let _ = std::fs::remove_file(app.jobs[0].stdout.as_ref().unwrap());
+    }
+
+    #[test]
+    fn command_history_keeps_the_typed_line() {
+        let mut app = app_with(&["1"]);
+        app.command_history = vec!["filter state=R".to_string()];
+        let press =
+            |app: &mut App, code| app.handle_command_key(KeyEvent::new(code, KeyModifiers::NONE));
+        app.command_input = Some(CommandInput {
+            text: "so".to_string(),
+            ..CommandInput::default()
+        });
+        press(&mut app, KeyCode::Down);
+        assert_eq!(app.command_input.as_ref().unwrap().text, "so");
+        press(&mut app, KeyCode::Up);
+        assert_eq!(app.command_input.as_ref().unwrap().text, "filter state=R");
+        press(&mut app, KeyCode::Down);
+        assert_eq!(app.command_input.as_ref().unwrap().text, "so");
+
+        // A line that doesn't parse isn't remembered
+        app.command_input = Some(CommandInput {
+            text: "frobnicate".to_string(),
+            ..CommandInput::default()
+        });
+        press(&mut app, KeyCode::Enter);
+        assert_eq!(app.command_history, ["filter state=R"]);
+        assert!(app.flash.as_ref().is_some_and(|f| f.is_error));
+    }
+
+    fn filter_for(text: &str) -> Option<JobFilter> {
+        match command::parse(&format!("filter {}", text)) {
+            Ok(Command::Filter(filter)) => filter,
+            _ => panic!("bad filter '{}'", text),
+        }
+    }
+
+    fn selected_id(app: &App) -> Option<&str> {
+        app.selected_job().map(|j| j.job_id.as_str())
+    }
+
+    #[test]
+    fn goto_exact_prefix_and_ambiguous_ids() {
+        let mut app = app_with(&["1234", "12345", "12350", "999"]);
+        app.goto_job("12345", false);
+        assert_eq!(selected_id(&app), Some("12345"));
+        app.goto_job(" 99 ", true);
+        assert_eq!(selected_id(&app), Some("999"));
+        assert_eq!(app.focus, FocusPanel::Log);
+
+        // An exact ID wins over the longer ones it starts
+        app.goto_job("1234", false);
+        assert_eq!(selected_id(&app), Some("1234"));
+        app.goto_job("123", false);
+        assert_eq!(selected_id(&app), Some("1234"));
+        assert!(
+            app.flash
+                .as_ref()
+                .is_some_and(|f| f.text.starts_with("3 jobs start with 123"))
+        );
+
+        app.goto_job("", false);
+        assert!(
+            app.flash
+                .as_ref()
+                .is_some_and(|f| f.is_error && f.text == "No job ID given")
+        );
+        assert!(app.goto_lookup.is_none());
+    }
+
+    #[test]
+    fn goto_reports_filtered_and_missing_jobs() {
+        let mut app = app_with(&["1", "2"]);
+        app.job_filter = filter_for("id:1");
+        app.marked.insert("2".to_string());
+        app.update_jobs(vec![Job::test("1"), Job::test("2")]);
+        assert!(app.is_marked("2"), "a filtered-out job keeps its mark");
+
+        app.goto_job("2", false);
+        assert_eq!(selected_id(&app), Some("1"));
+        assert!(
+            app.flash
+                .as_ref()
+                .is_some_and(|f| f.is_error && f.text.contains("hidden by the filter"))
+        );
+        assert!(app.goto_lookup.is_none());
+
+        // sacct's answers arrive later
+        app.finish_goto("3", false, Ok(None));
+        assert!(
+            app.flash
+                .as_ref()
+                .is_some_and(|f| f.is_error && f.text == "No job 3")
+        );
+        app.finish_goto("20", false, Ok(Some(Job::test("20"))));
+        assert_eq!(selected_id(&app), Some("1"));
+        app.job_filter = None;
+        app.finish_goto("20", true, Ok(Some(Job::test("20"))));
+        assert_eq!(selected_id(&app), Some("20"));
+        assert_eq!(app.focus, FocusPanel::Log);
+
+        // The looked-up job stays on refresh while selected, unless a filter hides it
+        app.update_jobs(vec![Job::test("1")]);
+        assert_eq!(selected_id(&app), Some("20"));
+        app.job_filter = filter_for("id:1");
+        app.update_jobs(vec![Job::test("1")]);
+        assert_eq!(
+            app.jobs
+                .iter()
+                .map(|j| j.job_id.as_str())
+                .collect::<Vec<_>>(),
+            ["1"]
+        );
+    }
+
+    #[test]
+    fn command_history_is_saved_to_its_file() {
+        let path = std::env::temp_dir().join(format!("ylurm_test_history_{}", std::process::id()));
+        std::fs::write(&path, "sort id\n").unwrap();
+        let mut app = App::without_jobs(Config::default(), Some(path.clone()));
+        assert_eq!(app.command_history, ["sort id"]);
+        app.command_input = Some(CommandInput {
+            text: "refresh".to_string(),
+            ..CommandInput::default()
+        });
+        app.handle_command_key(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE));
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap(),
+            "sort id\nrefresh\n"
+        );
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[test]
+    fn job_at_skips_the_border_and_header() {
+        let ids: Vec<String> = (0..20).map(|i| i.to_string()).collect();
+        let mut app = app_with_jobs(ids.iter().map(|id| Job::test(id)).collect());
+        app.job_list_area = Rect::new(0, 5, 40, 10);
+        // Top border at row 5, header at 6, jobs from 7 to 13, bottom border at 14
+        assert_eq!(app.job_at(10, 5), None);
+        assert_eq!(app.job_at(10, 6), None);
+        assert_eq!(app.job_at(10, 7), Some(0));
+        assert_eq!(app.job_at(10, 13), Some(6));
+        assert_eq!(app.job_at(10, 14), None);
+        assert_eq!(app.job_at(0, 7), None);
+        assert_eq!(app.job_at(39, 7), None);
+
+        *app.table_state.offset_mut() = 12;
+        assert_eq!(app.job_at(10, 7), Some(12));
+        assert_eq!(app.job_at(10, 13), Some(18));
+        *app.table_state.offset_mut() = 15;
+        assert_eq!(app.job_at(10, 13), None);
+    }
+
+    #[test]
+    fn double_click_opens_the_log_zoomed() {
+        let mut app = app_with(&["1", "2", "3"]);
+        app.job_list_area = Rect::new(0, 0, 40, 10);
+        app.click_job(5, 3);
+        assert_eq!(selected_id(&app), Some("2"));
+        assert!(!app.zoomed);
+        // A second click on another job is a new first click
+        app.click_job(5, 2);
+        assert!(!app.zoomed);
+        app.click_job(5, 2);
+        assert_eq!(selected_id(&app), Some("1"));
+        assert_eq!((app.focus, app.zoomed), (FocusPanel::Log, true));
+
+        // A refresh between the clicks that puts another job on the clicked row
+        app.zoomed = false;
+        app.click_job(5, 2);
+        app.update_jobs(vec![Job::test("0"), Job::test("1"), Job::test("2")]);
+        app.click_job(5, 2);
+        assert_eq!(selected_id(&app), Some("0"));
+        assert!(!app.zoomed);
+    }
+
+    #[test]
+    fn pinned_pane_keeps_its_own_scroll() {
+        let mut app = app_with_jobs(vec![job_with_log("1", 300), job_with_log("2", 300)]);
+        app.log.area = Rect::new(0, 0, 80, 22);
+        app.ensure_log_loaded();
+        app.toggle_pin();
+        app.next_job();
+        app.ensure_log_loaded();
+        app.with_pinned(|app| {
+            app.log.area = Rect::new(0, 0, 80, 22);
+            app.ensure_log_loaded();
+        });
+
+        app.scroll_log_top();
+        app.scroll_log_down(5);
+        app.with_pinned(|app| {
+            app.scroll_log_top();
+            app.scroll_log_down(40);
+        });
+        assert_eq!(app.log.scroll, 5);
+        assert_eq!(app.with_pinned(|app| app.log.scroll), Some(40));
+        let first_line = |app: &App| app.log.window.as_ref().map(|w| w.lines[0].clone());
+        assert_eq!(first_line(&app).as_deref(), Some("job 2 line 1"));
+        assert_eq!(
+            app.with_pinned(|app| first_line(app)).flatten().as_deref(),
+            Some("job 1 line 1")
+        );
+
+        app.sync_scroll = true;
+        app.scroll_log(Scroll::Down(3));
+        assert_eq!(app.log.scroll, 8);
+        assert_eq!(app.with_pinned(|app| app.log.scroll), Some(43));
+        assert!(!app.pinned_swapped);
+
+        for job in &app.jobs {
+            let _ = std::fs::remove_file(job.stdout.as_ref().unwrap());
+        }
+    }
+
+    #[test]
+    fn tab_visits_every_shown_panel_and_zoom_follows_focus() {
+        let mut app = app_with_jobs(vec![job_with_log("1", 10)]);
+        let tab_order = |app: &mut App| {
+            app.focus = FocusPanel::Jobs;
+            let mut order = vec![app.focus];
+            loop {
+                app.cycle_focus();
+                if app.focus == FocusPanel::Jobs {
+                    break order;
+                }
+                order.push(app.focus);
+            }
+        };
+        assert_eq!(
+            tab_order(&mut app),
+            [FocusPanel::Jobs, FocusPanel::Log, FocusPanel::Details]
+        );
+
+        // The pinned pane comes right after the log, but only while the Log tab shows it
+        app.toggle_pin();
+        assert_eq!(
+            tab_order(&mut app),
+            [
+                FocusPanel::Jobs,
+                FocusPanel::Log,
+                FocusPanel::Pinned,
+                FocusPanel::Details
+            ]
+        );
+        app.preview_tab = PreviewTab::Script;
+        assert_eq!(
+            tab_order(&mut app),
+            [FocusPanel::Jobs, FocusPanel::Log, FocusPanel::Details]
+        );
+        app.preview_tab = PreviewTab::Log;
+
+        for panel in [
+            FocusPanel::Jobs,
+            FocusPanel::Log,
+            FocusPanel::Pinned,
+            FocusPanel::Details,
+        ] {
+            app.focus = panel;
+            assert_eq!(app.zoomed_panel(), None);
+            app.toggle_zoom();
+            assert_eq!(app.zoomed_panel(), Some(panel));
+            app.toggle_zoom();
+            assert_eq!(app.zoomed_panel(), None);
+        }
+
+        // Tab while zoomed zooms the next panel instead
+        app.focus = FocusPanel::Log;
+        app.toggle_zoom();
+        app.cycle_focus();
+        assert_eq!(app.zoomed_panel(), Some(FocusPanel::Pinned));
+        let _ = std::fs::remove_file(app.jobs[0].stdout.as_ref().unwrap());
+    }
+
+    #[test]
+    fn job_list_share_stays_within_limits() {
+        let mut config = Config::default();
+        config.layout.jobs_percent = 100;
+        let mut app = App::without_jobs(config, None);
+        assert_eq!(app.jobs_percent, MAX_JOBS_PERCENT);
+        app.resize_jobs(-200);
+        assert_eq!(app.jobs_percent, MIN_JOBS_PERCENT);
+        app.resize_jobs(5);
+        assert_eq!(app.jobs_percent, MIN_JOBS_PERCENT + 5);
+
+        app.main_area = Rect::new(10, 0, 100, 40);
+        app.drag_divider(5, 20);
+        assert_eq!(app.jobs_percent, MIN_JOBS_PERCENT);
+        app.drag_divider(40, 20);
+        assert_eq!(app.jobs_percent, 30);
+        app.drag_divider(109, 20);
+        assert_eq!(app.jobs_percent, MAX_JOBS_PERCENT);
+    }
+
+    #[test]
+    fn divider_follows_the_orientation() {
+        let mut app = App::without_jobs(Config::default(), None);
+        app.main_area = Rect::new(0, 0, 100, 40);
+        app.job_list_area = Rect::new(0, 0, 45, 40);
+        // Side by side: the job list's right border and the column after it
+        assert!(app.on_divider(44, 10) && app.on_divider(45, 10));
+        assert!(!app.on_divider(43, 10) && !app.on_divider(46, 10) && !app.on_divider(45, 40));
+        app.drag_divider(60, 10);
+        assert_eq!(app.jobs_percent, 60);
+
+        app.config.layout.orientation = "vertical".to_string();
+        app.job_list_area = Rect::new(0, 0, 100, 18);
+        assert!(app.on_divider(50, 17) && app.on_divider(50, 18));
+        assert!(!app.on_divider(50, 16) && !app.on_divider(50, 19) && !app.on_divider(100, 18));
+        app.drag_divider(50, 10);
+        assert_eq!(app.jobs_percent, 25);
+
+        // Nothing to drag while one panel fills the screen
+        app.toggle_zoom();
+        assert!(!app.on_divider(50, 18));
+    }
+
+    #[test]
+    fn hiding_the_focused_panel_moves_focus_to_the_job_list() {
+        let mut app = app_with(&["1"]);
+        app.focus = FocusPanel::Details;
+        app.run_command_line("set show_details=false");
+        assert_eq!(app.focus, FocusPanel::Jobs);
+        app.cycle_focus();
+        assert_eq!(app.focus, FocusPanel::Log);
+
+        app.run_command_line("set show_log=false");
+        assert_eq!(app.focus, FocusPanel::Jobs);
+        app.cycle_focus();
+        assert_eq!(app.focus, FocusPanel::Jobs);
+
+        // The legacy `[display] show_details` hides the panel as well
+        let mut config = Config::default();
+        config.display.show_details = false;
+        let app = App::without_jobs(config, None);
+        assert!(!app.panel_shown(FocusPanel::Details));
+    }
+
+    #[test]
+    fn wrapped_log_scrolls_by_row_and_sticks_to_the_bottom() {
+        // 50-column lines in a 20-column pane take 3 rows each; the viewport is 10 rows
+        let mut app = app_with_jobs(vec![job_with_log("1", 0)]);
+        let path = app.jobs[0].stdout.clone().unwrap();
+        let wide = |lines: usize| {
+            (1..=lines)
+                .map(|i| format!("{:02}{}\n", i, "x".repeat(48)))
+                .collect::<String>()
+        };
+        std::fs::write(&path, wide(20)).unwrap();
+        app.wrap_logs = true;
+        app.log.area = Rect::new(0, 0, 22, 12);
+        app.ensure_log_loaded();
+        let position = |app: &App| (app.log.scroll, app.log_top_row());
+
+        // The bottom is the last row of line 20 on the viewport's last row
+        assert_eq!(position(&app), (16, 2));
+        assert!(app.is_at_bottom());
+        assert_eq!(app.last_visible_log_line(), 19);
+        app.scroll_log_up(4);
+        assert_eq!(position(&app), (15, 1));
+        assert!(!app.is_at_bottom());
+        app.scroll_log_down(100);
+        assert_eq!(position(&app), (16, 2));
+
+        // Following the end: new lines keep the view at the bottom
+        std::fs::write(&path, wide(21)).unwrap();
+        app.log.last_key = None;
+        app.ensure_log_loaded();
+        assert_eq!(position(&app), (17, 2));
+
+        // Scrolled up a row: new lines leave the view where it was
+        app.scroll_log_up(1);
+        std::fs::write(&path, wide(22)).unwrap();
+        app.log.last_key = None;
+        app.ensure_log_loaded();
+        assert_eq!(position(&app), (17, 1));
+        let _ = std::fs::remove_file(path);
+    }
+}
@@ -0,0 +1,501 @@
+use std::cmp::Ordering;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::action::{ALL_ACTIONS, Action};
+use crate::config::SETTINGS;
+use crate::slurm::{Job, JobState};
+use crate::ui::THEMES;
+
+/// Command lines kept in the history file
+const HISTORY_LIMIT: usize = 500;
+
+/// Commands that aren't key actions, with their usage for the help line
+pub const BUILTINS: &[(&str, &str)] = &[
+    (
+        "filter",
+        "filter [user:U] [state:S] [partition:P] [name:N] [id:PREFIX] [node:N] [TEXT] (none clears)",
+    ),
+    (
+        "sort",
+        "sort id|name|user|state|time|partition|nodes [asc|desc] (none restores squeue order)",
+    ),
+    ("set", "set KEY=VALUE, or set KEY to show it"),
+    ("goto", "goto JOBID (exact or prefix)"),
+    ("theme", "theme NAME"),
+];
+
+const FILTER_FIELDS: &[&str] = &["user:", "state:", "partition:", "name:", "id:", "node:"];
+
+/// A parsed `:` command line
+#[derive(Debug, Clone, PartialEq)]
+pub enum Command {
+    Action(Action),
+    /// None clears the filter
+    Filter(Option<JobFilter>),
+    /// None restores squeue's order
+    Sort(Option<JobSort>),
+    /// A setting and its new value; no value shows the current one
+    Set(String, Option<String>),
+    Goto(String),
+    Theme(String),
+}
+
+/// Action by command name: its `[keybindings]` name, or without a `_job` suffix (`:cancel`)
+fn action_named(name: &str) -> Option<Action> {
+    Action::from_config_name(name).or_else(|| Action::from_config_name(&format!("{}_job", name)))
+}
+
+fn command_names() -> impl Iterator<Item = &'static str> {
+    let actions = ALL_ACTIONS.iter().map(|a| {
+        a.config_name()
+            .strip_suffix("_job")
+            .unwrap_or(a.config_name())
+    });
+    BUILTINS.iter().map(|(name, _)| *name).chain(actions)
+}
+
+pub fn parse(line: &str) -> Result<Command, String> {
+    let line = line.trim();
+    let (name, rest) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+    let rest = rest.trim();
+    let args: Vec<&str> = rest.split_whitespace().collect();
+    match name {
+        "filter" => JobFilter::parse(rest).map(Command::Filter),
+        "sort" => JobSort::parse(&args).map(Command::Sort),
+        "set" => {
+            if rest.is_empty() {
+                return Err(format!("Usage: set KEY=VALUE ({})", SETTINGS.join(", ")));
+            }
+            let (key, value) = match rest.split_once('=') {
+                Some((key, value)) => (key.trim(), Some(value.trim().to_string())),
+                None => match rest.split_once(char::is_whitespace) {
+                    Some((key, value)) => (key, Some(value.trim().to_string())),
+                    None => (rest, None),
+                },
+            };
+            Ok(Command::Set(key.to_string(), value))
+        }
+        "goto" => match args.as_slice() {
+            [id] => Ok(Command::Goto(id.to_string())),
+            _ => Err("Usage: goto JOBID".to_string()),
+        },
+        "theme" => match args.as_slice() {
+            [theme] => Ok(Command::Theme(theme.to_string())),
+            _ => Err(format!("Usage: theme {}", THEMES.join("|"))),
+        },
+        "q" => Ok(Command::Action(Action::Quit)),
+        _ => match action_named(name) {
+            Some(action) if args.is_empty() => Ok(Command::Action(action)),
+            Some(_) => Err(format!("{} takes no arguments", name)),
+            None => Err(format!("Unknown command '{}'", name)),
+        },
+    }
+}
+
+/// Completions of the last word of `line`, each as the whole new line. A single
+/// complete word gets a trailing space so the next Tab moves on to its arguments.
+pub fn complete(line: &str, jobs: &[Job]) -> Vec<String> {
+    let split = line.rfind(' ').map_or(0, |i| i + 1);
+    let (head, word) = line.split_at(split);
+    let words: Vec<&str> = head.split_whitespace().collect();
+
+    let mut options: Vec<String> = match words.as_slice() {
+        [] => command_names().map(str::to_string).collect(),
+        ["set"] => SETTINGS.iter().map(|s| format!("{}=", s)).collect(),
+        ["theme"] => THEMES.iter().map(|t| t.to_string()).collect(),
+        ["sort"] => SortKey::ALL.iter().map(|k| k.name().to_string()).collect(),
+        ["sort", _] => vec!["asc".to_string(), "desc".to_string()],
+        ["goto"] => jobs.iter().map(|j| j.job_id.clone()).collect(),
+        ["filter", ..] => match word.split_once(':') {
+            Some((field, _)) => filter_values(field, jobs)
+                .into_iter()
+                .map(|v| format!("{}:{}", field, v))
+                .collect(),
+            None => FILTER_FIELDS.iter().map(|f| f.to_string()).collect(),
+        },
+        _ => Vec::new(),
+    };
+    options.retain(|o| o.starts_with(word));
+    options.sort();
+    options.dedup();
+    let single = options.len() == 1;
+    options
+        .into_iter()
+        .map(|o| {
+            let space = if single && !o.ends_with([':', '=']) {
+                " "
+            } else {
+                ""
+            };
+            format!("{}{}{}", head, o, space)
+        })
+        .collect()
+}
+
+/// Values seen in the job list for a filter field
+fn filter_values(field: &str, jobs: &[Job]) -> Vec<String> {
+    jobs.iter()
+        .filter_map(|j| match field {
+            "user" => Some(j.user.clone()),
+            "state" => Some(j.state.as_str().to_string()),
+            "partition" => Some(j.partition.clone()),
+            "id" => Some(j.job_id.clone()),
+            _ => None,
+        })
+        .collect()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum FilterField {
+    User,
+    State,
+    Partition,
+    Name,
+    Id,
+    Node,
+    /// Bare text: ID, name or user
+    Any,
+}
+
+/// Jobs kept in the list: every term has to match
+#[derive(Debug, Clone, PartialEq)]
+pub struct JobFilter {
+    terms: Vec<(FilterField, String)>,
+}
+
+impl JobFilter {
+    /// `user:bob state:R train`; empty text means no filter
+    fn parse(text: &str) -> Result<Option<Self>, String> {
+        let mut terms = Vec::new();
+        for term in text.split_whitespace() {
+            let (field, value) = match term.split_once(':') {
+                Some((field, value)) => {
+                    let field = match field {
+                        "user" | "u" => FilterField::User,
+                        "state" | "st" => FilterField::State,
+                        "partition" | "part" | "p" => FilterField::Partition,
+                        "name" | "n" => FilterField::Name,
+                        "id" | "job" => FilterField::Id,
+                        "node" | "nodelist" => FilterField::Node,
+                        other => return Err(format!("Unknown filter field '{}'", other)),
+                    };
+                    (field, value)
+                }
+                None => (FilterField::Any, term),
+            };
+            if value.is_empty() {
+                return Err(format!("Missing value in '{}'", term));
+            }
+            terms.push((field, value.to_lowercase()));
+        }
+        Ok((!terms.is_empty()).then_some(Self { terms }))
+    }
+
+    pub fn matches(&self, job: &Job) -> bool {
+        self.terms.iter().all(|(field, value)| {
+            let contains = |s: &str| s.to_lowercase().contains(value.as_str());
+            match field {
+                FilterField::User => job.user.eq_ignore_ascii_case(value),
+                FilterField::State => JobState::from_str(&value.to_uppercase()) == job.state,
+                FilterField::Partition => job.partition.eq_ignore_ascii_case(value),
+                FilterField::Name => contains(&job.name),
+                FilterField::Id => job.job_id.starts_with(value.as_str()),
+                FilterField::Node => contains(&job.nodelist),
+                FilterField::Any => {
+                    contains(&job.job_id) || contains(&job.name) || contains(&job.user)
+                }
+            }
+        })
+    }
+}
+
+impl fmt::Display for JobFilter {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let terms: Vec<String> = self
+            .terms
+            .iter()
+            .map(|(field, value)| match field {
+                FilterField::User => format!("user:{}", value),
+                FilterField::State => format!("state:{}", value),
+                FilterField::Partition => format!("partition:{}", value),
+                FilterField::Name => format!("name:{}", value),
+                FilterField::Id => format!("id:{}", value),
+                FilterField::Node => format!("node:{}", value),
+                FilterField::Any => value.clone(),
+            })
+            .collect();
+        f.write_str(&terms.join(" "))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum SortKey {
+    Id,
+    Name,
+    User,
+    State,
+    Time,
+    Partition,
+    Nodes,
+}
+
+impl SortKey {
+    const ALL: [SortKey; 7] = [
+        SortKey::Id,
+        SortKey::Name,
+        SortKey::User,
+        SortKey::State,
+        SortKey::Time,
+        SortKey::Partition,
+        SortKey::Nodes,
+    ];
+
+    fn name(self) -> &'static str {
+        match self {
+            SortKey::Id => "id",
+            SortKey::Name => "name",
+            SortKey::User => "user",
+            SortKey::State => "state",
+            SortKey::Time => "time",
+            SortKey::Partition => "partition",
+            SortKey::Nodes => "nodes",
+        }
+    }
+}
+
+/// Job list order
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct JobSort {
+    key: SortKey,
+    descending: bool,
+}
+
+impl JobSort {
+    /// `time desc`; no arguments means squeue's order
+    fn parse(args: &[&str]) -> Result<Option<Self>, String> {
+        let (key, order) = match args {
+            [] => return Ok(None),
+            [key] => (*key, "asc"),
+            [key, order] => (*key, *order),
+            _ => return Err("Usage: sort KEY [asc|desc]".to_string()),
+        };
+        let key = SortKey::ALL
+            .into_iter()
+            .find(|k| k.name() == key)
+            .ok_or_else(|| format!("Can't sort by '{}'", key))?;
+        let descending = match order {
+            "asc" => false,
+            "desc" => true,
+            other => return Err(format!("Sort order must be asc or desc, not '{}'", other)),
+        };
+        Ok(Some(Self { key, descending }))
+    }
+
+    /// Stable, so jobs with equal keys keep squeue's order
+    pub fn sort(&self, jobs: &mut [Job]) {
+        jobs.sort_by(|a, b| {
+            let order = self.compare(a, b);
+            if self.descending {
+                order.reverse()
+            } else {
+                order
+            }
+        });
+    }
+
+    fn compare(&self, a: &Job, b: &Job) -> Ordering {
+        match self.key {
+            SortKey::Id => id_key(&a.job_id).cmp(&id_key(&b.job_id)),
+            SortKey::Name => a.name.cmp(&b.name),
+            SortKey::User => a.user.cmp(&b.user),
+            SortKey::State => a.state.as_str().cmp(b.state.as_str()),
+            SortKey::Time => duration_secs(&a.time).cmp(&duration_secs(&b.time)),
+            SortKey::Partition => a.partition.cmp(&b.partition),
+            SortKey::Nodes => a
+                .nodes
+                .parse::<u64>()
+                .unwrap_or(0)
+                .cmp(&b.nodes.parse::<u64>().unwrap_or(0)),
+        }
+    }
+}
+
+impl fmt::Display for JobSort {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} {}",
+            self.key.name(),
+            if self.descending { "desc" } else { "asc" }
+        )
+    }
+}
+
+/// Numeric order for IDs like "1234" and "1234_7" (array tasks)
+fn id_key(id: &str) -> (u64, u64, &str) {
+    let mut parts = id.splitn(2, '_');
+    let main = parts
+        .next()
+        .and_then(|p| p.parse().ok())
+        .unwrap_or(u64::MAX);
+    let task = parts.next().and_then(|p| p.parse().ok()).unwrap_or(0);
+    (main, task, id)
+}
+
+/// Seconds in a squeue time like "1-02:03:04", "12:34" or "0:05"
+fn duration_secs(time: &str) -> u64 {
+    let (days, clock) = match time.split_once('-') {
+        Some((days, clock)) => (days.parse().unwrap_or(0), clock),
+        None => (0, time),
+    };
+    let clock_secs = clock.split(':').fold(0u64, |acc, part| {
+        acc * 60 + part.parse::<u64>().unwrap_or(0)
+    });
+    days * 86_400 + clock_secs
+}
+
+/// Where command history is kept between sessions
+pub fn history_path() -> Option<PathBuf> {
+    dirs::state_dir()
+        .or_else(dirs::data_local_dir)
+        .map(|dir| dir.join("ylurm").join("history"))
+}
+
+pub fn load_history(path: &Path) -> Vec<String> {
+    fs::read_to_string(path)
+        .map(|text| {
+            text.lines()
+                .filter(|l| !l.trim().is_empty())
+                .map(str::to_string)
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Write the most recent entries back; a failure only costs the history
+pub fn save_history(path: &Path, history: &[String]) -> Result<(), String> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+    }
+    let start = history.len().saturating_sub(HISTORY_LIMIT);
+    let mut text = history[start..].join("\n");
+    text.push('\n');
+    fs::write(path, text).map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn job(id: &str, user: &str, state: &str, time: &str, name: &str) -> Job {
+        Job {
+            name: name.to_string(),
+            user: user.to_string(),
+            state: JobState::from_str(state),
+            time: time.to_string(),
+            ..Job::test(id)
+        }
+    }
+
+    #[test]
+    fn parse_commands() {
+        assert_eq!(parse("cancel"), Ok(Command::Action(Action::CancelJob)));
+        assert_eq!(parse("cancel_job"), Ok(Command::Action(Action::CancelJob)));
+        assert_eq!(parse("refresh"), Ok(Command::Action(Action::Refresh)));
+        assert_eq!(parse("q"), Ok(Command::Action(Action::Quit)));
+        assert_eq!(
+            parse("set refresh_interval=5"),
+            Ok(Command::Set("refresh_interval".into(), Some("5".into())))
+        );
+        assert_eq!(
+            parse("set wrap_logs"),
+            Ok(Command::Set("wrap_logs".into(), None))
+        );
+        assert_eq!(
+            parse("set theme=minimal"),
+            Ok(Command::Set("theme".into(), Some("minimal".into())))
+        );
+        assert_eq!(parse("goto 123456"), Ok(Command::Goto("123456".into())));
+        assert_eq!(parse("filter"), Ok(Command::Filter(None)));
+        assert_eq!(parse("sort"), Ok(Command::Sort(None)));
+        assert!(parse("bogus").is_err());
+        assert!(parse("cancel now").is_err());
+        assert!(parse("sort time sideways").is_err());
+        assert!(parse("filter colour:red").is_err());
+    }
+
+    #[test]
+    fn filter_and_sort_jobs() {
+        let mut jobs = vec![
+            job("200", "bob", "R", "1:00:00", "train-a"),
+            job("100_2", "alice", "PD", "0:00", "eval"),
+            job("100_10", "bob", "R", "1-00:00:00", "train-b"),
+        ];
+        let Ok(Command::Filter(Some(filter))) = parse("filter user:Bob state:running") else {
+            panic!()
+        };
+        let kept: Vec<&str> = jobs
+            .iter()
+            .filter(|j| filter.matches(j))
+            .map(|j| j.job_id.as_str())
+            .collect();
+        assert_eq!(kept, vec!["200", "100_10"]);
+        assert_eq!(filter.to_string(), "user:bob state:running");
+        let Ok(Command::Filter(Some(text))) = parse("filter TRAIN id:1") else {
+            panic!()
+        };
+        assert!(
+            jobs.iter()
+                .filter(|j| text.matches(j))
+                .map(|j| j.job_id.as_str())
+                .eq(["100_10"])
+        );
+
+        let Ok(Command::Sort(Some(sort))) = parse("sort time desc") else {
+            panic!()
+        };
+        sort.sort(&mut jobs);
+        assert!(
+            jobs.iter()
+                .map(|j| j.job_id.as_str())
+                .eq(["100_10", "200", "100_2"])
+        );
+        let Ok(Command::Sort(Some(sort))) = parse("sort id") else {
+            panic!()
+        };
+        sort.sort(&mut jobs);
+        assert!(
+            jobs.iter()
+                .map(|j| j.job_id.as_str())
+                .eq(["100_2", "100_10", "200"])
+        );
+        assert_eq!(
+            duration_secs("2-03:04:05"),
+            2 * 86_400 + 3 * 3600 + 4 * 60 + 5
+        );
+    }
+
+    #[test]
+    fn completion() {
+        let jobs = vec![
+            job("4242", "bob", "R", "0:01", "x"),
+            job("4250", "carol", "R", "0:01", "y"),
+        ];
+        assert_eq!(complete("canc", &jobs), vec!["cancel "]);
+        assert!(complete("s", &jobs).contains(&"sort".to_string()));
+        assert_eq!(
+            complete("set refresh", &jobs),
+            vec!["set refresh_interval="]
+        );
+        assert_eq!(complete("sort time d", &jobs), vec!["sort time desc "]);
+        assert_eq!(complete("goto 42", &jobs), vec!["goto 4242", "goto 4250"]);
+        assert_eq!(
+            complete("filter state:R user:c", &jobs),
+            vec!["filter state:R user:carol "]
+        );
+        assert_eq!(complete("filter us", &jobs), vec!["filter user:"]);
+        assert_eq!(complete("theme sol", &jobs), vec!["theme solarized "]);
+    }
+}
@@ -3,13 +3,20 @@ use std::collections::HashMap;
 use std::fs;
 use std::path::PathBuf;
 
-#[derive(Debug, Deserialize, Clone)]
+use crate::action::Panel;
+use crate::ui::{THEMES, Theme};
+
+#[derive(Debug, Deserialize, Clone, Default)]
 #[serde(default)]
 pub struct Config {
     pub general: GeneralConfig,
     pub keybindings: KeyBindings,
     pub display: DisplayConfig,
+    pub layout: LayoutConfig,
     pub remote: RemoteConfig,
+    pub actions: ActionsConfig,
+    pub analyzer: AnalyzerConfig,
+    pub metrics: MetricsConfig,
 }
 
 #[derive(Debug, Deserialize, Clone)]
@@ -23,18 +30,125 @@ pub struct GeneralConfig {
     pub all_users: bool,
 }
 
+/// Keys bound to one action: a single string or a list, each in key notation
+/// ("j", "C-r", "<F5>", "gg", "<C-w>j")
+#[derive(Debug, Deserialize, Clone, PartialEq)]
+#[serde(from = "KeysRepr")]
+pub struct Keys(pub Vec<String>);
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum KeysRepr {
+    One(String),
+    Many(Vec<String>),
+}
+
+impl From<KeysRepr> for Keys {
+    fn from(repr: KeysRepr) -> Self {
+        match repr {
+            KeysRepr::One(key) => Keys(vec![key]),
+            KeysRepr::Many(keys) => Keys(keys),
+        }
+    }
+}
+
+impl Keys {
+    fn of(keys: &[&str]) -> Self {
+        Keys(keys.iter().map(|k| k.to_string()).collect())
+    }
+}
+
 #[derive(Debug, Deserialize, Clone)]
 #[serde(default)]
 pub struct KeyBindings {
-    pub quit: String,
-    pub up: String,
-    pub down: String,
-    pub top: String,
-    pub bottom: String,
-    pub toggle_logs: String,
-    pub cancel_job: String,
-    pub refresh: String,
-    pub ssh_view_log: String,
+    pub quit: Keys,
+    pub up: Keys,
+    pub down: Keys,
+    pub top: Keys,
+    pub bottom: Keys,
+    pub toggle_logs: Keys,
+    pub cancel_job: Keys,
+    pub refresh: Keys,
+    /// Open an interactive shell on the selected running job
+    #[serde(alias = "ssh_view_log")]
+    pub open_shell: Keys,
+    /// Toggle the mark on the selected job
+    pub mark: Keys,
+    /// Mark every listed job (or clear marks if all are already marked)
+    pub mark_all: Keys,
+    /// Start/finish a visual range selection
+    pub visual_mark: Keys,
+    /// Copy job IDs of the marked jobs (or the selected job)
+    pub copy_ids: Keys,
+    pub hold_job: Keys,
+    pub release_job: Keys,
+    /// Edit the selected job's limits via scontrol update
+    pub edit_job: Keys,
+    /// Send a signal to marked (or selected) jobs
+    pub signal_job: Keys,
+    /// Open the sbatch submission form
+    pub submit_job: Keys,
+    /// Edit and resubmit the selected job's batch script
+    pub resubmit_job: Keys,
+    /// Switch the preview panel between Log and Script
+    pub next_tab: Keys,
+    /// Open the full log in $PAGER
+    pub open_pager: Keys,
+    /// Open the full log in $EDITOR
+    pub open_editor: Keys,
+    /// Regex search forward / backward in the log
+    pub search: Keys,
+    pub search_backward: Keys,
+    /// Repeat the last search in the same / opposite direction
+    pub search_next: Keys,
+    pub search_prev: Keys,
+    /// Jump to the next / previous line matching an error signature
+    pub next_error: Keys,
+    pub prev_error: Keys,
+    /// Pin the selected job's log into a second pane (again to unpin)
+    pub pin_log: Keys,
+    /// Show the pinned pane beside or below the main log
+    pub pin_split: Keys,
+    /// Scroll both log panes together
+    pub sync_scroll: Keys,
+    /// Toggle wrapping of long log lines
+    pub toggle_wrap: Keys,
+    /// Scroll unwrapped log lines left / right
+    pub scroll_left: Keys,
+    pub scroll_right: Keys,
+    /// Maximize the focused panel (again to restore the layout)
+    pub zoom: Keys,
+    /// Widen / narrow the job list (or make it taller / shorter when stacked)
+    pub grow_jobs: Keys,
+    pub shrink_jobs: Keys,
+    /// Show the key reference
+    pub help: Keys,
+    /// Open the `:` command line
+    pub command_line: Keys,
+    /// Prompt for a job ID to select
+    pub goto_job: Keys,
+    /// Focus the next panel
+    pub next_panel: Keys,
+    /// Back to the job list (or cancel a visual selection)
+    pub back: Keys,
+    /// Focus the log from the job list
+    pub focus_log: Keys,
+    /// Scroll by a screen / half a screen of the focused panel
+    pub page_down: Keys,
+    pub page_up: Keys,
+    pub half_page_down: Keys,
+    pub half_page_up: Keys,
+    /// Mark the log's top line with the letter typed next / jump back to it
+    pub set_mark: Keys,
+    pub jump_mark: Keys,
+    /// After a count, jump that many percent into the log
+    pub goto_percent: Keys,
+    /// Per-panel overrides, e.g. `[keybindings.log] down = ["j", "C-n"]`
+    pub jobs: HashMap<String, Keys>,
+    pub details: HashMap<String, Keys>,
+    pub log: HashMap<String, Keys>,
+    pub script: HashMap<String, Keys>,
+    pub metrics: HashMap<String, Keys>,
 }
 
 #[derive(Debug, Deserialize, Clone)]
@@ -44,8 +158,37 @@ pub struct DisplayConfig {
     pub columns: Vec<String>,
     /// Color scheme: "default", "minimal", "solarized"
     pub theme: String,
-    /// Show job details panel
+    /// Legacy alias of `[layout] show_details`: false also hides the details panel
     pub show_details: bool,
+    /// Render ANSI colors in logs (false strips them)
+    pub ansi_colors: bool,
+    /// Show a job list column with each job's latest tqdm/Lightning progress
+    pub progress_column: bool,
+    /// Wrap long log lines (otherwise scroll them horizontally)
+    pub wrap_logs: bool,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+#[serde(default)]
+pub struct LayoutConfig {
+    /// Share of the screen given to the job list, in percent (10–90)
+    pub jobs_percent: u16,
+    /// "horizontal" (job list beside details + log) or "vertical" (stacked, for narrow terminals)
+    pub orientation: String,
+    /// Rows of the details panel, before extra rows for a failure cause and progress bars
+    pub details_height: u16,
+    /// Show the job details panel
+    pub show_details: bool,
+    /// Show the log / script / metrics panel
+    pub show_log: bool,
+    /// Terminals narrower than this show one panel at a time, switched with Tab (0 never does)
+    pub compact_width: u16,
+}
+
+impl LayoutConfig {
+    pub fn is_vertical(&self) -> bool {
+        self.orientation.eq_ignore_ascii_case("vertical")
+    }
 }
 
 #[derive(Debug, Deserialize, Clone)]
@@ -60,15 +203,44 @@ pub struct RemoteConfig {
     pub ssh_timeout: u64,
 }
 
-impl Default for Config {
-    fn default() -> Self {
-        Self {
-            general: GeneralConfig::default(),
-            keybindings: KeyBindings::default(),
-            display: DisplayConfig::default(),
-            remote: RemoteConfig::default(),
-        }
-    }
+#[derive(Debug, Deserialize, Clone)]
+#[serde(default)]
+pub struct ActionsConfig {
+    /// Signals offered first in the signal picker (names or numbers)
+    pub signals: Vec<String>,
+    /// How to open a shell on a job: "srun" (overlapping step) or "ssh" (batch host)
+    pub shell_method: String,
+    /// Per-partition override of shell_method
+    pub partition_shell: HashMap<String, String>,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+#[serde(default)]
+pub struct AnalyzerConfig {
+    /// Use the built-in error signatures (Traceback, CUDA OOM, NCCL, oom-kill, ...)
+    pub builtin: bool,
+    /// Extra signatures, checked before the built-ins
+    pub patterns: Vec<ErrorPattern>,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+#[serde(default)]
+pub struct MetricsConfig {
+    /// Recognize key=value pairs, Lightning postfixes and HF Trainer dicts
+    pub builtin: bool,
+    /// Regexes with named captures; each group is a metric, a `step` group is the x axis
+    pub patterns: Vec<String>,
+}
+
+/// A user-defined error signature
+#[derive(Debug, Deserialize, Clone, Default)]
+#[serde(default)]
+pub struct ErrorPattern {
+    pub name: String,
+    /// Regex matched against each log line
+    pub pattern: String,
+    /// One-line explanation shown for failed jobs
+    pub cause: String,
 }
 
 impl Default for GeneralConfig {
@@ -84,15 +256,75 @@ impl Default for GeneralConfig {
 impl Default for KeyBindings {
     fn default() -> Self {
         Self {
-            quit: "q".to_string(),
-            up: "k".to_string(),
-            down: "j".to_string(),
-            top: "g".to_string(),
-            bottom: "G".to_string(),
-            toggle_logs: "o".to_string(),
-            cancel_job: "x".to_string(),
-            refresh: "r".to_string(),
-            ssh_view_log: "s".to_string(),
+            quit: Keys::of(&["q"]),
+            up: Keys::of(&["k"]),
+            down: Keys::of(&["j"]),
+            top: Keys::of(&["g"]),
+            bottom: Keys::of(&["G"]),
+            toggle_logs: Keys::of(&["o"]),
+            cancel_job: Keys::of(&["x"]),
+            refresh: Keys::of(&["r"]),
+            open_shell: Keys::of(&["s"]),
+            mark: Keys::of(&[" "]),
+            mark_all: Keys::of(&["a"]),
+            visual_mark: Keys::of(&["v"]),
+            copy_ids: Keys::of(&["y"]),
+            hold_job: Keys::of(&["H"]),
+            release_job: Keys::of(&["U"]),
+            edit_job: Keys::of(&["e"]),
+            signal_job: Keys::of(&["S"]),
+            submit_job: Keys::of(&["n"]),
+            resubmit_job: Keys::of(&["R"]),
+            next_tab: Keys::of(&["t"]),
+            open_pager: Keys::of(&["p"]),
+            open_editor: Keys::of(&["E"]),
+            search: Keys::of(&["/"]),
+            search_backward: Keys::of(&["?"]),
+            search_next: Keys::of(&["n"]),
+            search_prev: Keys::of(&["N"]),
+            next_error: Keys::of(&["]"]),
+            prev_error: Keys::of(&["["]),
+            pin_log: Keys::of(&["P"]),
+            pin_split: Keys::of(&["|"]),
+            sync_scroll: Keys::of(&["="]),
+            toggle_wrap: Keys::of(&["w"]),
+            scroll_left: Keys::of(&["h"]),
+            scroll_right: Keys::of(&["l"]),
+            zoom: Keys::of(&["z"]),
+            grow_jobs: Keys::of(&[">"]),
+            shrink_jobs: Keys::of(&["<"]),
+            help: Keys::of(&["<F1>"]),
+            command_line: Keys::of(&[":"]),
+            goto_job: Keys::of(&["<C-g>"]),
+            next_panel: Keys::of(&["<Tab>"]),
+            back: Keys::of(&["<Esc>"]),
+            focus_log: Keys::of(&["<Enter>"]),
+            page_down: Keys::of(&["<PgDn>", "<C-f>"]),
+            page_up: Keys::of(&["<PgUp>", "<C-b>"]),
+            half_page_down: Keys::of(&["<C-d>"]),
+            half_page_up: Keys::of(&["<C-u>"]),
+            set_mark: Keys::of(&["m"]),
+            jump_mark: Keys::of(&["'"]),
+            goto_percent: Keys::of(&["%"]),
+            jobs: HashMap::new(),
+            details: HashMap::new(),
+            log: HashMap::new(),
+            script: HashMap::new(),
+            metrics: HashMap::new(),
+        }
+    }
+}
+
+impl KeyBindings {
+    /// `[keybindings.<panel>]` overrides; global actions are overridden per panel
+    pub fn overrides(&self, panel: Panel) -> Option<&HashMap<String, Keys>> {
+        match panel {
+            Panel::Global => None,
+            Panel::Jobs => Some(&self.jobs),
+            Panel::Details => Some(&self.details),
+            Panel::Log => Some(&self.log),
+            Panel::Script => Some(&self.script),
+            Panel::Metrics => Some(&self.metrics),
         }
     }
 }
@@ -112,6 +344,22 @@ impl Default for DisplayConfig {
             ],
             theme: "default".into(),
             show_details: true,
+            ansi_colors: true,
+            progress_column: false,
+            wrap_logs: false,
+        }
+    }
+}
+
+impl Default for LayoutConfig {
+    fn default() -> Self {
+        Self {
+            jobs_percent: 45,
+            orientation: "horizontal".into(),
+            details_height: 10,
+            show_details: true,
+            show_log: true,
+            compact_width: 100,
         }
     }
 }
@@ -131,7 +379,132 @@ impl Default for RemoteConfig {
     }
 }
 
+impl Default for ActionsConfig {
+    fn default() -> Self {
+        Self {
+            signals: vec!["USR1".into(), "USR2".into(), "TERM".into(), "INT".into()],
+            shell_method: "srun".into(),
+            partition_shell: HashMap::new(),
+        }
+    }
+}
+
+impl Default for AnalyzerConfig {
+    fn default() -> Self {
+        Self {
+            builtin: true,
+            patterns: vec![],
+        }
+    }
+}
+
+impl Default for MetricsConfig {
+    fn default() -> Self {
+        Self {
+            builtin: true,
+            patterns: vec![],
+        }
+    }
+}
+
+/// `[general]`, `[display]` and `[layout]` values that `:set` can change while running.
+/// `columns` isn't one: the job list's columns are fixed and don't read it.
+pub const SETTINGS: &[&str] = &[
+    "refresh_interval",
+    "all_users",
+    "squeue_args",
+    "theme",
+    "ansi_colors",
+    "progress_column",
+    "wrap_logs",
+    "show_details",
+    "show_log",
+];
+
+/// `key` without its optional section prefix ("general.all_users")
+fn setting_name(key: &str) -> &str {
+    ["general.", "display.", "layout."]
+        .iter()
+        .find_map(|p| key.strip_prefix(p))
+        .unwrap_or(key)
+}
+
+fn parse_bool(value: &str) -> Result<bool, String> {
+    match value {
+        "true" | "on" | "yes" | "1" => Ok(true),
+        "false" | "off" | "no" | "0" => Ok(false),
+        other => Err(format!("expected true or false, not '{}'", other)),
+    }
+}
+
 impl Config {
+    /// Current value of a `:set` setting; the section prefix is optional ("general.all_users")
+    pub fn get(&self, key: &str) -> Option<String> {
+        let value = match setting_name(key) {
+            "refresh_interval" => self.general.refresh_interval.to_string(),
+            "all_users" => self.general.all_users.to_string(),
+            "squeue_args" => self.general.squeue_args.join(" "),
+            "theme" => self.display.theme.clone(),
+            "ansi_colors" => self.display.ansi_colors.to_string(),
+            "progress_column" => self.display.progress_column.to_string(),
+            "wrap_logs" => self.display.wrap_logs.to_string(),
+            "show_details" => (self.layout.show_details && self.display.show_details).to_string(),
+            "show_log" => self.layout.show_log.to_string(),
+            _ => return None,
+        };
+        Some(value)
+    }
+
+    /// Change a `:set` setting for this session. `squeue_args` takes the arguments separated
+    /// by spaces.
+    pub fn set(&mut self, key: &str, value: &str) -> Result<(), String> {
+        let key = setting_name(key);
+        match key {
+            "refresh_interval" => {
+                self.general.refresh_interval = match value.parse() {
+                    Ok(secs) if secs > 0 => secs,
+                    _ => {
+                        return Err(format!(
+                            "refresh_interval must be a whole number of seconds, not '{}'",
+                            value
+                        ));
+                    }
+                }
+            }
+            "all_users" => self.general.all_users = parse_bool(value)?,
+            "squeue_args" => {
+                self.general.squeue_args = value.split_whitespace().map(str::to_string).collect()
+            }
+            "theme" => {
+                if Theme::named(value).is_none() {
+                    return Err(format!(
+                        "Unknown theme '{}' (one of {})",
+                        value,
+                        THEMES.join(", ")
+                    ));
+                }
+                self.display.theme = value.to_string();
+            }
+            "ansi_colors" => self.display.ansi_colors = parse_bool(value)?,
+            "progress_column" => self.display.progress_column = parse_bool(value)?,
+            "wrap_logs" => self.display.wrap_logs = parse_bool(value)?,
+            "show_details" => {
+                // Setting it overrides the legacy `[display] show_details` too
+                self.layout.show_details = parse_bool(value)?;
+                self.display.show_details = self.layout.show_details;
+            }
+            "show_log" => self.layout.show_log = parse_bool(value)?,
+            _ => {
+                return Err(format!(
+                    "Unknown setting '{}' (one of {})",
+                    key,
+                    SETTINGS.join(", ")
+                ));
+            }
+        }
+        Ok(())
+    }
+
     pub fn load() -> Self {
         let config_path = Self::config_path();
         if config_path.exists() {
@@ -167,21 +540,119 @@ all_users = true
 # squeue_args = ["--partition=a100"]
 
 [keybindings]
+# Each action takes one key or a list. Notation: "j", "G", "C-r" (Ctrl), "A-x" (Alt),
+# "<F5>", "<Tab>", "<S-Tab>", "<Esc>", "<Enter>", "<Up>", "<PgDn>", "<Space>",
+# and sequences such as "gg" or "<C-w>j". Arrows, Home and End are always bound as well.
+# Conflicting bindings are reported at startup.
 quit = "q"
 up = "k"
 down = "j"
 top = "g"
 bottom = "G"
+page_down = ["<PgDn>", "<C-f>"]
+page_up = ["<PgUp>", "<C-b>"]
+half_page_down = "<C-d>"
+half_page_up = "<C-u>"
+# In the log: 5j, 10G or 50% take a count; m<letter> marks a line, '<letter> jumps back
+set_mark = "m"
+jump_mark = "'"
+goto_percent = "%"
+help = "<F1>"
+# :cancel, :filter user:bob, :sort time desc, :set refresh_interval=5, :goto 123, :theme solarized
+command_line = ":"
+# Select a job by ID or prefix; jobs no longer queued are looked up with sacct
+goto_job = "<C-g>"
+next_panel = "<Tab>"
+back = "<Esc>"
+focus_log = "<Enter>"
 toggle_logs = "o"
 cancel_job = "x"
 refresh = "r"
-ssh_view_log = "s"
+open_shell = "s"
+# Marking: job actions (cancel, hold, copy IDs) apply to all marked jobs
+mark = " "
+mark_all = "a"
+visual_mark = "v"
+copy_ids = "y"
+hold_job = "H"
+release_job = "U"
+edit_job = "e"
+signal_job = "S"
+submit_job = "n"
+resubmit_job = "R"
+next_tab = "t"
+open_pager = "p"
+open_editor = "E"
+search = "/"
+search_backward = "?"
+search_next = "n"
+search_prev = "N"
+next_error = "]"
+prev_error = "["
+pin_log = "P"
+pin_split = "|"
+sync_scroll = "="
+toggle_wrap = "w"
+scroll_left = "h"
+scroll_right = "l"
+zoom = "z"
+grow_jobs = ">"
+shrink_jobs = "<"
+
+# Per-panel overrides (jobs, details, log, script, metrics) replace an action's keys there;
+# ? opens the help outside the log, where it searches backward
+# [keybindings.log]
+# down = ["j", "C-n"]
 
 [display]
+# default, minimal or solarized
 theme = "default"
-show_details = true
+# Render ANSI colors in logs (false strips them)
+ansi_colors = true
+# Job list column with the latest tqdm/Lightning progress read from each job's log
+progress_column = false
+# Wrap long log lines instead of scrolling them sideways (toggle with w)
+wrap_logs = false
 columns = ["JobID", "Partition", "Name", "User", "State", "Time", "Nodes", "NodeList"]
 
+[layout]
+# Job list share of the screen in percent; < and > resize it, or drag the divider
+jobs_percent = 45
+# "horizontal" (job list left of details + log) or "vertical" (stacked, for narrow terminals)
+orientation = "horizontal"
+# Details panel rows (a failure cause and progress bars add more, up to half the height)
+details_height = 10
+show_details = true
+# The log / script / metrics panel
+show_log = true
+# Narrower terminals show one panel at a time with tabs (0 disables)
+compact_width = 100
+
+[actions]
+# Signals listed first in the signal picker ("custom" lets you type any other)
+signals = ["USR1", "USR2", "TERM", "INT"]
+# Shell on a running job: "srun" (srun --jobid --overlap --pty $SHELL) or "ssh" (batch host)
+shell_method = "srun"
+# Per-partition overrides, e.g. partitions where srun steps aren't allowed
+# [actions.partition_shell]
+# cpu = "ssh"
+
+[analyzer]
+# Built-in error signatures: Traceback, CUDA out of memory, NCCL errors,
+# segfaults, oom-kill, DUE TO TIME LIMIT, ...
+builtin = true
+# Extra signatures, checked first
+# [[analyzer.patterns]]
+# name = "dataloader"
+# pattern = "DataLoader worker \\(pid \\d+\\) is killed"
+# cause = "DataLoader worker died (often host OOM)"
+
+[metrics]
+# Built-in recognizers: key=value pairs, Lightning progress postfixes, HF Trainer dicts
+builtin = true
+# Regexes with named captures; each group becomes a metric, a `step` group is the x axis
+# patterns = ['loss=(?P<loss>[0-9.]+)', 'it (?P<step>\d+) \| reward (?P<reward>[-0-9.]+)']
+
 [remote]
 # SSH to compute nodes to read node-local log files
 ssh_enabled = true
@@ -195,3 +666,59 @@ ssh_timeout = 5
         .to_string()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_config_file_matches_the_defaults() {
+        let config: Config = toml::from_str(&Config::generate_default()).unwrap();
+        let defaults = Config::default();
+        assert_eq!(config.layout.compact_width, defaults.layout.compact_width);
+        assert_eq!(config.keybindings.help.0, defaults.keybindings.help.0);
+    }
+
+    #[test]
+    fn set_and_get_settings() {
+        let mut config = Config::default();
+        config.set("general.refresh_interval", "5").unwrap();
+        assert_eq!(config.get("refresh_interval").as_deref(), Some("5"));
+        assert!(config.set("refresh_interval", "0").is_err());
+        assert!(config.set("all_users", "maybe").is_err());
+        assert!(
+            config
+                .set("theme", "neon")
+                .unwrap_err()
+                .starts_with("Unknown theme 'neon'")
+        );
+        config.set("display.theme", "solarized").unwrap();
+        assert_eq!(config.get("theme").as_deref(), Some("solarized"));
+
+        config
+            .set("squeue_args", "--partition=a100  --qos=high")
+            .unwrap();
+        assert_eq!(
+            config.general.squeue_args,
+            ["--partition=a100", "--qos=high"]
+        );
+        assert_eq!(
+            config.get("squeue_args").as_deref(),
+            Some("--partition=a100 --qos=high")
+        );
+
+        config.display.show_details = false;
+        config.set("layout.show_details", "on").unwrap();
+        assert_eq!(config.get("show_details").as_deref(), Some("true"));
+        config.set("show_log", "off").unwrap();
+        assert!(!config.layout.show_log);
+
+        assert!(
+            config
+                .set("columns", "id,name")
+                .unwrap_err()
+                .starts_with("Unknown setting 'columns'")
+        );
+        assert_eq!(config.get("columns"), None);
+    }
+}
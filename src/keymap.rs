@@ -0,0 +1,450 @@
+use std::collections::HashMap;
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+use crate::action::{self, Action, Panel};
+use crate::config::KeyBindings;
+
+/// One key press with the modifiers that matter for bindings. Shift is folded into the
+/// character for printable keys, so "G" matches Shift+g.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct KeyPress {
+    pub code: KeyCode,
+    pub ctrl: bool,
+    pub alt: bool,
+    pub shift: bool,
+}
+
+impl KeyPress {
+    fn plain(code: KeyCode) -> Self {
+        Self {
+            code,
+            ctrl: false,
+            alt: false,
+            shift: false,
+        }
+    }
+
+    pub fn from_event(key: KeyEvent) -> Self {
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+        let alt = key.modifiers.contains(KeyModifiers::ALT);
+        match key.code {
+            // Terminals report Ctrl+R as Ctrl+r
+            KeyCode::Char(c) if ctrl => Self {
+                code: KeyCode::Char(c.to_ascii_lowercase()),
+                ctrl,
+                alt,
+                shift: false,
+            },
+            KeyCode::Char(_) | KeyCode::BackTab => Self {
+                code: key.code,
+                ctrl,
+                alt,
+                shift: false,
+            },
+            code => Self {
+                code,
+                ctrl,
+                alt,
+                shift: key.modifiers.contains(KeyModifiers::SHIFT),
+            },
+        }
+    }
+
+    /// Short form for hints and the help screen, e.g. "j", "C-r", "F5", "↓"
+    pub fn label(&self) -> String {
+        let name = match self.code {
+            KeyCode::Char(' ') => "Space".to_string(),
+            KeyCode::Char(c) => c.to_string(),
+            KeyCode::Up => "↑".to_string(),
+            KeyCode::Down => "↓".to_string(),
+            KeyCode::Left => "←".to_string(),
+            KeyCode::Right => "→".to_string(),
+            KeyCode::Home => "Home".to_string(),
+            KeyCode::End => "End".to_string(),
+            KeyCode::PageUp => "PgUp".to_string(),
+            KeyCode::PageDown => "PgDn".to_string(),
+            KeyCode::Tab => "Tab".to_string(),
+            KeyCode::BackTab => "S-Tab".to_string(),
+            KeyCode::Esc => "Esc".to_string(),
+            KeyCode::Enter => "Enter".to_string(),
+            KeyCode::Backspace => "Backspace".to_string(),
+            KeyCode::Delete => "Del".to_string(),
+            KeyCode::Insert => "Ins".to_string(),
+            KeyCode::F(n) => format!("F{}", n),
+            other => format!("{:?}", other),
+        };
+        let mut label = String::new();
+        if self.ctrl {
+            label.push_str("C-");
+        }
+        if self.alt {
+            label.push_str("A-");
+        }
+        if self.shift {
+            label.push_str("S-");
+        }
+        label + &name
+    }
+}
+
+fn named_key(name: &str) -> Option<KeyCode> {
+    let code = match name.to_ascii_lowercase().as_str() {
+        "up" => KeyCode::Up,
+        "down" => KeyCode::Down,
+        "left" => KeyCode::Left,
+        "right" => KeyCode::Right,
+        "home" => KeyCode::Home,
+        "end" => KeyCode::End,
+        "pgup" | "pageup" => KeyCode::PageUp,
+        "pgdn" | "pagedown" => KeyCode::PageDown,
+        "tab" => KeyCode::Tab,
+        "backtab" => KeyCode::BackTab,
+        "esc" | "escape" => KeyCode::Esc,
+        "enter" | "cr" | "return" => KeyCode::Enter,
+        "space" => KeyCode::Char(' '),
+        "bs" | "backspace" => KeyCode::Backspace,
+        "del" | "delete" => KeyCode::Delete,
+        "ins" | "insert" => KeyCode::Insert,
+        "lt" => KeyCode::Char('<'),
+        "gt" => KeyCode::Char('>'),
+        f if f.len() > 1 && f.starts_with('f') => {
+            KeyCode::F(f[1..].parse().ok().filter(|n| (1..=24).contains(n))?)
+        }
+        _ => return None,
+    };
+    Some(code)
+}
+
+const MODIFIERS: [&str; 7] = ["c-", "ctrl-", "a-", "m-", "alt-", "s-", "shift-"];
+
+/// A single key with optional modifiers: "r", "C-r", "ctrl-r", "S-Tab", "F5", "PgDn"
+fn parse_key(text: &str) -> Option<KeyPress> {
+    let mut key = KeyPress::plain(KeyCode::Null);
+    let mut rest = text;
+    loop {
+        let lower = rest.to_ascii_lowercase();
+        let prefix = MODIFIERS.into_iter().find(|p| lower.starts_with(p));
+        // A trailing "-" is the key itself ("C--")
+        match prefix {
+            Some(p) if rest.len() > p.len() => {
+                match p.as_bytes()[0] {
+                    b'c' => key.ctrl = true,
+                    b'a' | b'm' => key.alt = true,
+                    _ => key.shift = true,
+                }
+                rest = &rest[p.len()..];
+            }
+            _ => break,
+        }
+    }
+    let mut chars = rest.chars();
+    key.code = match (chars.next(), chars.next()) {
+        (Some(c), None) => KeyCode::Char(c),
+        _ => named_key(rest)?,
+    };
+    match key.code {
+        KeyCode::Char(c) if key.ctrl => key.code = KeyCode::Char(c.to_ascii_lowercase()),
+        // Shifted characters are typed as the character itself
+        KeyCode::Char(c) if key.shift => {
+            key.code = KeyCode::Char(c.to_ascii_uppercase());
+            key.shift = false;
+        }
+        KeyCode::Tab if key.shift => {
+            key.code = KeyCode::BackTab;
+            key.shift = false;
+        }
+        _ => {}
+    }
+    Some(key)
+}
+
+/// Whether a bare string is one key: modified ("C-r", "shift-Tab") or a function key ("F5").
+/// Other names need brackets, so "gt" stays two keys rather than becoming ">".
+fn is_bare_key(text: &str) -> bool {
+    let lower = text.to_ascii_lowercase();
+    MODIFIERS.iter().any(|p| lower.starts_with(p))
+        || lower
+            .strip_prefix('f')
+            .and_then(|n| n.parse::<u8>().ok())
+            .is_some_and(|n| (1..=24).contains(&n))
+}
+
+/// Parse a binding into the key sequence it stands for. A whole string that names one key
+/// ("C-r", "F5", "shift-Tab") is that key; otherwise each character is a key ("gg") and
+/// `<...>` groups are single keys ("<C-w>j", "<lt>").
+pub fn parse_keys(text: &str) -> Result<Vec<KeyPress>, String> {
+    if text.is_empty() {
+        return Err("empty key binding".to_string());
+    }
+    if text.chars().count() > 1
+        && !text.contains('<')
+        && is_bare_key(text)
+        && let Some(key) = parse_key(text)
+    {
+        return Ok(vec![key]);
+    }
+    let mut keys = Vec::new();
+    let mut rest = text;
+    while let Some(c) = rest.chars().next() {
+        if c == '<'
+            && let Some(end) = rest.find('>')
+            && end > 1
+        {
+            let inner = &rest[1..end];
+            keys.push(
+                parse_key(inner).ok_or_else(|| format!("unknown key <{}> in '{}'", inner, text))?,
+            );
+            rest = &rest[end + 1..];
+        } else {
+            keys.push(KeyPress::plain(KeyCode::Char(c)));
+            rest = &rest[c.len_utf8()..];
+        }
+    }
+    Ok(keys)
+}
+
+/// Hint / help label for a key sequence
+pub fn sequence_label(keys: &[KeyPress]) -> String {
+    let labels: Vec<String> = keys.iter().map(|k| k.label()).collect();
+    if labels.iter().all(|l| l.chars().count() == 1) {
+        labels.concat()
+    } else {
+        labels.join(" ")
+    }
+}
+
+/// Result of feeding keys to a panel's keymap
+#[derive(Debug, PartialEq)]
+pub enum Lookup {
+    Action(Action),
+    /// The keys so far start a longer binding ("g" of "gg")
+    Pending,
+    None,
+}
+
+/// Key sequences bound to actions, per panel: the panel's own entries from the action
+/// registry plus the global ones, with `[keybindings.<panel>]` overrides applied
+#[derive(Debug, Clone, Default)]
+pub struct Keymap {
+    panels: HashMap<Panel, Vec<(Vec<KeyPress>, Action)>>,
+}
+
+impl Keymap {
+    /// Build the keymap; unparsable keys, unknown action names and conflicting bindings
+    /// are returned as errors (the first of two conflicting bindings wins)
+    pub fn new(kb: &KeyBindings) -> (Self, Vec<String>) {
+        let mut errors = Vec::new();
+        let mut panels = HashMap::new();
+        for panel in Panel::ALL {
+            let no_overrides = HashMap::new();
+            let overrides = kb.overrides(panel).unwrap_or(&no_overrides);
+            for name in overrides.keys() {
+                if Action::from_config_name(name).is_none() {
+                    errors.push(format!(
+                        "Unknown action '{}' in [keybindings.{}]",
+                        name,
+                        panel.config_name()
+                    ));
+                }
+            }
+
+            let mut bindings: Vec<(Vec<KeyPress>, Action)> = Vec::new();
+            let mut seen = Vec::new();
+            for entry in action::entries_for(panel) {
+                // An action in both the panel and the global list uses the panel's entry
+                if seen.contains(&entry.action) {
+                    continue;
+                }
+                seen.push(entry.action);
+                let configured = match overrides.get(entry.action.config_name()) {
+                    Some(keys) => &keys.0,
+                    None => &entry.action.binding(kb).0,
+                };
+                let texts = configured
+                    .iter()
+                    .cloned()
+                    .chain(entry.keys.iter().map(|k| k.to_string()));
+                for text in texts {
+                    let keys = match parse_keys(&text) {
+                        Ok(keys) => keys,
+                        Err(e) => {
+                            errors.push(format!(
+                                "Key binding for {}: {}",
+                                entry.action.config_name(),
+                                e
+                            ));
+                            continue;
+                        }
+                    };
+                    let clash = bindings.iter().find(|(other, a)| {
+                        *a != entry.action && (other.starts_with(&keys) || keys.starts_with(other))
+                    });
+                    if let Some((other, a)) = clash {
+                        let msg = format!(
+                            "{} ({}) conflicts with {} ({}) in {}",
+                            sequence_label(&keys),
+                            entry.action.config_name(),
+                            sequence_label(other),
+                            a.config_name(),
+                            panel.title().to_lowercase(),
+                        );
+                        if !errors.contains(&msg) {
+                            errors.push(msg);
+                        }
+                        continue;
+                    }
+                    bindings.push((keys, entry.action));
+                }
+            }
+            panels.insert(panel, bindings);
+        }
+        (Self { panels }, errors)
+    }
+
+    pub fn lookup(&self, panel: Panel, keys: &[KeyPress]) -> Lookup {
+        let bindings = match self.panels.get(&panel) {
+            Some(b) => b,
+            None => return Lookup::None,
+        };
+        if let Some((_, action)) = bindings.iter().find(|(k, _)| k == keys) {
+            return Lookup::Action(*action);
+        }
+        if bindings.iter().any(|(k, _)| k.starts_with(keys)) {
+            Lookup::Pending
+        } else {
+            Lookup::None
+        }
+    }
+
+    /// Labels of the key sequences bound to `action` in `panel`, in binding order
+    pub fn labels(&self, panel: Panel, action: Action) -> Vec<String> {
+        self.panels
+            .get(&panel)
+            .map(|b| {
+                b.iter()
+                    .filter(|(_, a)| *a == action)
+                    .map(|(k, _)| sequence_label(k))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Keys;
+
+    fn key(code: KeyCode) -> KeyPress {
+        KeyPress::plain(code)
+    }
+
+    #[test]
+    fn parse_notation() {
+        let ctrl_r = KeyPress {
+            ctrl: true,
+            ..key(KeyCode::Char('r'))
+        };
+        assert_eq!(parse_keys("C-r").unwrap(), vec![ctrl_r]);
+        assert_eq!(parse_keys("<C-R>").unwrap(), vec![ctrl_r]);
+        assert_eq!(parse_keys("ctrl-r").unwrap(), vec![ctrl_r]);
+        assert_eq!(parse_keys("<F5>").unwrap(), vec![key(KeyCode::F(5))]);
+        assert_eq!(parse_keys("F5").unwrap(), vec![key(KeyCode::F(5))]);
+        assert_eq!(
+            parse_keys("shift-Tab").unwrap(),
+            vec![key(KeyCode::BackTab)]
+        );
+        assert_eq!(parse_keys("gg").unwrap(), vec![key(KeyCode::Char('g')); 2]);
+        assert_eq!(
+            parse_keys("gt").unwrap(),
+            vec![key(KeyCode::Char('g')), key(KeyCode::Char('t'))]
+        );
+        assert_eq!(parse_keys("end").unwrap().len(), 3);
+        assert_eq!(parse_keys("f30").unwrap().len(), 3);
+        assert_eq!(parse_keys(" ").unwrap(), vec![key(KeyCode::Char(' '))]);
+        assert_eq!(parse_keys("<lt>").unwrap(), vec![key(KeyCode::Char('<'))]);
+        assert_eq!(parse_keys("<").unwrap(), vec![key(KeyCode::Char('<'))]);
+        assert_eq!(
+            parse_keys("<C-w>j").unwrap(),
+            vec![
+                KeyPress {
+                    ctrl: true,
+                    ..key(KeyCode::Char('w'))
+                },
+                key(KeyCode::Char('j'))
+            ]
+        );
+        assert!(parse_keys("<Nope>").is_err());
+        assert!(parse_keys("").is_err());
+    }
+
+    #[test]
+    fn events_match_parsed_keys() {
+        let shifted = KeyEvent::new(KeyCode::Char('G'), KeyModifiers::SHIFT);
+        assert_eq!(KeyPress::from_event(shifted), parse_keys("G").unwrap()[0]);
+        let ctrl = KeyEvent::new(KeyCode::Char('d'), KeyModifiers::CONTROL);
+        assert_eq!(KeyPress::from_event(ctrl), parse_keys("C-d").unwrap()[0]);
+        assert_eq!(sequence_label(&parse_keys("<C-d>").unwrap()), "C-d");
+        assert_eq!(sequence_label(&parse_keys("gg").unwrap()), "gg");
+    }
+
+    #[test]
+    fn default_keymap_has_no_conflicts() {
+        let (keymap, errors) = Keymap::new(&KeyBindings::default());
+        assert_eq!(errors, Vec::<String>::new());
+        let j = parse_keys("j").unwrap();
+        assert_eq!(keymap.lookup(Panel::Jobs, &j), Lookup::Action(Action::Down));
+        assert_eq!(
+            keymap.lookup(Panel::Jobs, &parse_keys("?").unwrap()),
+            Lookup::Action(Action::Help)
+        );
+        assert_eq!(
+            keymap.lookup(Panel::Log, &parse_keys("?").unwrap()),
+            Lookup::Action(Action::SearchBackward)
+        );
+        assert_eq!(keymap.labels(Panel::Log, Action::Down), vec!["j", "↓"]);
+    }
+
+    #[test]
+    fn sequences_overrides_and_conflicts() {
+        let mut kb = KeyBindings {
+            top: Keys(vec!["gg".to_string()]),
+            refresh: Keys(vec!["j".to_string()]),
+            ..KeyBindings::default()
+        };
+        kb.log
+            .insert("down".to_string(), Keys(vec!["<C-n>".to_string()]));
+        kb.jobs
+            .insert("bogus".to_string(), Keys(vec!["x".to_string()]));
+        let (keymap, errors) = Keymap::new(&kb);
+
+        let g = parse_keys("g").unwrap();
+        assert_eq!(keymap.lookup(Panel::Jobs, &g), Lookup::Pending);
+        assert_eq!(
+            keymap.lookup(Panel::Jobs, &parse_keys("gg").unwrap()),
+            Lookup::Action(Action::Top)
+        );
+        assert_eq!(
+            keymap.lookup(Panel::Log, &parse_keys("C-n").unwrap()),
+            Lookup::Action(Action::Down)
+        );
+        // Overriding down in the log frees "j" there, so refresh gets it
+        assert_eq!(
+            keymap.lookup(Panel::Log, &parse_keys("j").unwrap()),
+            Lookup::Action(Action::Refresh)
+        );
+        // ...but the arrow keys stay bound
+        assert_eq!(
+            keymap.lookup(Panel::Log, &parse_keys("<Down>").unwrap()),
+            Lookup::Action(Action::Down)
+        );
+
+        assert!(errors.iter().any(|e| e.contains("'bogus'")));
+        assert!(
+            errors
+                .iter()
+                .any(|e| e == "j (refresh) conflicts with j (down) in job list")
+        );
+    }
+}
@@ -1,9 +1,14 @@
+mod action;
 mod app;
+mod command;
 mod config;
+mod keymap;
 mod slurm;
 mod ui;
 
 use std::io;
+#[cfg(unix)]
+use std::os::unix::process::CommandExt;
 use std::panic;
 use std::time::{Duration, Instant};
 
@@ -15,7 +20,8 @@ use crossterm::{
 };
 use ratatui::{Terminal, backend::CrosstermBackend};
 
-use app::{App, FocusPanel};
+use action::{Action, Panel};
+use app::{App, ExternalCommand, FocusPanel, PreviewTab, Scroll};
 use config::Config;
 
 #[derive(Parser)]
@@ -32,6 +38,10 @@ struct Cli {
     /// Path to config file
     #[arg(short, long)]
     config: Option<String>,
+
+    /// Start with this job selected and its log focused (ID or ID prefix)
+    #[arg(short, long, value_name = "ID")]
+    job: Option<String>,
 }
 
 fn install_panic_hook() {
@@ -44,6 +54,76 @@ fn install_panic_hook() {
     }));
 }
 
+/// Hand the terminal to an external program (editor, shell, pager) and take it back
+/// afterwards, undoing and redoing the same setup `main` and the panic hook do
+fn run_external(
+    terminal: &mut Terminal<CrosstermBackend<io::Stdout>>,
+    cmd: &ExternalCommand,
+) -> Result<(), String> {
+    disable_raw_mode().map_err(|e| e.to_string())?;
+    execute!(
+        terminal.backend_mut(),
+        LeaveAlternateScreen,
+        DisableMouseCapture
+    )
+    .map_err(|e| e.to_string())?;
+    terminal.show_cursor().map_err(|e| e.to_string())?;
+
+    let status = run_foreground(cmd);
+
+    enable_raw_mode().map_err(|e| e.to_string())?;
+    execute!(
+        terminal.backend_mut(),
+        EnterAlternateScreen,
+        EnableMouseCapture
+    )
+    .map_err(|e| e.to_string())?;
+    terminal.clear().map_err(|e| e.to_string())?;
+
+    match status {
+        Ok(s) if s.success() => Ok(()),
+        Ok(s) => Err(format!("{} exited with {}", cmd.program, s)),
+        Err(e) => Err(format!("Failed to run {}: {}", cmd.program, e)),
+    }
+}
+
+/// Run a program attached to the terminal. The terminal sends SIGINT and SIGQUIT to the whole
+/// foreground process group, so like system(3) ylurm ignores them until the program exits;
+/// the program itself gets the default handlers back.
+#[cfg(unix)]
+fn run_foreground(cmd: &ExternalCommand) -> io::Result<std::process::ExitStatus> {
+    let mut command = std::process::Command::new(&cmd.program);
+    command.args(&cmd.args);
+    // SAFETY: signal() is async-signal-safe, so it may run between fork and exec
+    unsafe {
+        command.pre_exec(|| {
+            libc::signal(libc::SIGINT, libc::SIG_DFL);
+            libc::signal(libc::SIGQUIT, libc::SIG_DFL);
+            Ok(())
+        });
+    }
+    // SAFETY: swapping dispositions touches no Rust state; the old ones are put back below
+    let (int, quit) = unsafe {
+        (
+            libc::signal(libc::SIGINT, libc::SIG_IGN),
+            libc::signal(libc::SIGQUIT, libc::SIG_IGN),
+        )
+    };
+    let status = command.status();
+    unsafe {
+        libc::signal(libc::SIGINT, int);
+        libc::signal(libc::SIGQUIT, quit);
+    }
+    status
+}
+
+#[cfg(not(unix))]
+fn run_foreground(cmd: &ExternalCommand) -> io::Result<std::process::ExitStatus> {
+    std::process::Command::new(&cmd.program)
+        .args(&cmd.args)
+        .status()
+}
+
 fn main() -> Result<(), Box<dyn std::error::Error>> {
     let cli = Cli::parse();
 
@@ -74,7 +154,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     let backend = CrosstermBackend::new(stdout);
     let mut terminal = Terminal::new(backend)?;
 
-    let result = run_app(&mut terminal, config);
+    let result = run_app(&mut terminal, config, cli.job.as_deref());
 
     // Restore terminal
     disable_raw_mode()?;
@@ -95,67 +175,131 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
 fn run_app(
     terminal: &mut Terminal<CrosstermBackend<io::Stdout>>,
     config: Config,
+    job: Option<&str>,
 ) -> Result<(), Box<dyn std::error::Error>> {
     let mut app = App::new(config.clone());
-    let tick_rate = Duration::from_secs(config.general.refresh_interval);
+    if let Some(id) = job {
+        app.goto_job(id, true);
+    }
     let mut last_tick = Instant::now();
 
     loop {
+        // The Script tab fetches its job's script once it's shown or the selection moves,
+        // here rather than while drawing
+        if app.preview_tab == PreviewTab::Script {
+            app.ensure_script_loaded();
+        }
+        app.collect_progress();
+        app.collect_goto();
+        app.poll_progress();
+        // Read every time round, `:set refresh_interval` changes it
+        let tick_rate = Duration::from_secs(app.config.general.refresh_interval.max(1));
         terminal.draw(|f| ui::draw_ui(f, &mut app))?;
 
         let timeout = tick_rate.saturating_sub(last_tick.elapsed());
         if event::poll(timeout)? {
             match event::read()? {
-                Event::Key(key) => {
-                    // Ctrl+C always quits
-                    if key.modifiers.contains(KeyModifiers::CONTROL)
-                        && key.code == KeyCode::Char('c')
-                    {
-                        app.should_quit = true;
-                    }
-
-                    // Global keys (work in any focus)
-                    match key.code {
-                        KeyCode::Tab => { app.cycle_focus(); continue; }
-                        KeyCode::Esc => { app.focus_jobs(); continue; }
-                        _ => {}
-                    }
-
-                    match app.focus {
-                        FocusPanel::Jobs => handle_jobs_keys(&mut app, key),
-                        FocusPanel::Log => handle_log_keys(&mut app, key),
+                Event::Key(key) => handle_key(&mut app, key),
+                // Forms, help, prompts and the command line own the screen until closed
+                Event::Mouse(_)
+                    if app.modal.is_some()
+                        || app.help_scroll.is_some()
+                        || app.pending_confirm.is_some()
+                        || app.command_input.is_some() => {}
+                Event::Mouse(mouse) if app.context_menu.is_some() => {
+                    // The menu takes clicks until it closes; one outside just closes it
+                    if let MouseEventKind::Down(_) = mouse.kind {
+                        match app.menu_item_at(mouse.column, mouse.row) {
+                            Some(i) => {
+                                if let Some(menu) = app.context_menu.as_mut() {
+                                    menu.selected = i;
+                                }
+                                if let Some(action) = app.choose_menu_item() {
+                                    run_command_action(&mut app, action);
+                                }
+                            }
+                            None => app.context_menu = None,
+                        }
                     }
                 }
-                Event::Mouse(mouse) => {
-                    match mouse.kind {
-                        MouseEventKind::Down(MouseButton::Left) => {
-                            let col = mouse.column;
-                            let row = mouse.row;
-                            if rect_contains(app.details_area, col, row) {
-                                app.handle_details_click(col, row);
-                            } else if rect_contains(app.log_area, col, row) {
-                                app.focus = FocusPanel::Log;
-                            } else if rect_contains(app.job_list_area, col, row) {
-                                app.focus = FocusPanel::Jobs;
-                            }
+                Event::Mouse(mouse) => match mouse.kind {
+                    MouseEventKind::Down(MouseButton::Left) => {
+                        let col = mouse.column;
+                        let row = mouse.row;
+                        if app.on_divider(col, row) {
+                            app.dragging_divider = true;
+                        } else if rect_contains(app.details_area, col, row) {
+                            app.focus = FocusPanel::Details;
+                            app.handle_details_click(col, row);
+                        } else if rect_contains(app.log.area, col, row) {
+                            app.focus = FocusPanel::Log;
+                            click_log(&mut app, col, row);
+                        } else if app
+                            .pinned_area()
+                            .is_some_and(|a| rect_contains(a, col, row))
+                        {
+                            app.focus = FocusPanel::Pinned;
+                            app.with_pinned(|app| click_log(app, col, row));
+                        } else if rect_contains(app.job_list_area, col, row) {
+                            app.click_job(col, row);
                         }
-                        MouseEventKind::ScrollUp => {
-                            if rect_contains(app.log_area, mouse.column, mouse.row) {
-                                app.scroll_log_up(3);
-                            }
+                    }
+                    MouseEventKind::Down(MouseButton::Right) => {
+                        app.open_context_menu(mouse.column, mouse.row);
+                    }
+                    MouseEventKind::Drag(MouseButton::Left) if app.dragging_divider => {
+                        app.drag_divider(mouse.column, mouse.row);
+                    }
+                    MouseEventKind::Up(MouseButton::Left) => app.dragging_divider = false,
+                    MouseEventKind::ScrollUp | MouseEventKind::ScrollDown => {
+                        let motion = if mouse.kind == MouseEventKind::ScrollUp {
+                            Scroll::Up(3)
+                        } else {
+                            Scroll::Down(3)
+                        };
+                        if rect_contains(app.job_list_area, mouse.column, mouse.row) {
+                            app.scroll_jobs(if mouse.kind == MouseEventKind::ScrollUp {
+                                -1
+                            } else {
+                                1
+                            });
+                        } else if rect_contains(app.log.area, mouse.column, mouse.row) {
+                            app.scroll_log(motion);
+                        } else if app
+                            .pinned_area()
+                            .is_some_and(|a| rect_contains(a, mouse.column, mouse.row))
+                        {
+                            app.with_pinned(|app| app.scroll_log(motion));
                         }
-                        MouseEventKind::ScrollDown => {
-                            if rect_contains(app.log_area, mouse.column, mouse.row) {
-                                app.scroll_log_down(3);
+                    }
+                    MouseEventKind::ScrollLeft | MouseEventKind::ScrollRight => {
+                        let scroll = |app: &mut App| {
+                            if mouse.kind == MouseEventKind::ScrollLeft {
+                                app.scroll_log_left(8);
+                            } else {
+                                app.scroll_log_right(8);
                             }
+                        };
+                        if rect_contains(app.log.area, mouse.column, mouse.row) {
+                            scroll(&mut app);
+                        } else if app
+                            .pinned_area()
+                            .is_some_and(|a| rect_contains(a, mouse.column, mouse.row))
+                        {
+                            app.with_pinned(scroll);
                         }
-                        _ => {}
                     }
-                }
+                    _ => {}
+                },
                 _ => {}
             }
         }
 
+        if let Some(cmd) = app.external.take() {
+            let result = run_external(terminal, &cmd);
+            app.external_finished(cmd, result);
+        }
+
         if last_tick.elapsed() >= tick_rate {
             app.refresh_jobs();
             last_tick = Instant::now();
@@ -167,84 +311,377 @@ fn run_app(
     }
 }
 
-fn handle_jobs_keys(app: &mut App, key: crossterm::event::KeyEvent) {
-    match key.code {
-        KeyCode::Char(c) => {
-            if key.modifiers.contains(KeyModifiers::CONTROL) {
-                match c {
-                    'd' => app.scroll_log_down(15),
-                    'u' => app.scroll_log_up(15),
-                    _ => {}
-                }
-            } else {
-                let ch = c.to_string();
-                if ch == app.config.keybindings.quit {
-                    app.should_quit = true;
-                } else if ch == app.config.keybindings.down {
-                    app.next_job();
-                } else if ch == app.config.keybindings.up {
-                    app.previous_job();
-                } else if ch == app.config.keybindings.top {
-                    app.select_first();
-                } else if ch == app.config.keybindings.bottom {
-                    app.select_last();
-                } else if ch == app.config.keybindings.refresh {
-                    app.refresh_jobs();
-                } else if ch == app.config.keybindings.toggle_logs {
-                    app.toggle_log_mode();
-                }
+/// Route a key press to whatever popup is open, else to the focused panel
+fn handle_key(app: &mut App, key: crossterm::event::KeyEvent) {
+    // Ctrl+C always quits, whatever is open
+    if key.modifiers.contains(KeyModifiers::CONTROL) && key.code == KeyCode::Char('c') {
+        app.should_quit = true;
+        return;
+    }
+
+    // Open forms capture every key (including Tab/Esc)
+    if app.modal.is_some() {
+        app.handle_modal_key(key);
+        return;
+    }
+
+    if app.help_scroll.is_some() {
+        handle_help_keys(app, key);
+        return;
+    }
+
+    // A pending y/n prompt swallows the next key
+    if app.pending_confirm.is_some() {
+        app.resolve_confirm(matches!(key.code, KeyCode::Char('y') | KeyCode::Char('Y')));
+        return;
+    }
+
+    // The command line takes text input until Enter/Esc
+    if app.command_input.is_some() {
+        if let Some(action) = app.handle_command_key(key) {
+            run_command_action(app, action);
+        }
+        return;
+    }
+
+    if app.context_menu.is_some() {
+        handle_menu_keys(app, key);
+        return;
+    }
+
+    // The search prompt takes text input until Enter/Esc
+    if app.search_input.is_some() {
+        on_log_pane(app, |app| app.handle_search_key(key));
+        return;
+    }
+
+    if let Some(mark) = app.awaiting_mark.take() {
+        if let KeyCode::Char(letter) = key.code {
+            on_log_pane(app, |app| match mark {
+                Action::SetMark => app.set_log_mark(letter),
+                _ => app.jump_log_mark(letter),
+            });
+        }
+        return;
+    }
+    if app.push_count_digit(key) {
+        return;
+    }
+    let Some(action) = app.feed_key(key) else {
+        // A key that isn't part of a binding drops the count too
+        if app.pending_keys.is_empty() {
+            app.count = None;
+        }
+        return;
+    };
+    let count = app.count.take();
+    dispatch(app, action, count);
+}
+
+/// Run an action in the focused panel. Global actions run first so they never see the
+/// pinned pane's state swapped in.
+fn dispatch(app: &mut App, action: Action, count: Option<usize>) {
+    if handle_global_action(app, action) {
+        return;
+    }
+    match app.focus {
+        FocusPanel::Jobs => handle_jobs_action(app, action),
+        FocusPanel::Details => handle_details_action(app, action),
+        FocusPanel::Log | FocusPanel::Pinned => {
+            on_log_pane(app, |app| handle_log_action(app, action, count));
+        }
+    }
+}
+
+/// `:` commands reach actions of other panels too (`:cancel` from the log), using the
+/// panel the action belongs to when the focused one doesn't have it
+fn run_command_action(app: &mut App, action: Action) {
+    if action::entries_for(app.key_panel()).any(|e| e.action == action) {
+        return dispatch(app, action, None);
+    }
+    match action::REGISTRY
+        .iter()
+        .find(|e| e.action == action)
+        .map(|e| e.panel)
+    {
+        Some(Panel::Jobs) => handle_jobs_action(app, action),
+        Some(Panel::Details) => handle_details_action(app, action),
+        Some(_) => on_log_pane(app, |app| handle_log_action(app, action, None)),
+        None => {}
+    }
+}
+
+/// Actions that work from any panel. Returns whether `action` was one.
+fn handle_global_action(app: &mut App, action: Action) -> bool {
+    match action {
+        Action::Quit => app.should_quit = true,
+        Action::Help => app.toggle_help(),
+        Action::CommandLine => app.open_command_line(""),
+        Action::GotoJob => app.open_command_line("goto "),
+        Action::NextPanel => app.cycle_focus(),
+        Action::Back => {
+            if !app.cancel_visual() {
+                app.focus_jobs();
             }
         }
-        KeyCode::Up => app.previous_job(),
-        KeyCode::Down => app.next_job(),
-        KeyCode::Home => app.select_first(),
-        KeyCode::End => app.select_last(),
-        KeyCode::Enter => app.cycle_focus(),
+        Action::Zoom => app.toggle_zoom(),
+        Action::GrowJobs => app.resize_jobs(5),
+        Action::ShrinkJobs => app.resize_jobs(-5),
+        Action::PinLog => app.toggle_pin(),
+        Action::PinSplit if app.pinned.is_some() => app.toggle_pinned_stacked(),
+        Action::SyncScroll if app.pinned.is_some() => app.toggle_sync_scroll(),
+        _ => return false,
+    }
+    true
+}
+
+fn handle_jobs_action(app: &mut App, action: Action) {
+    match action {
+        Action::Down => app.next_job(),
+        Action::Up => app.previous_job(),
+        Action::Top => app.select_first(),
+        Action::Bottom => app.select_last(),
+        Action::FocusLog => app.cycle_focus(),
+        Action::HalfPageDown => app.scroll_log_down(app.page_lines() / 2),
+        Action::HalfPageUp => app.scroll_log_up(app.page_lines() / 2),
+        Action::Refresh => app.refresh_jobs(),
+        Action::ToggleLogs => app.toggle_log_mode(),
+        Action::Mark => app.toggle_mark(),
+        Action::MarkAll => app.toggle_mark_all(),
+        Action::VisualMark => app.toggle_visual_mark(),
+        Action::CopyIds => app.copy_target_ids(),
+        Action::CancelJob => app.request_cancel(),
+        Action::HoldJob => app.hold_targets(),
+        Action::ReleaseJob => app.release_targets(),
+        Action::EditJob => app.open_edit_form(),
+        Action::SignalJob => app.open_signal_form(),
+        Action::SubmitJob => app.open_submit_form(),
+        Action::ResubmitJob => app.start_resubmit(),
+        Action::OpenShell => app.start_shell(),
+        Action::OpenPager => app.open_log_external(false),
+        Action::OpenEditor => app.open_log_external(true),
+        Action::NextTab => app.next_preview_tab(),
         _ => {}
     }
 }
 
-fn handle_log_keys(app: &mut App, key: crossterm::event::KeyEvent) {
+/// The job menu moves with the usual keys, runs its entry on Enter and closes on back or quit
+fn handle_menu_keys(app: &mut App, key: crossterm::event::KeyEvent) {
     match key.code {
-        KeyCode::Char(c) => {
-            if key.modifiers.contains(KeyModifiers::CONTROL) {
-                match c {
-                    'd' => app.scroll_log_down(15),
-                    'u' => app.scroll_log_up(15),
-                    _ => {}
-                }
-            } else {
-                let ch = c.to_string();
-                if ch == app.config.keybindings.quit {
-                    app.should_quit = true;
-                } else if ch == app.config.keybindings.down || ch == app.config.keybindings.up {
-                    // j/k scroll the log when focused
-                    if ch == app.config.keybindings.down {
-                        app.scroll_log_down(1);
-                    } else {
-                        app.scroll_log_up(1);
-                    }
-                } else if ch == app.config.keybindings.top {
-                    app.scroll_log_top();
-                } else if ch == app.config.keybindings.bottom {
-                    app.scroll_log_bottom();
-                } else if ch == app.config.keybindings.toggle_logs {
-                    app.toggle_log_mode();
-                } else if ch == app.config.keybindings.refresh {
-                    app.refresh_jobs();
-                }
+        KeyCode::Enter => {
+            if let Some(action) = app.choose_menu_item() {
+                run_command_action(app, action);
             }
+            return;
         }
-        KeyCode::Up => app.scroll_log_up(1),
-        KeyCode::Down => app.scroll_log_down(1),
-        KeyCode::PageUp => app.scroll_log_up(30),
-        KeyCode::PageDown => app.scroll_log_down(30),
-        KeyCode::Home => app.scroll_log_top(),
-        KeyCode::End => app.scroll_log_bottom(),
+        KeyCode::Esc => {
+            app.context_menu = None;
+            return;
+        }
+        KeyCode::Down => return app.move_menu(1),
+        KeyCode::Up => return app.move_menu(-1),
+        _ => {}
+    }
+    match app.feed_key(key) {
+        Some(Action::Down) => app.move_menu(1),
+        Some(Action::Up) => app.move_menu(-1),
+        Some(Action::Back | Action::Quit) => app.context_menu = None,
         _ => {}
     }
 }
 
+/// The help overlay scrolls with the usual keys and closes on help, back or quit
+fn handle_help_keys(app: &mut App, key: crossterm::event::KeyEvent) {
+    if key.code == KeyCode::Char('?') {
+        app.toggle_help();
+        return;
+    }
+    match app.feed_key(key) {
+        Some(Action::Help | Action::Back | Action::Quit) => app.toggle_help(),
+        Some(Action::Down) => app.scroll_help(1),
+        Some(Action::Up) => app.scroll_help(-1),
+        Some(Action::PageDown | Action::HalfPageDown) => app.scroll_help(10),
+        Some(Action::PageUp | Action::HalfPageUp) => app.scroll_help(-10),
+        Some(Action::Top) => app.help_scroll = Some(0),
+        _ => {}
+    }
+}
+
+/// The details panel follows the selection, so j/k keep moving through jobs
+fn handle_details_action(app: &mut App, action: Action) {
+    match action {
+        Action::Down => app.next_job(),
+        Action::Up => app.previous_job(),
+        Action::Refresh => app.refresh_jobs(),
+        _ => {}
+    }
+}
+
+/// Log keys; `count` is the number typed before the key, if any
+fn handle_log_action(app: &mut App, action: Action, count: Option<usize>) {
+    match app.preview_tab {
+        PreviewTab::Script => return handle_script_action(app, action, count),
+        PreviewTab::Metrics => return handle_metrics_action(app, action),
+        PreviewTab::Log => {}
+    }
+    let n = count.unwrap_or(1);
+    let page = app.page_lines();
+    match action {
+        Action::Down => app.scroll_log(Scroll::Down(n)),
+        Action::Up => app.scroll_log(Scroll::Up(n)),
+        Action::Top | Action::Bottom if count.is_some() => app.goto_log_line(n),
+        Action::Top => app.scroll_log(Scroll::Top),
+        Action::Bottom => app.scroll_log(Scroll::Bottom),
+        Action::GotoPercent => match count {
+            Some(percent) => app.goto_log_percent(percent),
+            None => app.flash_message("Type a percentage first, e.g. 50%".to_string(), true),
+        },
+        Action::PageDown => app.scroll_log(Scroll::Down(n.saturating_mul(page))),
+        Action::PageUp => app.scroll_log(Scroll::Up(n.saturating_mul(page))),
+        Action::HalfPageDown => app.scroll_log(Scroll::Down(n.saturating_mul(page / 2))),
+        Action::HalfPageUp => app.scroll_log(Scroll::Up(n.saturating_mul(page / 2))),
+        Action::SetMark | Action::JumpMark => app.awaiting_mark = Some(action),
+        Action::ToggleLogs => app.toggle_log_mode(),
+        Action::Refresh => app.refresh_jobs(),
+        Action::NextTab => app.next_preview_tab(),
+        Action::OpenPager => app.open_log_external(false),
+        Action::OpenEditor => app.open_log_external(true),
+        Action::Search => app.start_search(false),
+        Action::SearchBackward => app.start_search(true),
+        Action::SearchNext => app.search_next(false),
+        Action::SearchPrev => app.search_next(true),
+        Action::NextError => app.jump_to_error(false),
+        Action::PrevError => app.jump_to_error(true),
+        Action::ToggleWrap => app.toggle_wrap(),
+        Action::ScrollLeft => app.scroll_log_left(n.saturating_mul(8)),
+        Action::ScrollRight => app.scroll_log_right(n.saturating_mul(8)),
+        _ => {}
+    }
+}
+
+/// The Script tab scrolls like the log but has no stdout/stderr toggle
+fn handle_script_action(app: &mut App, action: Action, count: Option<usize>) {
+    let n = count.unwrap_or(1);
+    let lines = |k: usize| n.saturating_mul(k).min(u16::MAX as usize) as u16;
+    let page = app.page_lines();
+    match action {
+        Action::Down => app.scroll_script_down(lines(1)),
+        Action::Up => app.scroll_script_up(lines(1)),
+        Action::Top => app.script_scroll = 0,
+        Action::Bottom => app.scroll_script_bottom(),
+        Action::PageDown => app.scroll_script_down(lines(page)),
+        Action::PageUp => app.scroll_script_up(lines(page)),
+        Action::NextTab => app.next_preview_tab(),
+        _ => {}
+    }
+}
+
+/// The Metrics tab picks which metric to chart
+fn handle_metrics_action(app: &mut App, action: Action) {
+    match action {
+        Action::Down => app.select_next_metric(),
+        Action::Up => app.select_previous_metric(),
+        Action::NextTab => app.next_preview_tab(),
+        Action::ToggleLogs => app.toggle_log_mode(),
+        _ => {}
+    }
+}
+
+/// Run `f` on the focused log pane, with the pinned pane's state swapped in when it has focus
+fn on_log_pane(app: &mut App, f: impl FnOnce(&mut App)) {
+    if app.focus == FocusPanel::Pinned {
+        app.with_pinned(f);
+    } else {
+        f(app);
+    }
+}
+
+/// Clicking the scrollbar column of a log pane jumps to that point in the file
+fn click_log(app: &mut App, col: u16, row: u16) {
+    let area = app.log.area;
+    if col == area.x + area.width - 1 && area.height > 2 && row > area.y {
+        let track = (area.height - 2) as f64;
+        let fraction = (row - area.y - 1) as f64 / (track - 1.0).max(1.0);
+        app.jump_log_to_fraction(fraction);
+    }
+}
+
 fn rect_contains(rect: ratatui::layout::Rect, col: u16, row: u16) -> bool {
     col >= rect.x && col < rect.x + rect.width && row >= rect.y && row < rect.y + rect.height
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use app::{ContextMenu, Modal, PendingAction};
+    use crossterm::event::KeyEvent;
+    use ratatui::layout::Rect;
+    use ui::Form;
+
+    #[test]
+    fn ctrl_c_quits_over_every_overlay() {
+        let overlays: [fn(&mut App); 6] = [
+            |app| {
+                app.modal = Some(Modal::EditJob {
+                    job_id: "1".to_string(),
+                    form: Form::new("Edit", vec![]),
+                })
+            },
+            |app| app.toggle_help(),
+            |app| app.pending_confirm = Some(PendingAction::Cancel(vec!["1".to_string()])),
+            |app| app.command_input = Some(Default::default()),
+            |app| {
+                app.context_menu = Some(ContextMenu {
+                    col: 0,
+                    row: 0,
+                    selected: 0,
+                    area: Rect::default(),
+                })
+            },
+            |app| app.start_search(false),
+        ];
+        for open in overlays {
+            let mut app = App::without_jobs(Config::default(), None);
+            open(&mut app);
+            handle_key(
+                &mut app,
+                KeyEvent::new(KeyCode::Char('c'), KeyModifiers::CONTROL),
+            );
+            assert!(app.should_quit);
+        }
+    }
+
+    #[test]
+    fn question_mark_searches_backward_in_the_log_and_opens_help_elsewhere() {
+        let question = KeyEvent::new(KeyCode::Char('?'), KeyModifiers::NONE);
+        let mut app = App::without_jobs(Config::default(), None);
+        app.focus = FocusPanel::Log;
+        handle_key(&mut app, question);
+        assert!(
+            app.search_input
+                .as_ref()
+                .is_some_and(|input| input.backward)
+        );
+        assert!(app.help_scroll.is_none());
+
+        let mut app = App::without_jobs(Config::default(), None);
+        handle_key(&mut app, question);
+        assert!(app.help_scroll.is_some());
+        handle_key(&mut app, KeyEvent::new(KeyCode::F(1), KeyModifiers::NONE));
+        assert!(app.help_scroll.is_none());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn ctrl_c_reaches_external_programs_but_not_ylurm() {
+        use std::os::unix::process::ExitStatusExt;
+        let sh = |script: &str| ExternalCommand {
+            program: "sh".to_string(),
+            args: vec!["-c".to_string(), script.to_string()],
+            on_success: None,
+        };
+        assert!(run_foreground(&sh("kill -INT $PPID")).unwrap().success());
+        let status = run_foreground(&sh("kill -INT $$; sleep 1")).unwrap();
+        assert_eq!(status.signal(), Some(libc::SIGINT));
+    }
+}
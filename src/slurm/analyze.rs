@@ -0,0 +1,276 @@
+use regex::{Regex, RegexSet};
+
+use crate::config::ErrorPattern;
+
+/// A known failure pattern and the explanation shown when it's found
+#[derive(Debug, Clone)]
+pub struct ErrorSignature {
+    pub name: String,
+    pub pattern: String,
+    pub cause: String,
+}
+
+impl ErrorSignature {
+    fn new(name: &str, pattern: &str, cause: &str) -> Self {
+        Self {
+            name: name.into(),
+            pattern: pattern.into(),
+            cause: cause.into(),
+        }
+    }
+}
+
+/// Built-in signatures, most specific first: when several match, the earliest wins
+pub fn builtin_signatures() -> Vec<ErrorSignature> {
+    vec![
+        ErrorSignature::new(
+            "oom-kill",
+            r"oom[-_ ]kill|Out Of Memory|OUT_OF_MEMORY",
+            "Host memory exhausted (cgroup OOM kill), request more --mem",
+        ),
+        ErrorSignature::new(
+            "time-limit",
+            r"DUE TO TIME LIMIT",
+            "Job reached its time limit",
+        ),
+        ErrorSignature::new("node-failure", r"DUE TO NODE FAILURE", "Node failure"),
+        ErrorSignature::new(
+            "cuda-oom",
+            r"CUDA out of memory|CUDA error: out of memory|OutOfMemoryError",
+            "GPU memory exhausted",
+        ),
+        ErrorSignature::new(
+            "nccl",
+            r"NCCL error|NCCL WARN|nccl\w*Error|Watchdog caught collective operation timeout",
+            "NCCL communication failure between ranks",
+        ),
+        ErrorSignature::new(
+            "cuda-error",
+            r"CUDA error|device-side assert|cudaError\w+",
+            "CUDA runtime error",
+        ),
+        ErrorSignature::new(
+            "crash",
+            r"Segmentation fault|SIGSEGV|Bus error|Illegal instruction|core dumped",
+            "Process crashed",
+        ),
+        ErrorSignature::new(
+            "python-exception",
+            r"^(\w+\.)*\w*(Error|Exception|Interrupt): ",
+            "Uncaught Python exception",
+        ),
+        ErrorSignature::new(
+            "traceback",
+            r"^Traceback \(most recent call last\)",
+            "Python traceback",
+        ),
+        ErrorSignature::new("slurmstepd", r"slurmstepd: error:", "Slurm step error"),
+    ]
+}
+
+/// What a failed job's log most likely died of
+#[derive(Debug, Clone, PartialEq)]
+pub struct LikelyCause {
+    pub name: String,
+    pub cause: String,
+    /// The matching log line, trimmed
+    pub line: String,
+}
+
+/// Finds error signatures in log lines
+#[derive(Debug, Clone)]
+pub struct LogAnalyzer {
+    signatures: Vec<ErrorSignature>,
+    set: RegexSet,
+    /// All signatures as one alternation, for searching the file on disk
+    combined: Regex,
+}
+
+impl LogAnalyzer {
+    /// Config patterns first (so they take priority), then the built-ins unless disabled.
+    /// Patterns that don't compile, alone or together with the others (a capture group name
+    /// used twice, the combined size limit), are skipped and reported in the returned errors.
+    pub fn new(patterns: &[ErrorPattern], builtin: bool) -> (Self, Vec<String>) {
+        let mut candidates: Vec<ErrorSignature> = patterns
+            .iter()
+            .map(|p| ErrorSignature::new(&p.name, &p.pattern, &p.cause))
+            .collect();
+        if builtin {
+            candidates.extend(builtin_signatures());
+        }
+
+        let mut errors = Vec::new();
+        let (signatures, (set, combined)) = match compile(&candidates) {
+            Ok(compiled) => (candidates, compiled),
+            // Find the culprits by adding the signatures one at a time
+            Err(_) => {
+                let mut signatures = Vec::new();
+                for sig in candidates {
+                    signatures.push(sig);
+                    if let Err(e) = compile(&signatures) {
+                        let sig = signatures.pop().expect("just pushed");
+                        errors.push(format!("Ignoring error pattern '{}': {}", sig.name, e));
+                    }
+                }
+                let compiled = compile(&signatures).expect("every kept signature compiles");
+                (signatures, compiled)
+            }
+        };
+        (
+            Self {
+                signatures,
+                set,
+                combined,
+            },
+            errors,
+        )
+    }
+
+    /// Regex matching any signature
+    pub fn regex(&self) -> &Regex {
+        &self.combined
+    }
+
+    pub fn is_error(&self, line: &str) -> bool {
+        self.set.is_match(line)
+    }
+
+    /// Highest-priority signature in `lines`, quoting its last occurrence
+    pub fn likely_cause<S: AsRef<str>>(&self, lines: &[S]) -> Option<LikelyCause> {
+        let mut best: Option<(usize, &str)> = None;
+        for line in lines {
+            let line = line.as_ref();
+            if let Some(idx) = self.set.matches(line).iter().next()
+                && best.is_none_or(|(b, _)| idx <= b)
+            {
+                best = Some((idx, line));
+            }
+        }
+        best.map(|(idx, line)| {
+            let sig = &self.signatures[idx];
+            LikelyCause {
+                name: sig.name.clone(),
+                cause: sig.cause.clone(),
+                line: line.trim().to_string(),
+            }
+        })
+    }
+}
+
+/// The signatures as a set (for which one matched) and as one alternation (for searching
+/// the file on disk)
+fn compile(signatures: &[ErrorSignature]) -> Result<(RegexSet, Regex), regex::Error> {
+    let set = RegexSet::new(signatures.iter().map(|s| &s.pattern))?;
+    let combined = if signatures.is_empty() {
+        // Matches nothing
+        Regex::new(r"[^\s\S]")?
+    } else {
+        let alternation: Vec<String> = signatures
+            .iter()
+            .map(|s| format!("(?:{})", s.pattern))
+            .collect();
+        Regex::new(&alternation.join("|"))?
+    };
+    Ok((set, combined))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn builtin() -> LogAnalyzer {
+        LogAnalyzer::new(&[], true).0
+    }
+
+    #[test]
+    fn builtin_signatures_compile_and_match() {
+        let analyzer = builtin();
+        for line in [
+            "Traceback (most recent call last):",
+            "torch.OutOfMemoryError: CUDA out of memory. Tried to allocate 2.00 GiB",
+            "RuntimeError: NCCL error in: ProcessGroupNCCL.cpp:1191, unhandled system error",
+            "/var/spool/slurmd/job123/slurm_script: line 12: 4242 Segmentation fault (core dumped) ./a.out",
+            "slurmstepd: error: Detected 1 oom_kill event in StepId=123.batch. Some of the step tasks have been OOM Killed.",
+            "slurmstepd: error: *** JOB 123 ON dgx01 CANCELLED AT 2024-05-01T10:00:00 DUE TO TIME LIMIT ***",
+        ] {
+            assert!(analyzer.is_error(line), "{} should match", line);
+            assert!(analyzer.regex().is_match(line));
+        }
+        assert!(!analyzer.is_error("Epoch 3: loss=0.123"));
+    }
+
+    #[test]
+    fn likely_cause_prefers_specific_signatures() {
+        let analyzer = builtin();
+        let log = [
+            "Traceback (most recent call last):",
+            "  File \"train.py\", line 10, in <module>",
+            "torch.OutOfMemoryError: CUDA out of memory. Tried to allocate 2.00 GiB",
+            "srun: error: dgx01: task 0: Exited with exit code 1",
+        ];
+        let cause = analyzer.likely_cause(&log).unwrap();
+        assert_eq!(cause.name, "cuda-oom");
+        assert!(cause.line.starts_with("torch.OutOfMemoryError"));
+
+        let log = [
+            "Traceback (most recent call last):",
+            "ValueError: bad shape",
+        ];
+        let cause = analyzer.likely_cause(&log).unwrap();
+        assert_eq!(cause.name, "python-exception");
+        assert_eq!(cause.line, "ValueError: bad shape");
+
+        assert_eq!(analyzer.likely_cause(&["all good"]), None);
+    }
+
+    #[test]
+    fn config_patterns_take_priority_and_bad_ones_are_reported() {
+        let patterns = vec![
+            ErrorPattern {
+                name: "dataloader".into(),
+                pattern: r"DataLoader worker \(pid \d+\) is killed".into(),
+                cause: "DataLoader worker died".into(),
+            },
+            ErrorPattern {
+                name: "broken".into(),
+                pattern: "(".into(),
+                cause: String::new(),
+            },
+        ];
+        let (analyzer, errors) = LogAnalyzer::new(&patterns, true);
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].contains("broken"));
+
+        let log = ["RuntimeError: DataLoader worker (pid 4242) is killed by signal: Killed."];
+        assert_eq!(analyzer.likely_cause(&log).unwrap().name, "dataloader");
+
+        let (none, _) = LogAnalyzer::new(&[], false);
+        assert!(!none.is_error("Segmentation fault"));
+        assert!(!none.regex().is_match("anything"));
+    }
+
+    #[test]
+    fn patterns_that_clash_when_combined_are_dropped() {
+        let pattern = |name: &str, pattern: &str| ErrorPattern {
+            name: name.into(),
+            pattern: pattern.into(),
+            cause: String::new(),
+        };
+        let patterns = vec![
+            pattern("exit", r"exit code (?P<code>\d+)"),
+            pattern("signal", r"killed by signal (?P<code>\d+)"),
+            pattern("huge", r"\w{1000}\w{1000}\w{1000}"),
+        ];
+        let (analyzer, errors) = LogAnalyzer::new(&patterns, true);
+        assert!(
+            errors.iter().any(|e| e.contains("'signal'")),
+            "{:?}",
+            errors
+        );
+        assert!(errors.iter().any(|e| e.contains("'huge'")), "{:?}", errors);
+        assert!(analyzer.is_error("task 0: Exited with exit code 1"));
+        assert!(!analyzer.is_error("killed by signal 9"));
+        // Built-ins still work after the bad patterns were dropped
+        assert!(analyzer.regex().is_match("Segmentation fault"));
+    }
+}
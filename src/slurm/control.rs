@@ -0,0 +1,288 @@
+use std::process::Command;
+
+use lazy_static::lazy_static;
+use regex::Regex;
+
+/// Run a Slurm client command, returning its stdout or Slurm's error message on failure
+fn run_slurm(program: &str, args: &[String]) -> Result<String, String> {
+    let output = Command::new(program)
+        .args(args)
+        .output()
+        .map_err(|e| format!("Failed to run {}: {}", program, e))?;
+
+    if output.status.success() {
+        Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+    } else {
+        let err = String::from_utf8_lossy(&output.stderr);
+        Err(format!("{}: {}", program, err.trim()))
+    }
+}
+
+/// Cancel one or more jobs with a single scancel call
+pub fn cancel_jobs(job_ids: &[String]) -> Result<(), String> {
+    run_slurm("scancel", job_ids).map(|_| ())
+}
+
+/// Hold pending jobs (scontrol hold accepts a comma-separated job list)
+pub fn hold_jobs(job_ids: &[String]) -> Result<(), String> {
+    run_slurm("scontrol", &["hold".to_string(), job_ids.join(",")]).map(|_| ())
+}
+
+/// Release previously held jobs
+pub fn release_jobs(job_ids: &[String]) -> Result<(), String> {
+    run_slurm("scontrol", &["release".to_string(), job_ids.join(",")]).map(|_| ())
+}
+
+/// Fields that `scontrol update` can change and the edit form exposes, in display order
+pub const EDITABLE_FIELDS: [&str; 7] = [
+    "TimeLimit",
+    "Partition",
+    "QOS",
+    "Nice",
+    "NumNodes",
+    "Comment",
+    "Dependency",
+];
+
+lazy_static! {
+    // [+|-] then minutes, M:S, H:M:S, D-H, D-H:M or D-H:M:S
+    static ref TIME_LIMIT_RE: Regex =
+        Regex::new(r"^[+-]?(\d+(:\d{1,2}){0,2}|\d+-\d{1,2}(:\d{1,2}){0,2})$").unwrap();
+    // N or N-M
+    static ref NODE_COUNT_RE: Regex = Regex::new(r"^\d+(-\d+)?$").unwrap();
+}
+
+/// Check a time limit in any format Slurm accepts
+pub fn validate_time_limit(value: &str) -> Result<(), String> {
+    let v = value.trim();
+    if v.eq_ignore_ascii_case("UNLIMITED")
+        || v.eq_ignore_ascii_case("INFINITE")
+        || TIME_LIMIT_RE.is_match(v)
+    {
+        Ok(())
+    } else {
+        Err(format!(
+            "TimeLimit '{}' must be minutes, [D-]HH:MM:SS or UNLIMITED (+/- prefix adjusts)",
+            v
+        ))
+    }
+}
+
+/// Validate an edited field value before handing it to scontrol
+pub fn validate_field(key: &str, value: &str) -> Result<(), String> {
+    let v = value.trim();
+    match key {
+        "TimeLimit" => validate_time_limit(v),
+        "Nice" => v
+            .parse::<i32>()
+            .map(|_| ())
+            .map_err(|_| format!("Nice '{}' must be an integer", v)),
+        "NumNodes" if !NODE_COUNT_RE.is_match(v) => {
+            Err(format!("NumNodes '{}' must be a count or min-max range", v))
+        }
+        "Partition" | "QOS" if v.is_empty() || v.contains(char::is_whitespace) => {
+            Err(format!("{} must be a single non-empty name", key))
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Apply changed fields with `scontrol update JobId=<id> Key=Value ...`
+pub fn update_job(job_id: &str, changes: &[(String, String)]) -> Result<(), String> {
+    let mut args = vec!["update".to_string(), format!("JobId={}", job_id)];
+    args.extend(changes.iter().map(|(k, v)| format!("{}={}", k, v.trim())));
+    run_slurm("scontrol", &args).map(|_| ())
+}
+
+/// Which processes of a job receive a signal
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SignalTarget {
+    /// Only the batch shell (`--batch`)
+    Batch,
+    /// Batch shell and every step (`--full`)
+    Full,
+    /// One job step, addressed as `<jobid>.<step>`
+    Step,
+}
+
+impl SignalTarget {
+    pub const ALL: [SignalTarget; 3] =
+        [SignalTarget::Batch, SignalTarget::Full, SignalTarget::Step];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SignalTarget::Batch => "batch",
+            SignalTarget::Full => "full",
+            SignalTarget::Step => "step",
+        }
+    }
+}
+
+impl std::str::FromStr for SignalTarget {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, String> {
+        SignalTarget::ALL
+            .into_iter()
+            .find(|t| t.as_str() == s)
+            .ok_or_else(|| format!("Unknown signal target '{}' (batch, full or step)", s))
+    }
+}
+
+/// "SIGUSR1" for names (with or without the prefix), "signal 10" for numbers
+pub fn signal_label(signal: &str) -> String {
+    let s = signal.trim();
+    if s.parse::<u32>().is_ok() {
+        format!("signal {}", s)
+    } else {
+        format!("SIG{}", s.strip_prefix("SIG").unwrap_or(s))
+    }
+}
+
+/// Accept signal names scancel understands (with or without SIG prefix) or numbers 1-64
+pub fn validate_signal(signal: &str) -> Result<(), String> {
+    let s = signal.trim();
+    if let Ok(n) = s.parse::<u32>() {
+        return if (1..=64).contains(&n) {
+            Ok(())
+        } else {
+            Err(format!("Signal number {} out of range 1-64", n))
+        };
+    }
+    let name = s.strip_prefix("SIG").unwrap_or(s);
+    if !name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric()) {
+        Ok(())
+    } else {
+        Err(format!("'{}' is not a signal name or number", s))
+    }
+}
+
+/// Send a signal via `scancel --signal=<sig>` to the chosen part of each job
+pub fn signal_jobs(
+    job_ids: &[String],
+    signal: &str,
+    target: SignalTarget,
+    step: &str,
+) -> Result<(), String> {
+    validate_signal(signal)?;
+    let mut args = vec![format!("--signal={}", signal.trim())];
+    match target {
+        SignalTarget::Batch => args.push("--batch".to_string()),
+        SignalTarget::Full => args.push("--full".to_string()),
+        SignalTarget::Step => {
+            let step = step.trim();
+            if step.is_empty() || !step.chars().all(|c| c.is_ascii_alphanumeric()) {
+                return Err(format!("Step '{}' must be a step id like 0 or batch", step));
+            }
+            args.extend(job_ids.iter().map(|id| format!("{}.{}", id, step)));
+            return run_slurm("scancel", &args).map(|_| ());
+        }
+    }
+    args.extend(job_ids.iter().cloned());
+    run_slurm("scancel", &args).map(|_| ())
+}
+
+/// Program and arguments for an interactive shell on a running job's allocation.
+/// `method` is "srun" (a new step overlapping the job's resources) or "ssh" (to the batch host).
+pub fn shell_command(
+    method: &str,
+    job_id: &str,
+    batch_host: &str,
+    shell: &str,
+) -> Result<(String, Vec<String>), String> {
+    match method {
+        "srun" => Ok((
+            "srun".to_string(),
+            vec![
+                format!("--jobid={}", job_id),
+                "--overlap".to_string(),
+                "--pty".to_string(),
+                shell.to_string(),
+            ],
+        )),
+        "ssh" if batch_host.is_empty() || batch_host == "(null)" => {
+            Err(format!("Job {} has no batch host to ssh to", job_id))
+        }
+        "ssh" => Ok((
+            "ssh".to_string(),
+            vec!["-t".to_string(), batch_host.to_string()],
+        )),
+        other => Err(format!(
+            "Unknown shell method '{}' (expected srun or ssh)",
+            other
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn time_limit_accepts_slurm_formats() {
+        for v in [
+            "60",
+            "30:00",
+            "2:00:00",
+            "1-00",
+            "1-12:00",
+            "2-00:00:00",
+            "UNLIMITED",
+            "+60",
+            "-1:00:00",
+        ] {
+            assert!(validate_time_limit(v).is_ok(), "{} should be valid", v);
+        }
+    }
+
+    #[test]
+    fn time_limit_rejects_garbage() {
+        for v in ["", "abc", "1:2:3:4", "1-", "--5", "1h"] {
+            assert!(validate_time_limit(v).is_err(), "{} should be invalid", v);
+        }
+    }
+
+    #[test]
+    fn validate_signal_names_and_numbers() {
+        for s in ["USR1", "SIGUSR1", "TERM", "10", "64"] {
+            assert!(validate_signal(s).is_ok(), "{} should be valid", s);
+        }
+        for s in ["", "0", "65", "USR 1", "SIG"] {
+            assert!(validate_signal(s).is_err(), "{} should be invalid", s);
+        }
+    }
+
+    #[test]
+    fn signal_targets_and_labels() {
+        assert_eq!("step".parse::<SignalTarget>(), Ok(SignalTarget::Step));
+        assert!("steps".parse::<SignalTarget>().is_err());
+        assert_eq!(signal_label("USR1"), "SIGUSR1");
+        assert_eq!(signal_label(" SIGTERM "), "SIGTERM");
+        assert_eq!(signal_label("10"), "signal 10");
+    }
+
+    #[test]
+    fn shell_command_methods() {
+        let (prog, args) = shell_command("srun", "42", "dgx", "/bin/zsh").unwrap();
+        assert_eq!(prog, "srun");
+        assert_eq!(args, vec!["--jobid=42", "--overlap", "--pty", "/bin/zsh"]);
+
+        let (prog, args) = shell_command("ssh", "42", "dgx", "/bin/zsh").unwrap();
+        assert_eq!(prog, "ssh");
+        assert_eq!(args, vec!["-t", "dgx"]);
+
+        assert!(shell_command("ssh", "42", "", "/bin/sh").is_err());
+        assert!(shell_command("mosh", "42", "dgx", "/bin/sh").is_err());
+    }
+
+    #[test]
+    fn validate_field_checks_numbers_and_names() {
+        assert!(validate_field("Nice", "100").is_ok());
+        assert!(validate_field("Nice", "high").is_err());
+        assert!(validate_field("NumNodes", "2-4").is_ok());
+        assert!(validate_field("NumNodes", "two").is_err());
+        assert!(validate_field("Partition", "a100").is_ok());
+        assert!(validate_field("Partition", "").is_err());
+        assert!(validate_field("Comment", "anything goes here").is_ok());
+    }
+}
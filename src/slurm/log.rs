@@ -0,0 +1,795 @@
+use std::fs::File;
+use std::io::{self, Read, Seek, SeekFrom};
+
+use regex::Regex;
+
+use super::parser::sanitize_line_ansi;
+
+/// Bytes read per seek when scanning a log file
+const CHUNK_SIZE: u64 = 8192;
+/// Lines loaded per page when scrolling past either edge of the window
+pub const PAGE_LINES: usize = 500;
+/// Lines kept in memory; the side away from the scroll direction is dropped beyond this
+pub const MAX_WINDOW_LINES: usize = 20_000;
+/// Bytes one `n`/`N` reads from disk looking for a match before handing back control
+pub const SEARCH_SCAN_BYTES: u64 = 64 * 1024 * 1024;
+/// If the file grew by more than this since the last read, reload the tail instead of appending
+pub(super) const MAX_APPEND_BYTES: u64 = 4 * 1024 * 1024;
+
+fn byte_at(file: &mut File, offset: u64) -> io::Result<u8> {
+    let mut byte = [0u8];
+    file.seek(SeekFrom::Start(offset))?;
+    file.read_exact(&mut byte)?;
+    Ok(byte[0])
+}
+
+/// Scan backward from `end` until `n` complete lines are found (or the file start).
+/// Returns the offset where those lines begin and the bytes from there up to `end`.
+/// Same approach as `tail`: only reads as many 8 KB chunks as it needs.
+pub fn scan_back(file: &mut File, end: u64, n: usize) -> io::Result<(u64, Vec<u8>)> {
+    if n == 0 || end == 0 {
+        return Ok((end, Vec::new()));
+    }
+
+    let mut buf = Vec::new();
+    let mut pos = end;
+    let mut newlines_found = 0;
+    let mut start = None;
+
+    while pos > 0 && start.is_none() {
+        let read_size = CHUNK_SIZE.min(pos);
+        pos -= read_size;
+        file.seek(SeekFrom::Start(pos))?;
+
+        let mut chunk = vec![0u8; read_size as usize];
+        file.read_exact(&mut chunk)?;
+
+        // Count newlines from the end, ignoring the terminator of the last line
+        for (i, &byte) in chunk.iter().enumerate().rev() {
+            let abs = pos + i as u64;
+            if byte == b'\n' && abs + 1 < end {
+                newlines_found += 1;
+                if newlines_found == n {
+                    start = Some(abs + 1);
+                    break;
+                }
+            }
+        }
+
+        buf.splice(0..0, chunk);
+    }
+
+    let start = start.unwrap_or(0);
+    Ok((start, buf.split_off((start - pos) as usize)))
+}
+
+/// Read forward from `start` until `n` lines are complete (or EOF).
+/// Returns the bytes and the offset just past them.
+pub fn read_forward(file: &mut File, start: u64, n: usize) -> io::Result<(Vec<u8>, u64)> {
+    file.seek(SeekFrom::Start(start))?;
+    let mut buf = Vec::new();
+    let mut newlines_found = 0;
+    let mut chunk = vec![0u8; CHUNK_SIZE as usize];
+
+    while newlines_found < n {
+        let read = file.read(&mut chunk)?;
+        if read == 0 {
+            break;
+        }
+        for (i, &byte) in chunk[..read].iter().enumerate() {
+            if byte == b'\n' {
+                newlines_found += 1;
+                if newlines_found == n {
+                    buf.extend_from_slice(&chunk[..=i]);
+                    let end = start + buf.len() as u64;
+                    return Ok((buf, end));
+                }
+            }
+        }
+        buf.extend_from_slice(&chunk[..read]);
+    }
+
+    let end = start + buf.len() as u64;
+    Ok((buf, end))
+}
+
+/// Scan from `from` (an offset at a line start and the newlines before it) until 1-based
+/// `line` begins or `end` is reached, whichever comes first. Returns the offset where the
+/// scan stopped and the newlines before it.
+fn scan_lines(
+    file: &mut File,
+    from: (u64, usize),
+    line: usize,
+    end: u64,
+) -> io::Result<(u64, usize)> {
+    let (mut pos, mut newlines) = from;
+    file.seek(SeekFrom::Start(pos))?;
+    let mut chunk = vec![0u8; CHUNK_SIZE as usize];
+    while newlines + 1 < line && pos < end {
+        let read = file.read(&mut chunk)?;
+        if read == 0 {
+            break;
+        }
+        let read = read.min((end - pos) as usize);
+        for (i, &byte) in chunk[..read].iter().enumerate() {
+            if byte == b'\n' {
+                newlines += 1;
+                if newlines + 1 == line {
+                    return Ok((pos + i as u64 + 1, newlines));
+                }
+            }
+        }
+        pos += read as u64;
+    }
+    Ok((pos, newlines))
+}
+
+/// Lines between two checkpoints of a `LineIndex`
+const LINE_INDEX_STEP: usize = 4096;
+
+/// Start offsets of every `LINE_INDEX_STEP`th line of a file, extended as the file grows, so
+/// going to a line number reads the file once rather than from the start on every jump
+#[derive(Debug, Clone, Default)]
+pub struct LineIndex {
+    path: String,
+    /// Start of lines 1, STEP + 1, 2 * STEP + 1, ...
+    checkpoints: Vec<u64>,
+    /// Bytes indexed so far and the newlines in them
+    indexed: u64,
+    newlines: usize,
+}
+
+impl LineIndex {
+    /// Index `path` up to `size`, starting over if it's another file or was truncated
+    fn update(&mut self, path: &str, file: &mut File, size: u64) -> io::Result<()> {
+        if self.path != path || size < self.indexed || self.checkpoints.is_empty() {
+            *self = Self {
+                path: path.to_string(),
+                checkpoints: vec![0],
+                ..Self::default()
+            };
+        }
+        file.seek(SeekFrom::Start(self.indexed))?;
+        let mut chunk = vec![0u8; CHUNK_SIZE as usize];
+        while self.indexed < size {
+            let read = file.read(&mut chunk)?;
+            if read == 0 {
+                break;
+            }
+            let read = read.min((size - self.indexed) as usize);
+            for (i, &byte) in chunk[..read].iter().enumerate() {
+                if byte == b'\n' {
+                    self.newlines += 1;
+                    if self.newlines.is_multiple_of(LINE_INDEX_STEP) {
+                        self.checkpoints.push(self.indexed + i as u64 + 1);
+                    }
+                }
+            }
+            self.indexed += read as u64;
+        }
+        Ok(())
+    }
+
+    /// The checkpoint at or before 1-based `line`, with the newlines before it
+    fn before_line(&self, line: usize) -> (u64, usize) {
+        let k = (line.saturating_sub(1) / LINE_INDEX_STEP).min(self.checkpoints.len() - 1);
+        (self.checkpoints[k], k * LINE_INDEX_STEP)
+    }
+
+    /// The checkpoint at or before `offset`, with the newlines before it
+    fn before_offset(&self, offset: u64) -> (u64, usize) {
+        let k = self
+            .checkpoints
+            .partition_point(|&c| c <= offset)
+            .saturating_sub(1);
+        (self.checkpoints[k], k * LINE_INDEX_STEP)
+    }
+}
+
+/// Lines split from a block of the file
+#[derive(Default)]
+pub(super) struct Lines {
+    pub lines: Vec<String>,
+    pub ansi: Vec<Option<String>>,
+    pub offsets: Vec<u64>,
+}
+
+impl Lines {
+    fn push(&mut self, offset: u64, raw: &str) {
+        let (plain, colored) = sanitize_line_ansi(raw);
+        self.lines.push(plain);
+        self.ansi.push(colored);
+        self.offsets.push(offset);
+    }
+}
+
+/// Split raw bytes starting at file offset `start` into sanitized lines and their offsets
+pub(super) fn split_lines(bytes: &[u8], start: u64) -> Lines {
+    let mut out = Lines::default();
+    let mut line_start = 0usize;
+
+    for (i, &byte) in bytes.iter().enumerate() {
+        if byte == b'\n' {
+            out.push(
+                start + line_start as u64,
+                &String::from_utf8_lossy(&bytes[line_start..i]),
+            );
+            line_start = i + 1;
+        }
+    }
+    // Last line without a trailing newline (still being written)
+    if line_start < bytes.len() {
+        out.push(
+            start + line_start as u64,
+            &String::from_utf8_lossy(&bytes[line_start..]),
+        );
+    }
+    out
+}
+
+/// Outcome of scanning a log file on disk for a pattern
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Scan {
+    /// Offset of the matching line
+    Found(u64),
+    /// Reached the start or end of the file without a match
+    NotFound,
+    /// Read the byte budget without a match; scanning can go on from this line boundary
+    Stopped(u64),
+}
+
+/// Offset of the last line before `before` that matches `re`, scanning backward page by page
+/// through at most `max_bytes`
+pub fn find_before(path: &str, before: u64, re: &Regex, max_bytes: u64) -> io::Result<Scan> {
+    let mut file = File::open(path)?;
+    let mut pos = before;
+    while pos > 0 {
+        if before - pos >= max_bytes {
+            return Ok(Scan::Stopped(pos));
+        }
+        let (start, bytes) = scan_back(&mut file, pos, PAGE_LINES)?;
+        let block = split_lines(&bytes, start);
+        if let Some(i) = block.lines.iter().rposition(|l| re.is_match(l)) {
+            return Ok(Scan::Found(block.offsets[i]));
+        }
+        pos = start;
+    }
+    Ok(Scan::NotFound)
+}
+
+/// Offset of the first line at or after `from` that matches `re`, scanning forward page by page
+/// through at most `max_bytes`
+pub fn find_after(path: &str, from: u64, re: &Regex, max_bytes: u64) -> io::Result<Scan> {
+    let mut file = File::open(path)?;
+    let mut pos = from;
+    loop {
+        if pos - from >= max_bytes {
+            return Ok(Scan::Stopped(pos));
+        }
+        let (bytes, end) = read_forward(&mut file, pos, PAGE_LINES)?;
+        if bytes.is_empty() {
+            return Ok(Scan::NotFound);
+        }
+        let block = split_lines(&bytes, pos);
+        if let Some(i) = block.lines.iter().position(|l| re.is_match(l)) {
+            return Ok(Scan::Found(block.offsets[i]));
+        }
+        pos = end;
+    }
+}
+
+/// A window of consecutive lines from a log file, plus the byte offset of each line
+/// so the window can grow in either direction or be re-positioned anywhere in the file.
+#[derive(Debug, Clone, Default)]
+pub struct LogWindow {
+    /// Sanitized plain-text lines, used for display, search and analysis
+    pub lines: Vec<String>,
+    /// Each line with its ANSI color/style sequences kept, when it has any
+    pub ansi: Vec<Option<String>>,
+    /// Byte offset in the file where each line starts
+    pub offsets: Vec<u64>,
+    /// Offset just past the last loaded line
+    pub end: u64,
+    /// File size when the window was last read
+    pub file_size: u64,
+    /// Local path used for paging; None for a one-off SSH snapshot
+    pub path: Option<String>,
+    /// 1-based line number of `lines[0]`, known once the window has touched the file start
+    pub first_line: Option<usize>,
+}
+
+impl LogWindow {
+    /// Last `n` lines of a local file
+    pub fn tail(path: &str, n: usize) -> io::Result<Self> {
+        let mut file = File::open(path)?;
+        let file_size = file.metadata()?.len();
+        let (start, bytes) = scan_back(&mut file, file_size, n)?;
+        let Lines {
+            lines,
+            ansi,
+            offsets,
+        } = split_lines(&bytes, start);
+        Ok(Self {
+            lines,
+            ansi,
+            offsets,
+            end: file_size,
+            file_size,
+            path: Some(path.to_string()),
+            first_line: (start == 0).then_some(1),
+        })
+    }
+
+    /// `n` lines starting at the first line boundary at or after `fraction` of the file
+    pub fn at_fraction(path: &str, fraction: f64, n: usize) -> io::Result<Self> {
+        let mut file = File::open(path)?;
+        let file_size = file.metadata()?.len();
+        let target = (file_size as f64 * fraction.clamp(0.0, 1.0)) as u64;
+
+        // Align to the start of the line containing `target`. scan_back skips the newline right
+        // before its end, so a `target` that already starts a line is checked first.
+        let starts_line =
+            target == 0 || (target < file_size && byte_at(&mut file, target - 1)? == b'\n');
+        let start = if starts_line {
+            target
+        } else {
+            let (line_start, _) = scan_back(&mut file, target, 1)?;
+            line_start
+        };
+        let (bytes, end) = read_forward(&mut file, start, n)?;
+        let Lines {
+            lines,
+            ansi,
+            offsets,
+        } = split_lines(&bytes, start);
+        Ok(Self {
+            lines,
+            ansi,
+            offsets,
+            end,
+            file_size,
+            path: Some(path.to_string()),
+            first_line: (start == 0).then_some(1),
+        })
+    }
+
+    /// About `n` lines centered on the line starting at `offset`
+    pub fn around(path: &str, offset: u64, n: usize) -> io::Result<Self> {
+        let mut file = File::open(path)?;
+        let file_size = file.metadata()?.len();
+        let (start, mut bytes) = scan_back(&mut file, offset, n / 2)?;
+        let (after, end) = read_forward(&mut file, offset, n - n / 2)?;
+        bytes.extend(after);
+        let Lines {
+            lines,
+            ansi,
+            offsets,
+        } = split_lines(&bytes, start);
+        Ok(Self {
+            lines,
+            ansi,
+            offsets,
+            end,
+            file_size,
+            path: Some(path.to_string()),
+            first_line: (start == 0).then_some(1),
+        })
+    }
+
+    /// About `n` lines around 1-based line `line` (the last page if the file is shorter),
+    /// with `first_line` counted so line numbers are absolute. `index` is brought up to date
+    /// and kept for the next jump in the same file.
+    pub fn at_line(path: &str, line: usize, n: usize, index: &mut LineIndex) -> io::Result<Self> {
+        let mut file = File::open(path)?;
+        let file_size = file.metadata()?.len();
+        index.update(path, &mut file, file_size)?;
+        let line = line.max(1);
+        let (offset, _) = scan_lines(&mut file, index.before_line(line), line, file_size)?;
+        let mut window = if offset >= file_size {
+            Self::tail(path, n)?
+        } else {
+            Self::around(path, offset, n)?
+        };
+        let start = window.start();
+        let (_, before) = scan_lines(&mut file, index.before_offset(start), usize::MAX, start)?;
+        window.first_line = Some(before + 1);
+        Ok(window)
+    }
+
+    /// Content that was read remotely and can't be paged (e.g. via SSH `tail`)
+    pub fn from_snapshot(text: &str) -> Self {
+        // Offsets within the snapshot still identify lines (e.g. the current search match)
+        let mut block = Lines::default();
+        let mut pos = 0;
+        for line in text.split_inclusive('\n') {
+            block.push(pos, line.trim_end_matches(['\n', '\r']));
+            pos += line.len() as u64;
+        }
+        Self {
+            lines: block.lines,
+            ansi: block.ansi,
+            offsets: block.offsets,
+            end: pos,
+            ..Self::default()
+        }
+    }
+
+    pub fn is_seekable(&self) -> bool {
+        self.path.is_some()
+    }
+
+    pub fn start(&self) -> u64 {
+        self.offsets.first().copied().unwrap_or(self.end)
+    }
+
+    pub fn at_start(&self) -> bool {
+        !self.is_seekable() || self.start() == 0
+    }
+
+    pub fn at_end(&self) -> bool {
+        !self.is_seekable() || self.end >= self.file_size
+    }
+
+    /// Whether every line of the file is loaded
+    pub fn is_complete(&self) -> bool {
+        self.at_start() && self.at_end()
+    }
+
+    /// Position of line `idx` within the file as a 0.0–1.0 fraction of its size
+    pub fn fraction_at(&self, idx: usize) -> f64 {
+        match self.offsets.get(idx) {
+            Some(&off) if self.file_size > 0 => off as f64 / self.file_size as f64,
+            _ => 1.0,
+        }
+    }
+
+    /// Index of the loaded line starting at `offset`
+    pub fn index_of(&self, offset: u64) -> Option<usize> {
+        self.offsets.binary_search(&offset).ok()
+    }
+
+    /// Prepend up to `n` older lines, returns how many were added
+    pub fn load_earlier(&mut self, n: usize) -> io::Result<usize> {
+        let path = match (&self.path, self.at_start()) {
+            (Some(p), false) => p.clone(),
+            _ => return Ok(0),
+        };
+        let mut file = File::open(&path)?;
+        let (start, bytes) = scan_back(&mut file, self.start(), n)?;
+        let block = split_lines(&bytes, start);
+        let added = block.lines.len();
+
+        self.lines.splice(0..0, block.lines);
+        self.ansi.splice(0..0, block.ansi);
+        self.offsets.splice(0..0, block.offsets);
+        self.first_line = match self.first_line {
+            Some(first) => Some(first.saturating_sub(added).max(1)),
+            None if start == 0 => Some(1),
+            None => None,
+        };
+
+        // Drop lines from the bottom to bound memory
+        if self.lines.len() > MAX_WINDOW_LINES {
+            self.end = self.offsets[MAX_WINDOW_LINES];
+            self.lines.truncate(MAX_WINDOW_LINES);
+            self.ansi.truncate(MAX_WINDOW_LINES);
+            self.offsets.truncate(MAX_WINDOW_LINES);
+        }
+        Ok(added)
+    }
+
+    /// Append up to `n` newer lines, returns (added, dropped from the top)
+    pub fn load_later(&mut self, n: usize) -> io::Result<(usize, usize)> {
+        let path = match self.path {
+            Some(ref p) => p.clone(),
+            None => return Ok((0, 0)),
+        };
+        let mut file = File::open(&path)?;
+        self.file_size = file.metadata()?.len();
+        if self.end >= self.file_size {
+            return Ok((0, 0));
+        }
+
+        // Re-read the last line in case it was incomplete
+        let resume = self.offsets.last().copied().unwrap_or(self.end);
+        let reread = self.lines.pop().is_some();
+        self.ansi.pop();
+        self.offsets.pop();
+        let before = self.lines.len();
+        let (bytes, end) = read_forward(&mut file, resume, n + 1)?;
+        let block = split_lines(&bytes, resume);
+        self.lines.extend(block.lines);
+        self.ansi.extend(block.ansi);
+        self.offsets.extend(block.offsets);
+        self.end = end;
+
+        let added = (self.lines.len() - before).saturating_sub(reread as usize);
+        Ok((added, self.trim_front()))
+    }
+
+    /// Pick up lines written since the last read while staying at the end of the file.
+    /// Reloads the tail if the file shrank (rewritten) or grew too much to append.
+    /// Returns the number of lines dropped from the top.
+    pub fn refresh_tail(&mut self, n: usize) -> io::Result<usize> {
+        let path = match self.path {
+            Some(ref p) => p.clone(),
+            None => return Ok(0),
+        };
+        let size = std::fs::metadata(&path)?.len();
+        if size < self.end || size - self.end > MAX_APPEND_BYTES {
+            *self = Self::tail(&path, n)?;
+            return Ok(0);
+        }
+        if size == self.end {
+            self.file_size = size;
+            return Ok(0);
+        }
+        let (_, dropped) = self.load_later(usize::MAX - 1)?;
+        Ok(dropped)
+    }
+
+    /// Drop lines from the top to bound memory, returns how many were dropped
+    fn trim_front(&mut self) -> usize {
+        let excess = self.lines.len().saturating_sub(MAX_WINDOW_LINES);
+        if excess > 0 {
+            self.lines.drain(..excess);
+            self.ansi.drain(..excess);
+            self.offsets.drain(..excess);
+            self.first_line = self.first_line.map(|f| f + excess);
+        }
+        excess
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write_temp(name: &str, contents: &str) -> String {
+        let dir = std::env::temp_dir().join(format!("ylurm_test_tail_{}", std::process::id()));
+        let _ = std::fs::create_dir_all(&dir);
+        let path = dir.join(name);
+        std::fs::write(&path, contents).unwrap();
+        path.to_str().unwrap().to_string()
+    }
+
+    fn numbered(count: usize) -> String {
+        (1..=count).map(|i| format!("line{}\n", i)).collect()
+    }
+
+    // ── tail ──────────────────────────────────────────────────
+
+    #[test]
+    fn tail_small_file() {
+        let path = write_temp("small.log", "line1\nline2\nline3\n");
+        let window = LogWindow::tail(&path, 2).unwrap();
+        assert_eq!(window.lines.join("\n"), "line2\nline3");
+        assert_eq!(window.offsets, vec![6, 12]);
+        assert_eq!(window.first_line, None);
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn tail_more_lines_than_file() {
+        let path = write_temp("tiny.log", "only\n");
+        let window = LogWindow::tail(&path, 500).unwrap();
+        assert_eq!(window.lines.join("\n"), "only");
+        assert_eq!(window.first_line, Some(1));
+        assert!(window.is_complete());
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn tail_empty_file() {
+        let path = write_temp("empty.log", "");
+        let window = LogWindow::tail(&path, 10).unwrap();
+        assert!(window.lines.is_empty());
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn tail_nonexistent_file() {
+        assert!(LogWindow::tail("/nonexistent/path/foo.log", 10).is_err());
+    }
+
+    #[test]
+    fn tail_keeps_unterminated_last_line() {
+        let path = write_temp("partial.log", "a\nb\nprogress 50%");
+        let window = LogWindow::tail(&path, 2).unwrap();
+        assert_eq!(window.lines, vec!["b", "progress 50%"]);
+        let _ = std::fs::remove_file(&path);
+    }
+
+    // ── paging ────────────────────────────────────────────────
+
+    #[test]
+    fn load_earlier_pages_back_to_file_start() {
+        let path = write_temp("paging.log", &numbered(30_000));
+        let mut window = LogWindow::tail(&path, 10).unwrap();
+        assert_eq!(window.lines[0], "line29991");
+
+        let added = window.load_earlier(5).unwrap();
+        assert_eq!(added, 5);
+        assert_eq!(window.lines[0], "line29986");
+        assert_eq!(window.lines.last().unwrap(), "line30000");
+
+        while !window.at_start() {
+            window.load_earlier(PAGE_LINES).unwrap();
+        }
+        assert_eq!(window.lines[0], "line1");
+        assert_eq!(window.first_line, Some(1));
+        assert_eq!(window.lines.len(), MAX_WINDOW_LINES);
+        assert!(!window.at_end());
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn at_fraction_aligns_to_line_start() {
+        let path = write_temp("fraction.log", &numbered(1000));
+        let window = LogWindow::at_fraction(&path, 0.5, 3).unwrap();
+        assert_eq!(window.lines.len(), 3);
+        assert!(window.lines[0].starts_with("line"));
+        // Every offset must point at the start of the line it belongs to
+        let contents = std::fs::read_to_string(&path).unwrap();
+        for (line, &off) in window.lines.iter().zip(&window.offsets) {
+            assert!(contents[off as usize..].starts_with(line.as_str()));
+            assert!(off == 0 || contents.as_bytes()[off as usize - 1] == b'\n');
+        }
+
+        let top = LogWindow::at_fraction(&path, 0.0, 2).unwrap();
+        assert_eq!(top.lines, vec!["line1", "line2"]);
+        assert_eq!(top.first_line, Some(1));
+        let _ = std::fs::remove_file(&path);
+
+        // A fraction landing exactly on a line start opens at that line, not the one before
+        let path = write_temp("fraction_exact.log", "aaa\nbbb\nccc\nddd\n");
+        assert_eq!(
+            LogWindow::at_fraction(&path, 0.5, 1).unwrap().lines,
+            vec!["ccc"]
+        );
+        assert_eq!(
+            LogWindow::at_fraction(&path, 0.4, 1).unwrap().lines,
+            vec!["bbb"]
+        );
+        assert_eq!(
+            LogWindow::at_fraction(&path, 1.0, 1).unwrap().lines,
+            vec!["ddd"]
+        );
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn load_later_and_refresh_tail_append_new_lines() {
+        let path = write_temp("grow.log", "a\nb\npart");
+        let mut window = LogWindow::at_fraction(&path, 0.0, 1).unwrap();
+        assert_eq!(window.lines, vec!["a"]);
+
+        let (added, dropped) = window.load_later(1).unwrap();
+        assert_eq!((added, dropped), (1, 0));
+        assert_eq!(window.lines, vec!["a", "b"]);
+
+        window.refresh_tail(10).unwrap();
+        assert_eq!(window.lines, vec!["a", "b", "part"]);
+        assert!(window.at_end());
+
+        // The partial last line is completed rather than duplicated
+        std::fs::write(&path, "a\nb\npartial done\nc\n").unwrap();
+        window.refresh_tail(10).unwrap();
+        assert_eq!(window.lines, vec!["a", "b", "partial done", "c"]);
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn snapshot_is_not_seekable() {
+        let window = LogWindow::from_snapshot("x\ny\n");
+        assert_eq!(window.lines, vec!["x", "y"]);
+        assert_eq!(window.index_of(2), Some(1));
+        assert!(window.is_complete());
+    }
+
+    #[test]
+    fn at_line_counts_line_numbers() {
+        let path = write_temp("at_line.log", &numbered(2000));
+        let mut index = LineIndex::default();
+        let window = LogWindow::at_line(&path, 1000, 100, &mut index).unwrap();
+        let first = window.first_line.unwrap();
+        assert_eq!(window.lines[1000 - first], "line1000");
+
+        // Past the end: the last page, still numbered
+        let window = LogWindow::at_line(&path, 5000, 100, &mut index).unwrap();
+        assert_eq!(window.first_line.unwrap() + window.lines.len() - 1, 2000);
+        assert!(window.at_end());
+
+        let window = LogWindow::at_line(&path, 1, 10, &mut index).unwrap();
+        assert_eq!(
+            (window.first_line, window.lines[0].as_str()),
+            (Some(1), "line1")
+        );
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn line_index_follows_a_growing_file() {
+        let path = write_temp("line_index.log", &numbered(10_000));
+        let mut index = LineIndex::default();
+        let window = LogWindow::at_line(&path, 9000, 100, &mut index).unwrap();
+        assert_eq!(window.lines[9000 - window.first_line.unwrap()], "line9000");
+        assert_eq!(index.checkpoints.len(), 10_000 / LINE_INDEX_STEP + 1);
+
+        // Appended lines are indexed from where the last jump stopped
+        std::fs::write(&path, numbered(13_000)).unwrap();
+        let window = LogWindow::at_line(&path, 12_500, 100, &mut index).unwrap();
+        assert_eq!(
+            window.lines[12_500 - window.first_line.unwrap()],
+            "line12500"
+        );
+        assert_eq!(index.newlines, 13_000);
+        let _ = std::fs::remove_file(&path);
+    }
+
+    // ── search ────────────────────────────────────────────────
+
+    #[test]
+    fn find_before_and_after_scan_outside_the_window() {
+        let mut contents = numbered(5000);
+        contents.push_str("Traceback (most recent call last):\n");
+        contents.push_str(&numbered(5000));
+        let path = write_temp("search.log", &contents);
+        let re = Regex::new("^Traceback").unwrap();
+
+        let window = LogWindow::tail(&path, 100).unwrap();
+        let Scan::Found(offset) = find_before(&path, window.start(), &re, u64::MAX).unwrap() else {
+            panic!("no match before the window");
+        };
+        assert!(contents[offset as usize..].starts_with("Traceback"));
+        assert_eq!(
+            find_after(&path, window.start(), &re, u64::MAX).unwrap(),
+            Scan::NotFound
+        );
+        assert_eq!(
+            find_after(&path, 0, &re, u64::MAX).unwrap(),
+            Scan::Found(offset)
+        );
+        assert_eq!(
+            find_before(&path, offset, &re, u64::MAX).unwrap(),
+            Scan::NotFound
+        );
+
+        let around = LogWindow::around(&path, offset, 10).unwrap();
+        let idx = around.index_of(offset).unwrap();
+        assert_eq!(idx, 5);
+        assert_eq!(around.lines[idx], "Traceback (most recent call last):");
+        assert_eq!(around.lines.len(), 10);
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn find_stops_at_the_byte_budget_on_a_line_boundary() {
+        let mut contents = numbered(20_000);
+        contents.push_str("needle\n");
+        let path = write_temp("budget.log", &contents);
+        let re = Regex::new("^needle").unwrap();
+
+        let Scan::Stopped(pos) = find_after(&path, 0, &re, 1000).unwrap() else {
+            panic!("expected the scan to stop");
+        };
+        assert!(pos >= 1000 && (pos as usize) < contents.len());
+        assert_eq!(contents.as_bytes()[pos as usize - 1], b'\n');
+        let needle = contents.len() as u64 - 7;
+        assert_eq!(
+            find_after(&path, pos, &re, u64::MAX).unwrap(),
+            Scan::Found(needle)
+        );
+
+        let end = contents.len() as u64;
+        let Scan::Stopped(back) = find_before(&path, end - 7, &re, 1000).unwrap() else {
+            panic!("expected the scan to stop");
+        };
+        assert!(back < end - 1000);
+        assert_eq!(
+            find_before(&path, back, &re, u64::MAX).unwrap(),
+            Scan::NotFound
+        );
+        let _ = std::fs::remove_file(&path);
+    }
+}
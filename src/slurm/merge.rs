@@ -0,0 +1,254 @@
+use std::fs::{File, Metadata};
+use std::io::{self, Read, Seek, SeekFrom};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use super::log::{
+    Lines, LogWindow, MAX_APPEND_BYTES, MAX_WINDOW_LINES, PAGE_LINES, scan_back, split_lines,
+};
+
+fn seconds(t: SystemTime) -> f64 {
+    t.duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs_f64())
+        .unwrap_or(0.0)
+}
+
+/// A line with its estimated write time
+struct Stamped {
+    time: f64,
+    from_stderr: bool,
+    line: String,
+    ansi: Option<String>,
+}
+
+/// Read position in one of the merged files
+#[derive(Debug, Clone)]
+struct Stream {
+    path: String,
+    /// Offset up to which complete lines have been read
+    pos: u64,
+    /// Estimated time `pos` was written (seconds since the epoch); None before the first read
+    seen_at: Option<f64>,
+}
+
+impl Stream {
+    fn new(path: &str) -> Self {
+        Self {
+            path: path.to_string(),
+            pos: 0,
+            seen_at: None,
+        }
+    }
+
+    /// Complete lines written since the last read, each stamped by interpolating its end
+    /// offset between the previous observation and the file's current (mtime, size).
+    /// The first read starts from the file's creation time and only keeps the last `tail` lines.
+    fn read_new(&mut self, from_stderr: bool, tail: usize) -> io::Result<Vec<Stamped>> {
+        let mut file = match File::open(&self.path) {
+            Ok(f) => f,
+            // Not created yet (e.g. nothing written to stderr so far)
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e),
+        };
+        let meta = file.metadata()?;
+        let size = meta.len();
+        let mtime = meta.modified().map(seconds)?;
+        if size < self.pos {
+            // Truncated or rewritten: start over
+            *self = Self::new(&self.path);
+        }
+        if size == self.pos {
+            return Ok(Vec::new());
+        }
+
+        let t0 = self.seen_at.unwrap_or_else(|| born(&meta).min(mtime));
+        let (start, mut bytes) = if self.seen_at.is_none() || size - self.pos > MAX_APPEND_BYTES {
+            scan_back(&mut file, size, tail)?
+        } else {
+            file.seek(SeekFrom::Start(self.pos))?;
+            let mut bytes = Vec::new();
+            (&mut file).take(size - self.pos).read_to_end(&mut bytes)?;
+            (self.pos, bytes)
+        };
+        // Only complete lines; a partial last line is picked up once it's finished
+        let complete = match bytes.iter().rposition(|&b| b == b'\n') {
+            Some(i) => i + 1,
+            None => return Ok(Vec::new()),
+        };
+        bytes.truncate(complete);
+        let end = start + complete as u64;
+
+        let span = (size - self.pos) as f64;
+        let t1 = mtime.max(t0);
+        let time_at = |offset: u64| t0 + (t1 - t0) * (offset - self.pos) as f64 / span;
+        let Lines {
+            lines,
+            ansi,
+            offsets,
+        } = split_lines(&bytes, start);
+        let stamped = lines
+            .into_iter()
+            .zip(ansi)
+            .enumerate()
+            .map(|(i, (line, ansi))| Stamped {
+                time: time_at(offsets.get(i + 1).copied().unwrap_or(end)),
+                from_stderr,
+                line,
+                ansi,
+            })
+            .collect();
+        self.seen_at = Some(time_at(end));
+        self.pos = end;
+        Ok(stamped)
+    }
+}
+
+/// When the file was created, falling back to its mtime where that isn't recorded
+fn born(meta: &Metadata) -> f64 {
+    meta.created()
+        .or_else(|_| meta.modified())
+        .map(seconds)
+        .unwrap_or(0.0)
+}
+
+/// stdout and stderr interleaved in approximate write order, shown in a non-seekable
+/// `LogWindow`. Slurm doesn't timestamp lines, so each line's time is estimated from its
+/// offset between the (mtime, size) observed at consecutive refreshes: lines written
+/// between two refreshes are ordered by where they fall in each file's growth.
+#[derive(Debug, Clone, Default)]
+pub struct MergedLog {
+    /// stdout and stderr; None when the logs were read over SSH and are shown one after the other
+    streams: Option<[Stream; 2]>,
+    /// Whether each line of the window came from stderr
+    pub from_stderr: Vec<bool>,
+}
+
+impl MergedLog {
+    /// Interleave the last `n` lines of each local file
+    pub fn open(stdout: &str, stderr: &str, n: usize) -> io::Result<(Self, LogWindow)> {
+        let mut merged = Self {
+            streams: Some([Stream::new(stdout), Stream::new(stderr)]),
+            from_stderr: Vec::new(),
+        };
+        let mut window = LogWindow::default();
+        merged.append(&mut window, n)?;
+        Ok((merged, window))
+    }
+
+    /// stdout followed by stderr, for snapshots read over SSH that carry no timing
+    pub fn from_snapshots(stdout: LogWindow, stderr: LogWindow) -> (Self, LogWindow) {
+        let mut merged = Self::default();
+        let mut window = LogWindow::default();
+        for (snapshot, from_stderr) in [(stdout, false), (stderr, true)] {
+            for (line, ansi) in snapshot.lines.into_iter().zip(snapshot.ansi) {
+                merged.push(&mut window, line, ansi, from_stderr);
+            }
+        }
+        (merged, window)
+    }
+
+    /// Whether lines are interleaved by time and `update` can pick up new ones
+    pub fn is_live(&self) -> bool {
+        self.streams.is_some()
+    }
+
+    /// Append lines written to either file since the last read.
+    /// Returns how many lines were dropped from the front to stay within the window limit.
+    pub fn update(&mut self, window: &mut LogWindow) -> io::Result<usize> {
+        self.append(window, PAGE_LINES)
+    }
+
+    fn append(&mut self, window: &mut LogWindow, tail: usize) -> io::Result<usize> {
+        let streams = match self.streams.as_mut() {
+            Some(s) => s,
+            None => return Ok(0),
+        };
+        let mut new = streams[0].read_new(false, tail)?;
+        new.extend(streams[1].read_new(true, tail)?);
+        // Stable sort: each stream stays in file order and stdout goes first on ties
+        new.sort_by(|a, b| a.time.total_cmp(&b.time));
+        for s in new {
+            self.push(window, s.line, s.ansi, s.from_stderr);
+        }
+
+        let excess = window.lines.len().saturating_sub(MAX_WINDOW_LINES);
+        window.lines.drain(..excess);
+        window.ansi.drain(..excess);
+        window.offsets.drain(..excess);
+        self.from_stderr.drain(..excess);
+        Ok(excess)
+    }
+
+    /// Offsets are positions in the merged text, so search and error cursors still identify lines
+    fn push(
+        &mut self,
+        window: &mut LogWindow,
+        line: String,
+        ansi: Option<String>,
+        from_stderr: bool,
+    ) {
+        window.offsets.push(window.end);
+        window.end += line.len() as u64 + 1;
+        window.lines.push(line);
+        window.ansi.push(ansi);
+        self.from_stderr.push(from_stderr);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs::OpenOptions;
+    use std::io::Write;
+    use std::time::Duration;
+
+    fn write_at(path: &std::path::Path, text: &str, append: bool, mtime: SystemTime) {
+        let mut file = OpenOptions::new()
+            .create(true)
+            .write(true)
+            .append(append)
+            .truncate(!append)
+            .open(path)
+            .unwrap();
+        file.write_all(text.as_bytes()).unwrap();
+        file.set_modified(mtime).unwrap();
+    }
+
+    #[test]
+    fn update_interleaves_new_lines_by_mtime() {
+        let dir = std::env::temp_dir().join(format!("ylurm_test_merge_{}", std::process::id()));
+        let _ = std::fs::create_dir_all(&dir);
+        let (out, err) = (dir.join("job.out"), dir.join("job.err"));
+        let t = SystemTime::now() - Duration::from_secs(100);
+        write_at(&out, "out 1\nout 2\n", false, t);
+        write_at(&err, "err 1\n", false, t);
+
+        let (mut merged, mut window) =
+            MergedLog::open(out.to_str().unwrap(), err.to_str().unwrap(), 10).unwrap();
+        assert_eq!(window.lines, vec!["out 1", "out 2", "err 1"]);
+        assert_eq!(merged.from_stderr, vec![false, false, true]);
+
+        // stderr was last written before stdout's new lines; the partial line waits
+        write_at(&out, "out 3\nout 4\nout", true, t + Duration::from_secs(20));
+        write_at(&err, "err 2\n", true, t + Duration::from_secs(5));
+        assert_eq!(merged.update(&mut window).unwrap(), 0);
+        assert_eq!(window.lines[3..], ["err 2", "out 3", "out 4"]);
+        assert_eq!(merged.from_stderr[3..], [true, false, false]);
+        assert_eq!(window.index_of(window.offsets[4]), Some(4));
+
+        assert!(merged.is_live());
+        let _ = std::fs::remove_file(&out);
+        let _ = std::fs::remove_file(&err);
+    }
+
+    #[test]
+    fn snapshots_are_concatenated() {
+        let (merged, window) = MergedLog::from_snapshots(
+            LogWindow::from_snapshot("a\nb\n"),
+            LogWindow::from_snapshot("c\n"),
+        );
+        assert_eq!(window.lines, vec!["a", "b", "c"]);
+        assert_eq!(merged.from_stderr, vec![false, false, true]);
+        assert!(!merged.is_live());
+        assert!(!window.is_seekable());
+    }
+}
@@ -0,0 +1,341 @@
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{self, Read, Seek, SeekFrom};
+
+use lazy_static::lazy_static;
+use regex::Regex;
+
+use super::log::{scan_back, split_lines};
+
+lazy_static! {
+    // HF Trainer prints dicts: {'loss': 0.6931, 'learning_rate': 5e-05, 'epoch': 0.1}
+    static ref DICT_LINE_RE: Regex = Regex::new(r"^\{.*\}$").unwrap();
+    static ref DICT_PAIR_RE: Regex =
+        Regex::new(r#"['"](?P<key>[A-Za-z_][\w/.\-]*)['"]\s*:\s*(?P<value>[-+]?[\w.+\-]+)"#).unwrap();
+    // key=value anywhere (Lightning postfix, most loggers), or lowercase `key: value`
+    static ref KV_RE: Regex =
+        Regex::new(r"\b(?P<key>[A-Za-z_][\w/.\-]*)(?P<sep>=|: ?)(?P<value>[-+]?[\w.+\-]+)").unwrap();
+}
+
+/// Keys used as the x axis instead of being plotted
+const STEP_KEYS: [&str; 4] = ["step", "global_step", "iter", "iteration"];
+/// Keys that look like metrics but aren't (Lightning's version number)
+const IGNORED_KEYS: [&str; 1] = ["v_num"];
+/// Most bytes read per update; the first scan of a big log starts this far from its end
+const MAX_SCAN_BYTES: u64 = 64 * 1024 * 1024;
+/// Points kept per metric (older ones are thinned out by half when exceeded)
+const MAX_POINTS: usize = 50_000;
+
+/// Extracts (metric, value) pairs from log lines
+#[derive(Debug, Clone)]
+pub struct MetricExtractor {
+    patterns: Vec<Regex>,
+    builtin: bool,
+}
+
+impl MetricExtractor {
+    /// Config patterns use named captures: each group is a metric, a `step` group sets the x value.
+    /// Invalid patterns are skipped and reported in the returned list of errors.
+    pub fn new(patterns: &[String], builtin: bool) -> (Self, Vec<String>) {
+        let mut compiled = Vec::new();
+        let mut errors = Vec::new();
+        for p in patterns {
+            match Regex::new(p) {
+                Ok(re) if re.capture_names().flatten().next().is_some() => compiled.push(re),
+                Ok(_) => errors.push(format!("Metric pattern '{}' has no named capture", p)),
+                Err(e) => errors.push(format!("Ignoring metric pattern '{}': {}", p, e)),
+            }
+        }
+        (
+            Self {
+                patterns: compiled,
+                builtin,
+            },
+            errors,
+        )
+    }
+
+    /// Metric values and the step (if the line has one) found in a line.
+    /// Config patterns win: built-in recognizers only run on lines they don't match.
+    pub fn extract(&self, line: &str) -> (Option<f64>, Vec<(String, f64)>) {
+        let mut step = None;
+        let mut values = Vec::new();
+        for re in &self.patterns {
+            if let Some(caps) = re.captures(line) {
+                for name in re.capture_names().flatten() {
+                    if let Some(v) = caps.name(name).and_then(|m| parse_value(m.as_str())) {
+                        if name == "step" {
+                            step = Some(v);
+                        } else {
+                            values.push((name.to_string(), v));
+                        }
+                    }
+                }
+            }
+        }
+        if !values.is_empty() || !self.builtin {
+            return (step, values);
+        }
+
+        let line = line.trim();
+        let pairs: Vec<(&str, &str)> = if DICT_LINE_RE.is_match(line) {
+            DICT_PAIR_RE
+                .captures_iter(line)
+                .map(|c| {
+                    (
+                        c.name("key").unwrap().as_str(),
+                        c.name("value").unwrap().as_str(),
+                    )
+                })
+                .collect()
+        } else {
+            KV_RE
+                .captures_iter(line)
+                .filter(|c| {
+                    // `key: value` only for lowercase keys, so "ERROR: 3 retries" isn't a metric
+                    &c["sep"] == "=" || c["key"].starts_with(|ch: char| ch.is_ascii_lowercase())
+                })
+                .map(|c| {
+                    (
+                        c.name("key").unwrap().as_str(),
+                        c.name("value").unwrap().as_str(),
+                    )
+                })
+                .collect()
+        };
+        for (key, value) in pairs {
+            let v = match parse_value(value) {
+                Some(v) => v,
+                None => continue,
+            };
+            if STEP_KEYS.contains(&key) {
+                step = Some(v);
+            } else if !IGNORED_KEYS.contains(&key) {
+                values.push((key.to_string(), v));
+            }
+        }
+        (step, values)
+    }
+}
+
+fn parse_value(s: &str) -> Option<f64> {
+    s.trim_end_matches(['.', '-', '+']).parse::<f64>().ok()
+}
+
+/// One metric over time
+#[derive(Debug, Clone, Default)]
+pub struct MetricSeries {
+    pub name: String,
+    /// (step, value); NaN/inf values are kept out and noted in `first_nan`
+    pub points: Vec<(f64, f64)>,
+    /// Step where the metric first became NaN or infinite
+    pub first_nan: Option<f64>,
+    /// Samples seen, used as the x value for lines without a step
+    samples: usize,
+}
+
+impl MetricSeries {
+    pub fn last(&self) -> Option<f64> {
+        self.points.last().map(|p| p.1)
+    }
+
+    /// (min, max) of the values
+    pub fn bounds(&self) -> Option<(f64, f64)> {
+        let mut values = self.points.iter().map(|p| p.1);
+        let first = values.next()?;
+        Some(values.fold((first, first), |(lo, hi), v| (lo.min(v), hi.max(v))))
+    }
+
+    fn push(&mut self, step: Option<f64>, value: f64) {
+        self.samples += 1;
+        let x = step.unwrap_or(self.samples as f64);
+        if !value.is_finite() {
+            self.first_nan.get_or_insert(x);
+            return;
+        }
+        // Progress bars reprint the same postfix on every update
+        if self.points.last() == Some(&(x, value)) {
+            return;
+        }
+        self.points.push((x, value));
+        if self.points.len() > MAX_POINTS {
+            let thinned = self.points.iter().step_by(2).copied().collect();
+            self.points = thinned;
+        }
+    }
+}
+
+/// Metrics read from a log, updated incrementally as the file grows
+#[derive(Debug, Clone, Default)]
+pub struct MetricsScan {
+    /// Local file being scanned; None when built from a one-off snapshot
+    pub path: Option<String>,
+    /// Offset up to which complete lines have been scanned
+    pos: u64,
+    /// The first scan skipped the start of a very large file
+    pub truncated: bool,
+    pub series: Vec<MetricSeries>,
+    index: HashMap<String, usize>,
+}
+
+impl MetricsScan {
+    pub fn new(path: Option<String>) -> Self {
+        Self {
+            path,
+            ..Self::default()
+        }
+    }
+
+    /// Metrics from lines already in memory (e.g. a log read over SSH)
+    pub fn from_lines<S: AsRef<str>>(lines: &[S], extractor: &MetricExtractor) -> Self {
+        let mut scan = Self::default();
+        for line in lines {
+            scan.add_line(line.as_ref(), extractor);
+        }
+        scan
+    }
+
+    /// Scan lines appended since the last call (at most `MAX_SCAN_BYTES` per call).
+    /// Starts over if the file shrank. Returns whether anything new was read.
+    pub fn update(&mut self, extractor: &MetricExtractor) -> io::Result<bool> {
+        let path = match self.path {
+            Some(ref p) => p.clone(),
+            None => return Ok(false),
+        };
+        let mut file = File::open(&path)?;
+        let size = file.metadata()?.len();
+        if size < self.pos {
+            *self = Self::new(Some(path));
+        }
+        if size == self.pos {
+            return Ok(false);
+        }
+        if self.pos == 0 && size > MAX_SCAN_BYTES {
+            let (start, _) = scan_back(&mut file, size - MAX_SCAN_BYTES, 1)?;
+            self.pos = start;
+            self.truncated = true;
+        }
+
+        file.seek(SeekFrom::Start(self.pos))?;
+        let mut bytes = Vec::new();
+        file.take(MAX_SCAN_BYTES).read_to_end(&mut bytes)?;
+        // Only complete lines; a partial last line is picked up next time
+        let complete = match bytes.iter().rposition(|&b| b == b'\n') {
+            Some(i) => i + 1,
+            None => return Ok(false),
+        };
+        bytes.truncate(complete);
+        for line in split_lines(&bytes, self.pos).lines {
+            self.add_line(&line, extractor);
+        }
+        self.pos += complete as u64;
+        Ok(true)
+    }
+
+    fn add_line(&mut self, line: &str, extractor: &MetricExtractor) {
+        let (step, values) = extractor.extract(line);
+        for (name, value) in values {
+            let idx = match self.index.get(&name) {
+                Some(&i) => i,
+                None => {
+                    self.series.push(MetricSeries {
+                        name: name.clone(),
+                        ..MetricSeries::default()
+                    });
+                    self.index.insert(name, self.series.len() - 1);
+                    self.series.len() - 1
+                }
+            };
+            self.series[idx].push(step, value);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn builtin() -> MetricExtractor {
+        MetricExtractor::new(&[], true).0
+    }
+
+    #[test]
+    fn extract_key_value_and_lightning_postfix() {
+        let ex = builtin();
+        let (step, values) = ex.extract("step=120 loss=0.421 lr: 1e-4. acc=91% ERROR: 3");
+        assert_eq!(step, Some(120.0));
+        assert_eq!(
+            values,
+            vec![
+                ("loss".to_string(), 0.421),
+                ("lr".to_string(), 1e-4),
+                ("acc".to_string(), 91.0)
+            ]
+        );
+
+        let (_, values) = ex.extract(
+            "Epoch 3:  12%|█▏        | 120/1000 [00:30<03:40, 4.00it/s, v_num=0, train_loss=0.421]",
+        );
+        assert_eq!(values, vec![("train_loss".to_string(), 0.421)]);
+    }
+
+    #[test]
+    fn extract_hf_trainer_dict() {
+        let (_, values) = builtin()
+            .extract("{'loss': 0.6931, 'grad_norm': nan, 'learning_rate': 5e-05, 'epoch': 0.1}");
+        let names: Vec<&str> = values.iter().map(|(k, _)| k.as_str()).collect();
+        assert_eq!(names, vec!["loss", "grad_norm", "learning_rate", "epoch"]);
+        assert!(values[1].1.is_nan());
+    }
+
+    #[test]
+    fn config_patterns_use_named_captures() {
+        let patterns = vec![
+            r"it (?P<step>\d+) \| reward (?P<reward>[-\d.]+)".to_string(),
+            "(".to_string(),
+        ];
+        let (ex, errors) = MetricExtractor::new(&patterns, true);
+        assert_eq!(errors.len(), 1);
+        let (step, values) = ex.extract("it 7 | reward -1.5 | loss=3");
+        assert_eq!(step, Some(7.0));
+        assert_eq!(values, vec![("reward".to_string(), -1.5)]);
+    }
+
+    #[test]
+    fn scan_builds_series_and_notes_nan() {
+        let lines = [
+            "loss=1.0",
+            "noise",
+            "loss=0.5",
+            "loss=0.5",
+            "loss=nan",
+            "loss=0.25",
+        ];
+        let scan = MetricsScan::from_lines(&lines, &builtin());
+        let loss = &scan.series[0];
+        assert_eq!(
+            loss.points,
+            vec![(1.0, 1.0), (2.0, 0.5), (3.0, 0.5), (5.0, 0.25)]
+        );
+        assert_eq!(loss.first_nan, Some(4.0));
+        assert_eq!(loss.bounds(), Some((0.25, 1.0)));
+    }
+
+    #[test]
+    fn update_reads_only_new_complete_lines() {
+        let path =
+            std::env::temp_dir().join(format!("ylurm_test_metrics_{}.log", std::process::id()));
+        std::fs::write(&path, "step=1 loss=2\nstep=2 loss=1\nstep=3 lo").unwrap();
+
+        let ex = builtin();
+        let mut scan = MetricsScan::new(Some(path.to_str().unwrap().to_string()));
+        assert!(scan.update(&ex).unwrap());
+        assert_eq!(scan.series[0].points, vec![(1.0, 2.0), (2.0, 1.0)]);
+        assert!(!scan.update(&ex).unwrap());
+
+        std::fs::write(&path, "step=1 loss=2\nstep=2 loss=1\nstep=3 loss=0.5\n").unwrap();
+        assert!(scan.update(&ex).unwrap());
+        assert_eq!(scan.series[0].last(), Some(0.5));
+        let _ = std::fs::remove_file(&path);
+    }
+}
@@ -1,3 +1,26 @@
+mod analyze;
+mod control;
+mod log;
+mod merge;
+mod metrics;
 mod parser;
+mod progress;
+mod submit;
 
-pub use parser::{Job, JobState, fetch_jobs, fetch_job_details, resolve_path, read_log_file};
+pub use analyze::{LikelyCause, LogAnalyzer};
+pub use control::{
+    EDITABLE_FIELDS, SignalTarget, cancel_jobs, hold_jobs, release_jobs, shell_command,
+    signal_jobs, signal_label, update_job, validate_field,
+};
+pub use log::{LineIndex, LogWindow, PAGE_LINES, SEARCH_SCAN_BYTES, Scan, find_after, find_before};
+pub use merge::MergedLog;
+pub use metrics::{MetricExtractor, MetricsScan};
+pub use parser::{
+    Job, JobState, LogLocation, fetch_job_details, fetch_job_fields, fetch_jobs, locate_log_file,
+    lookup_sacct_job, open_merged_logs, read_log_file,
+};
+pub use progress::{Progress, ProgressRequest, ProgressUpdate, latest_progress, poll_progress};
+pub use submit::{
+    SubmitSpec, fetch_batch_script, fetch_partitions, resubmit_spec, script_with_directives,
+    shell_quote, write_temp_script,
+};
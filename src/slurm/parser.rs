@@ -1,9 +1,13 @@
 use std::collections::HashMap;
-use std::io::{Read, Seek, SeekFrom};
 use std::process::Command;
+use std::sync::mpsc::{self, Receiver};
+use std::thread;
 
 use crate::config::Config;
 
+use super::log::LogWindow;
+use super::merge::MergedLog;
+
 #[derive(Debug, Clone, PartialEq)]
 pub enum JobState {
     Running,
@@ -23,7 +27,8 @@ impl JobState {
             "PD" | "PENDING" => JobState::Pending,
             "CG" | "COMPLETING" => JobState::Completing,
             "CD" | "COMPLETED" => JobState::Completed,
-            "F" | "FAILED" => JobState::Failed,
+            // sacct's end states that are failures by another name
+            "F" | "FAILED" | "OOM" | "OUT_OF_MEMORY" | "NF" | "NODE_FAIL" => JobState::Failed,
             "CA" | "CANCELLED" => JobState::Cancelled,
             "TO" | "TIMEOUT" => JobState::Timeout,
             other => JobState::Unknown(other.to_string()),
@@ -42,6 +47,15 @@ impl JobState {
             JobState::Unknown(s) => s.as_str(),
         }
     }
+
+    /// Whether the job ended abnormally (worth looking for an error in its log)
+    pub fn is_failure(&self) -> bool {
+        match self {
+            JobState::Failed | JobState::Timeout => true,
+            JobState::Unknown(s) => matches!(s.as_str(), "BF" | "BOOT_FAIL" | "DL" | "DEADLINE"),
+            _ => false,
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -62,6 +76,28 @@ pub struct Job {
     pub stdout: Option<String>,
 }
 
+#[cfg(test)]
+impl Job {
+    /// A running one-node job for tests; override fields with struct update syntax
+    pub fn test(id: &str) -> Self {
+        Job {
+            job_id: id.to_string(),
+            partition: "gpu".to_string(),
+            name: format!("train-{}", id),
+            user: "alice".to_string(),
+            state: JobState::Running,
+            time: "1:00".to_string(),
+            nodes: "1".to_string(),
+            nodelist: "node01".to_string(),
+            tres: String::new(),
+            command: String::new(),
+            work_dir: String::new(),
+            stderr: None,
+            stdout: None,
+        }
+    }
+}
+
 /// Fetch jobs from squeue (lightweight — no stderr/stdout, those come from scontrol)
 pub fn fetch_jobs(config: &Config) -> Vec<Job> {
     // %i=JobID %P=Partition %j=Name %u=User %T=State %M=Time %D=NumNodes %R=NodeList %b=TRES %o=Command %Z=WorkDir
@@ -69,10 +105,10 @@ pub fn fetch_jobs(config: &Config) -> Vec<Job> {
     let mut cmd = Command::new("squeue");
     cmd.args(["--noheader", "--format", format]);
 
-    if !config.general.all_users {
-        if let Ok(user) = std::env::var("USER") {
-            cmd.args(["--user", &user]);
-        }
+    if !config.general.all_users
+        && let Ok(user) = std::env::var("USER")
+    {
+        cmd.args(["--user", &user]);
     }
 
     for arg in &config.general.squeue_args {
@@ -120,6 +156,105 @@ pub fn parse_squeue_output(output: &str) -> Vec<Job> {
         .collect()
 }
 
+/// Parse `sacct --parsable2` rows of JobID|Partition|JobName|User|State|Elapsed|NNodes|NodeList|AllocTRES|WorkDir
+pub fn parse_sacct_output(output: &str) -> Vec<Job> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split('|').map(str::trim).collect();
+            if fields.len() < 10 || fields[0].is_empty() {
+                return None;
+            }
+            Some(Job {
+                job_id: fields[0].to_string(),
+                partition: fields[1].to_string(),
+                name: fields[2].to_string(),
+                user: fields[3].to_string(),
+                // "CANCELLED by 1234"
+                state: JobState::from_str(fields[4].split_whitespace().next().unwrap_or_default()),
+                time: fields[5].to_string(),
+                nodes: fields[6].to_string(),
+                nodelist: fields[7].to_string(),
+                tres: fields[8].to_string(),
+                command: String::new(),
+                work_dir: fields[9].to_string(),
+                stderr: None,
+                stdout: None,
+            })
+        })
+        .collect()
+}
+
+/// Look up a job that has left the queue in the accounting database. Log paths come from
+/// scontrol while it still remembers the job, else Slurm's default `slurm-<id>.out`.
+pub fn fetch_sacct_job(job_id: &str) -> Result<Option<Job>, String> {
+    let output = Command::new("sacct")
+        .args([
+            "--jobs",
+            job_id,
+            "--allocations",
+            "--noheader",
+            "--parsable2",
+        ])
+        .args([
+            "--format",
+            "JobID,Partition,JobName,User,State,Elapsed,NNodes,NodeList,AllocTRES,WorkDir",
+        ])
+        .output()
+        .map_err(|e| format!("Failed to run sacct: {}", e))?;
+    if !output.status.success() {
+        let err = String::from_utf8_lossy(&output.stderr);
+        return Err(format!("sacct: {}", err.trim()));
+    }
+    let mut job = match find_sacct_job(
+        parse_sacct_output(&String::from_utf8_lossy(&output.stdout)),
+        job_id,
+    ) {
+        Some(job) => job,
+        None => return Ok(None),
+    };
+    let job_id = job.job_id.clone();
+    match fetch_job_details(&job_id) {
+        Some(details) => {
+            job.stdout = Some(details.stdout);
+            job.stderr = Some(details.stderr);
+        }
+        None if !job.work_dir.is_empty() => {
+            let path = format!(
+                "{}/slurm-{}.out",
+                job.work_dir.trim_end_matches('/'),
+                job_id
+            );
+            job.stdout = Some(path.clone());
+            job.stderr = Some(path);
+        }
+        None => {}
+    }
+    Ok(Some(job))
+}
+
+/// The row for `job_id`, or for an array job's base ID the first of its tasks
+/// (sacct lists them as `778_1`, `778_2`, ...)
+fn find_sacct_job(mut jobs: Vec<Job>, job_id: &str) -> Option<Job> {
+    let task_prefix = format!("{}_", job_id);
+    let i = jobs
+        .iter()
+        .position(|j| j.job_id == job_id)
+        .or_else(|| jobs.iter().position(|j| j.job_id.starts_with(&task_prefix)))?;
+    Some(jobs.swap_remove(i))
+}
+
+/// `fetch_sacct_job` on a background thread; sacct can take seconds on a busy cluster
+pub fn lookup_sacct_job(job_id: &str) -> Receiver<Result<Option<Job>, String>> {
+    let (tx, rx) = mpsc::channel();
+    let job_id = job_id.to_string();
+    thread::spawn(move || {
+        // The app may have quit in the meantime
+        let _ = tx.send(fetch_sacct_job(&job_id));
+    });
+    rx
+}
+
 #[derive(Debug, Clone)]
 pub struct JobDetails {
     pub stderr: String,
@@ -175,6 +310,64 @@ pub fn fetch_job_details(job_id: &str) -> Option<JobDetails> {
     })
 }
 
+/// Fields scontrol prints on a line of their own; their values run to the end of it (or to
+/// the next of these)
+const LINE_FIELDS: &[&str] = &["Command", "WorkDir", "StdErr", "StdIn", "StdOut"];
+
+/// `Key=` where Key looks like a scontrol field name (`TimeLimit`, `CPUs/Task`, `ReqB:S:C:T`)
+fn field_key(token: &str) -> Option<(&str, &str)> {
+    let (key, val) = token.split_once('=')?;
+    let mut chars = key.chars();
+    let named = chars.next().is_some_and(|c| c.is_ascii_uppercase())
+        && chars.all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | ':' | '/'));
+    named.then_some((key, val))
+}
+
+/// Parse `scontrol show job` output into a Key=Value map.
+/// Values may contain spaces and `=` (e.g. `Comment=two words`, `Command=train.sh --lr=1`),
+/// so only a token shaped like a field name starts a new field; the rest continue the
+/// previous value.
+pub fn parse_scontrol_fields(text: &str) -> HashMap<String, String> {
+    let mut fields = HashMap::new();
+
+    for line in text.lines() {
+        let mut last_key: Option<&str> = None;
+        for token in line.split_whitespace() {
+            let rest_of_line = last_key.is_some_and(|k| LINE_FIELDS.contains(&k));
+            match field_key(token) {
+                Some((key, val)) if !rest_of_line || LINE_FIELDS.contains(&key) => {
+                    fields.insert(key.to_string(), val.to_string());
+                    last_key = Some(key);
+                }
+                _ => {
+                    if let Some(key) = last_key
+                        && let Some(val) = fields.get_mut(key)
+                    {
+                        val.push(' ');
+                        val.push_str(token);
+                    }
+                }
+            }
+        }
+    }
+    fields
+}
+
+/// Fetch every `scontrol show job` field for a job
+pub fn fetch_job_fields(job_id: &str) -> Result<HashMap<String, String>, String> {
+    let output = Command::new("scontrol")
+        .args(["show", "job", job_id])
+        .output()
+        .map_err(|e| format!("Failed to run scontrol: {}", e))?;
+    if !output.status.success() {
+        let err = String::from_utf8_lossy(&output.stderr);
+        return Err(format!("scontrol: {}", err.trim()));
+    }
+    Ok(parse_scontrol_fields(&String::from_utf8_lossy(
+        &output.stdout,
+    )))
+}
+
 /// Resolve a path using config path_mappings, falling back to the original path.
 /// e.g., "/raid/asds/projects/foo" with mapping "/raid/asds/" -> "/nfs/dgx/raid/asds/"
 /// becomes "/nfs/dgx/raid/asds/projects/foo"
@@ -187,69 +380,54 @@ pub fn resolve_path(path: &str, mappings: &HashMap<String, String>) -> String {
     path.to_string()
 }
 
-/// Read the last N lines of a file efficiently by seeking from the end.
-/// Similar to `tail -n N` — reads only a small chunk, not the entire file.
-fn tail_read(path: &str, tail_lines: usize) -> Result<String, std::io::Error> {
-    let mut file = std::fs::File::open(path)?;
-    let file_size = file.metadata()?.len();
-
-    if file_size == 0 {
-        return Ok(String::new());
-    }
-
-    // Read backward in chunks to find enough newlines
-    let chunk_size: u64 = 8192;
-    let mut buf = Vec::new();
-    let mut newlines_found = 0;
-    let mut pos = file_size;
-
-    loop {
-        let read_size = chunk_size.min(pos);
-        pos -= read_size;
-        file.seek(SeekFrom::Start(pos))?;
-
-        let mut chunk = vec![0u8; read_size as usize];
-        file.read_exact(&mut chunk)?;
-
-        // Count newlines in this chunk (from end to start)
-        for &byte in chunk.iter().rev() {
-            if byte == b'\n' {
-                newlines_found += 1;
-                // +1 because the last line might not end with \n
-                if newlines_found > tail_lines {
-                    break;
-                }
-            }
-        }
-
-        buf.splice(0..0, chunk);
+/// Whether SSH fallback is enabled and the job has a node to connect to
+fn ssh_reachable(node: &str, config: &Config) -> bool {
+    config.remote.ssh_enabled && !node.is_empty() && node != "(None)"
+}
 
-        if newlines_found > tail_lines || pos == 0 {
-            break;
-        }
-    }
+/// Where a log file can be opened from, following the same order as `read_log_file`
+#[derive(Debug, Clone, PartialEq)]
+pub enum LogLocation {
+    /// Readable on this host (after path mapping)
+    Local(String),
+    /// Only reachable by SSH to the job's node
+    Remote { node: String, path: String },
+}
 
-    let text = String::from_utf8_lossy(&buf);
-    let lines: Vec<&str> = text.lines().collect();
-    let start = lines.len().saturating_sub(tail_lines);
-    Ok(lines[start..].join("\n"))
+pub fn locate_log_file(path: &str, node: &str, config: &Config) -> Result<LogLocation, String> {
+    let resolved = resolve_path(path, &config.remote.path_mappings);
+    if std::path::Path::new(&resolved).is_file() {
+        return Ok(LogLocation::Local(resolved));
+    }
+    if ssh_reachable(node, config) {
+        return Ok(LogLocation::Remote {
+            node: node.to_string(),
+            path: path.to_string(),
+        });
+    }
+    Err(format!(
+        "Cannot open: {} (not accessible locally or via SSH)",
+        path
+    ))
 }
 
-/// Try to read a log file: first try local (with path mapping), then SSH if enabled
+/// Try to read a log file: first try local (with path mapping), then SSH if enabled.
+/// A local file yields a seekable window that can later page through the whole file;
+/// an SSH read is a one-off snapshot of the last `tail_lines` lines.
 pub fn read_log_file(
     path: &str,
     node: &str,
     config: &Config,
     tail_lines: usize,
-) -> Result<String, String> {
+) -> Result<LogWindow, String> {
     // Try path-mapped local read first
     let resolved = resolve_path(path, &config.remote.path_mappings);
-    if let Ok(content) = tail_read(&resolved, tail_lines) {
-        return Ok(sanitize_log_content(&content));
+    if let Ok(window) = LogWindow::tail(&resolved, tail_lines) {
+        return Ok(window);
     }
 
     // Fall back to SSH if enabled
-    if config.remote.ssh_enabled && !node.is_empty() && node != "(None)" {
+    if ssh_reachable(node, config) {
         let ssh_result = Command::new("ssh")
             .args([
                 "-o", "ConnectTimeout=3",
@@ -262,8 +440,8 @@ pub fn read_log_file(
 
         match ssh_result {
             Ok(output) if output.status.success() => {
-                let raw = String::from_utf8_lossy(&output.stdout).to_string();
-                return Ok(sanitize_log_content(&raw));
+                let raw = String::from_utf8_lossy(&output.stdout);
+                return Ok(LogWindow::from_snapshot(&raw));
             }
             Ok(output) => {
                 let err = String::from_utf8_lossy(&output.stderr);
@@ -278,29 +456,63 @@ pub fn read_log_file(
     Err(format!("Cannot read: {} (not accessible locally or via SSH)", path))
 }
 
-/// Sanitize log content for TUI display:
-/// 1. Simulate terminal \r behavior: for each \n-delimited line, split by \r
-///    and keep only the last non-empty segment (what a terminal would show).
+/// Open stdout and stderr for the merged view: interleaved when readable on this host,
+/// otherwise SSH snapshots of both shown one after the other
+pub fn open_merged_logs(
+    stdout: &str,
+    stderr: &str,
+    node: &str,
+    config: &Config,
+    tail_lines: usize,
+) -> Result<(MergedLog, LogWindow), String> {
+    let out = resolve_path(stdout, &config.remote.path_mappings);
+    let err = resolve_path(stderr, &config.remote.path_mappings);
+    if std::path::Path::new(&out).is_file() || std::path::Path::new(&err).is_file() {
+        return MergedLog::open(&out, &err, tail_lines)
+            .map_err(|e| format!("Cannot read logs: {}", e));
+    }
+    let out = read_log_file(stdout, node, config, tail_lines)?;
+    // Nothing may have been written to stderr yet
+    let err = read_log_file(stderr, node, config, tail_lines).unwrap_or_default();
+    Ok(MergedLog::from_snapshots(out, err))
+}
+
+/// Sanitize a log line for TUI display:
+/// 1. Simulate terminal \r behavior: split by \r and keep only the last non-empty
+///    segment (what a terminal would show).
 /// 2. Strip ANSI escape sequences (colors, cursor movement) that ratatui
 ///    Paragraph doesn't interpret.
-fn sanitize_log_content(content: &str) -> String {
-    content
-        .lines()
-        .map(|line| {
-            // Simulate carriage return: keep only the last \r-segment
-            let last_segment = line.rsplit('\r')
-                .find(|s| !s.is_empty())
-                .unwrap_or("");
-            strip_ansi(last_segment)
-        })
-        .collect::<Vec<String>>()
-        .join("\n")
+///
+/// Also returns the line with its SGR (color/style) sequences kept when it has any;
+/// other sequences are dropped from both.
+pub(super) fn sanitize_line_ansi(line: &str) -> (String, Option<String>) {
+    let segment = last_cr_segment(line);
+    let plain = strip_ansi(segment);
+    if !segment.contains('\x1b') {
+        return (plain, None);
+    }
+    let colored = filter_ansi(segment, true);
+    if colored == plain {
+        (plain, None)
+    } else {
+        (plain, Some(colored))
+    }
+}
+
+/// Simulate carriage return: keep only the last non-empty \r-segment
+fn last_cr_segment(line: &str) -> &str {
+    line.rsplit('\r').find(|s| !s.is_empty()).unwrap_or("")
 }
 
 /// Strip ANSI escape sequences from a string.
 /// Handles CSI sequences: ESC [ <params> <final byte>
 /// and OSC sequences: ESC ] ... BEL/ST
 fn strip_ansi(s: &str) -> String {
+    filter_ansi(s, false)
+}
+
+/// Drop ANSI escape sequences, optionally keeping SGR ones (`ESC [ ... m`)
+fn filter_ansi(s: &str, keep_sgr: bool) -> String {
     let mut result = String::with_capacity(s.len());
     let bytes = s.as_bytes();
     let len = bytes.len();
@@ -309,11 +521,17 @@ fn strip_ansi(s: &str) -> String {
         if bytes[i] == 0x1b && i + 1 < len {
             if bytes[i + 1] == b'[' {
                 // CSI sequence: ESC [ ... <final byte (0x40-0x7E)>
+                let seq_start = i;
                 i += 2;
                 while i < len && !(bytes[i] >= 0x40 && bytes[i] <= 0x7E) {
                     i += 1;
                 }
-                if i < len { i += 1; } // skip final byte
+                if i < len {
+                    if keep_sgr && bytes[i] == b'm' {
+                        result.push_str(&s[seq_start..=i]);
+                    }
+                    i += 1; // skip final byte
+                }
             } else if bytes[i + 1] == b']' {
                 // OSC sequence: ESC ] ... (BEL or ESC \)
                 i += 2;
@@ -415,37 +633,61 @@ mod tests {
         assert_eq!(strip_ansi(""), "");
     }
 
-    // ── sanitize_log_content ──────────────────────────────────
+    #[test]
+    fn sanitize_line_ansi_keeps_only_sgr() {
+        let (plain, colored) = sanitize_line_ansi("\x1b[2K\x1b[1;32mPASSED\x1b[0m test_a\x1b[A");
+        assert_eq!(plain, "PASSED test_a");
+        assert_eq!(colored.as_deref(), Some("\x1b[1;32mPASSED\x1b[0m test_a"));
+
+        let (plain, colored) = sanitize_line_ansi("\x1b[32mold\x1b[0m\r\x1b[33mnew\x1b[0m");
+        assert_eq!(plain, "new");
+        assert_eq!(colored.as_deref(), Some("\x1b[33mnew\x1b[0m"));
+
+        assert_eq!(sanitize_line_ansi("bar\x1b[A"), ("bar".to_string(), None));
+        assert_eq!(sanitize_line_ansi("plain"), ("plain".to_string(), None));
+    }
+
+    // ── sanitize_line_ansi, as the log window applies it ─────
+
+    /// Plain text of each line, the way `LogWindow` splits and cleans a block
+    fn sanitize_lines(content: &str) -> Vec<String> {
+        content
+            .lines()
+            .map(|line| sanitize_line_ansi(line).0)
+            .collect()
+    }
 
     #[test]
     fn sanitize_simulates_carriage_return() {
         // \r overwrites the line — only the last segment should survive
-        // .lines() strips trailing \n, .join("\n") doesn't re-add it
         let input = "Epoch 1:  0%|  | 0/100\rEpoch 1: 50%|█████| 50/100\rEpoch 1: 100%|██████████| 100/100\n";
-        let result = sanitize_log_content(input);
-        assert_eq!(result, "Epoch 1: 100%|██████████| 100/100");
+        assert_eq!(sanitize_lines(input), ["Epoch 1: 100%|██████████| 100/100"]);
     }
 
     #[test]
     fn sanitize_strips_ansi_after_cr() {
         let input = "\x1b[32mold\x1b[0m\r\x1b[33mnew text\x1b[0m\n";
-        let result = sanitize_log_content(input);
-        assert_eq!(result, "new text");
+        assert_eq!(sanitize_lines(input), ["new text"]);
     }
 
     #[test]
     fn sanitize_handles_plain_text() {
         let input = "line 1\nline 2\nline 3\n";
-        let result = sanitize_log_content(input);
-        assert_eq!(result, "line 1\nline 2\nline 3");
+        assert_eq!(sanitize_lines(input), ["line 1", "line 2", "line 3"]);
     }
 
     #[test]
     fn sanitize_handles_pytorch_lightning_progress() {
         // Simulates typical PyTorch Lightning output with \r + \x1b[A
-        let input = "\rValidation: 50%|█████| 5/10\x1b[A\n\rValidation: 100%|██████████| 10/10\x1b[A\n";
-        let result = sanitize_log_content(input);
-        assert_eq!(result, "Validation: 50%|█████| 5/10\nValidation: 100%|██████████| 10/10");
+        let input =
+            "\rValidation: 50%|█████| 5/10\x1b[A\n\rValidation: 100%|██████████| 10/10\x1b[A\n";
+        assert_eq!(
+            sanitize_lines(input),
+            [
+                "Validation: 50%|█████| 5/10",
+                "Validation: 100%|██████████| 10/10"
+            ]
+        );
     }
 
     #[test]
@@ -457,50 +699,39 @@ mod tests {
             input.push_str(&format!("Step {}/100", i + 1));
         }
         input.push('\n');
-        let result = sanitize_log_content(&input);
-        assert_eq!(result, "Step 100/100");
-        assert!(!result.is_empty());
-    }
-
-    // ── tail_read ─────────────────────────────────────────────
-
-    #[test]
-    fn tail_read_small_file() {
-        let dir = std::env::temp_dir().join("ylurm_test_tail");
-        let _ = std::fs::create_dir_all(&dir);
-        let path = dir.join("small.log");
-        std::fs::write(&path, "line1\nline2\nline3\n").unwrap();
-        let result = tail_read(path.to_str().unwrap(), 2).unwrap();
-        assert_eq!(result, "line2\nline3");
-        let _ = std::fs::remove_file(&path);
+        assert_eq!(sanitize_lines(&input), ["Step 100/100"]);
     }
 
-    #[test]
-    fn tail_read_more_lines_than_file() {
-        let dir = std::env::temp_dir().join("ylurm_test_tail");
-        let _ = std::fs::create_dir_all(&dir);
-        let path = dir.join("tiny.log");
-        std::fs::write(&path, "only\n").unwrap();
-        let result = tail_read(path.to_str().unwrap(), 500).unwrap();
-        assert_eq!(result, "only");
-        let _ = std::fs::remove_file(&path);
-    }
+    // ── locate_log_file ───────────────────────────────────────
 
     #[test]
-    fn tail_read_empty_file() {
-        let dir = std::env::temp_dir().join("ylurm_test_tail");
+    fn locate_log_file_prefers_local() {
+        let dir = std::env::temp_dir().join(format!("ylurm_test_locate_{}", std::process::id()));
         let _ = std::fs::create_dir_all(&dir);
-        let path = dir.join("empty.log");
-        std::fs::write(&path, "").unwrap();
-        let result = tail_read(path.to_str().unwrap(), 10).unwrap();
-        assert_eq!(result, "");
+        let path = dir.join("job.out");
+        std::fs::write(&path, "x\n").unwrap();
+        let config = Config::default();
+        let p = path.to_str().unwrap();
+        assert_eq!(
+            locate_log_file(p, "dgx", &config),
+            Ok(LogLocation::Local(p.to_string()))
+        );
         let _ = std::fs::remove_file(&path);
     }
 
     #[test]
-    fn tail_read_nonexistent_file() {
-        let result = tail_read("/nonexistent/path/foo.log", 10);
-        assert!(result.is_err());
+    fn locate_log_file_falls_back_to_ssh() {
+        let mut config = Config::default();
+        let missing = "/nonexistent/ylurm/job.out";
+        assert_eq!(
+            locate_log_file(missing, "dgx", &config),
+            Ok(LogLocation::Remote {
+                node: "dgx".into(),
+                path: missing.into()
+            })
+        );
+        config.remote.ssh_enabled = false;
+        assert!(locate_log_file(missing, "dgx", &config).is_err());
     }
 
     // ── parse_squeue_output ───────────────────────────────────
@@ -529,6 +760,72 @@ mod tests {
         assert!(jobs.is_empty());
     }
 
+    #[test]
+    fn parse_sacct_rows() {
+        let output = "777|gpu|train|bob|CANCELLED by 1001|00:12:30|2|dgx[01-02]|cpu=8,gres/gpu=2|/home/bob/run\n\
+                      778_3|gpu|eval|bob|OUT_OF_MEMORY|1-02:00:00|1|dgx03|cpu=4|/home/bob\n";
+        let jobs = parse_sacct_output(output);
+        assert_eq!(jobs.len(), 2);
+        assert_eq!(jobs[0].state, JobState::Cancelled);
+        assert_eq!(jobs[0].nodelist, "dgx[01-02]");
+        assert_eq!(jobs[1].job_id, "778_3");
+        assert_eq!(jobs[1].state, JobState::Failed);
+        assert!(jobs[1].state.is_failure());
+        assert_eq!(jobs[1].work_dir, "/home/bob");
+    }
+
+    #[test]
+    fn sacct_lookup_falls_back_to_the_first_array_task() {
+        let output = "7780|gpu|other|bob|COMPLETED|00:01:00|1|dgx01|cpu=1|/home/bob\n\
+                      778_1|gpu|eval|bob|NODE_FAIL|00:02:00|1|dgx02|cpu=1|/home/bob\n\
+                      778_2|gpu|eval|bob|COMPLETED|00:03:00|1|dgx03|cpu=1|/home/bob\n";
+        let job = find_sacct_job(parse_sacct_output(output), "778").unwrap();
+        assert_eq!(job.job_id, "778_1");
+        assert_eq!(job.state, JobState::Failed);
+        let job = find_sacct_job(parse_sacct_output(output), "778_2").unwrap();
+        assert_eq!(job.job_id, "778_2");
+        assert!(find_sacct_job(parse_sacct_output(output), "77").is_none());
+    }
+
+    // ── parse_scontrol_fields ─────────────────────────────────
+
+    #[test]
+    fn parse_scontrol_fields_basic() {
+        let text = "JobId=123 JobName=train\n   Partition=a100 TimeLimit=1-00:00:00 Nice=0\n";
+        let fields = parse_scontrol_fields(text);
+        assert_eq!(fields["JobId"], "123");
+        assert_eq!(fields["Partition"], "a100");
+        assert_eq!(fields["TimeLimit"], "1-00:00:00");
+        assert_eq!(fields["Nice"], "0");
+    }
+
+    #[test]
+    fn parse_scontrol_fields_value_with_spaces() {
+        let text = "Comment=rerun after node failure Nice=0";
+        let fields = parse_scontrol_fields(text);
+        assert_eq!(fields["Comment"], "rerun after node failure");
+        assert_eq!(fields["Nice"], "0");
+    }
+
+    #[test]
+    fn parse_scontrol_fields_value_with_equals() {
+        let text = "JobId=7 Comment=a=b c Nice=0\n   Command=/home/u/train.sh --lr=1e-3 BATCH=32\n   WorkDir=/home/u/run 1\n";
+        let fields = parse_scontrol_fields(text);
+        assert_eq!(fields["Comment"], "a=b c");
+        assert_eq!(fields["Nice"], "0");
+        assert_eq!(fields["Command"], "/home/u/train.sh --lr=1e-3 BATCH=32");
+        assert_eq!(fields["WorkDir"], "/home/u/run 1");
+        assert!(!fields.contains_key("--lr"));
+        assert!(!fields.contains_key("BATCH"));
+    }
+
+    #[test]
+    fn parse_scontrol_fields_empty_value() {
+        let fields = parse_scontrol_fields("Dependency= Reason=None");
+        assert_eq!(fields["Dependency"], "");
+        assert_eq!(fields["Reason"], "None");
+    }
+
     // ── Integration: resolve + read on cluster NFS ────────────
 
     #[test]
@@ -0,0 +1,246 @@
+use std::collections::HashMap;
+use std::sync::mpsc::{self, Receiver};
+use std::thread;
+
+use lazy_static::lazy_static;
+use regex::Regex;
+
+use super::log::LogWindow;
+use super::parser::{JobDetails, fetch_job_details, resolve_path};
+
+lazy_static! {
+    // tqdm / Lightning: "Epoch 3:  45%|████▌     | 450/1000 [01:23<01:40,  5.47it/s, loss=0.1]"
+    static ref TQDM_RE: Regex = Regex::new(
+        r"^(?P<desc>.*?)[:\s]*(?P<pct>\d{1,3})%\|[^|]*\|\s*(?P<n>[\d.]+[kMGT]?)/(?P<total>[\d.]+[kMGT]?)\s*\[(?P<elapsed>[\d:]+)<(?P<remaining>[\d:?]+)(?:,\s*(?P<rate>[^,\]]+))?(?P<post>[^\]]*)\]"
+    )
+    .unwrap();
+    static ref DIGITS_RE: Regex = Regex::new(r"\d+").unwrap();
+}
+
+/// Most bars kept per job (Lightning shows at most train + val + test at once)
+const MAX_BARS: usize = 4;
+/// Lines scanned back from the end of a log for progress bars
+const SCAN_LINES: usize = 200;
+
+/// One progress bar as last printed
+#[derive(Debug, Clone, PartialEq)]
+pub struct Progress {
+    /// Bar description, e.g. "Epoch 3" or "Validation DataLoader 0"
+    pub desc: String,
+    pub percent: u16,
+    pub n: String,
+    pub total: String,
+    pub elapsed: String,
+    /// Estimated time left, "?" when tqdm doesn't know yet
+    pub remaining: String,
+    pub rate: String,
+    /// Lightning metrics after the rate, e.g. "v_num=0, loss=0.123"
+    pub postfix: String,
+}
+
+impl Progress {
+    /// Bars are the same if their descriptions differ only in numbers (Epoch 3 → Epoch 4)
+    fn key(&self) -> String {
+        DIGITS_RE.replace_all(&self.desc, "#").to_string()
+    }
+
+    /// Short label for gauges and the job list
+    pub fn summary(&self) -> String {
+        let desc = if self.desc.is_empty() {
+            "progress"
+        } else {
+            &self.desc
+        };
+        let mut s = format!("{} {}% {}/{}", desc, self.percent, self.n, self.total);
+        if self.remaining != "?" {
+            s.push_str(&format!(" ETA {}", self.remaining));
+        }
+        if !self.rate.is_empty() && !self.rate.starts_with('?') {
+            s.push_str(&format!(" {}", self.rate));
+        }
+        if !self.postfix.is_empty() {
+            s.push_str(&format!(" {}", self.postfix));
+        }
+        s
+    }
+}
+
+/// Parse a tqdm-style progress line
+pub fn parse_progress(line: &str) -> Option<Progress> {
+    let caps = TQDM_RE.captures(line.trim())?;
+    let get = |name: &str| {
+        caps.name(name)
+            .map(|m| m.as_str().trim())
+            .unwrap_or("")
+            .to_string()
+    };
+    Some(Progress {
+        desc: get("desc"),
+        percent: get("pct").parse::<u16>().ok()?.min(100),
+        n: get("n"),
+        total: get("total"),
+        elapsed: get("elapsed"),
+        remaining: get("remaining"),
+        rate: get("rate"),
+        postfix: get("post").trim_start_matches(',').trim().to_string(),
+    })
+}
+
+/// The latest state of each progress bar near the end of a log, in the order they appear
+pub fn latest_progress<S: AsRef<str>>(lines: &[S]) -> Vec<Progress> {
+    let mut bars: Vec<Progress> = Vec::new();
+    for line in lines.iter().rev().take(SCAN_LINES) {
+        if let Some(p) = parse_progress(line.as_ref())
+            && !bars.iter().any(|b| b.key() == p.key())
+        {
+            bars.push(p);
+            if bars.len() == MAX_BARS {
+                break;
+            }
+        }
+    }
+    bars.reverse();
+    bars
+}
+
+/// A job whose log the background poll reads: its ID and stdout path, when already known
+#[derive(Debug, Clone)]
+pub struct ProgressRequest {
+    pub job_id: String,
+    pub stdout: Option<String>,
+}
+
+/// What the poll found for one job
+#[derive(Debug, Clone)]
+pub struct ProgressUpdate {
+    pub job_id: String,
+    /// Paths looked up with scontrol because the request had none
+    pub details: Option<JobDetails>,
+    pub bars: Vec<Progress>,
+}
+
+/// Parse progress bars from the end of each job's stdout on a background thread. Jobs without
+/// a known path get one from scontrol first; only files readable from this machine (directly
+/// or through `path_mappings`) are read, never over SSH.
+pub fn poll_progress(
+    requests: Vec<ProgressRequest>,
+    mappings: HashMap<String, String>,
+) -> Receiver<Vec<ProgressUpdate>> {
+    let (tx, rx) = mpsc::channel();
+    thread::spawn(move || {
+        let updates = requests
+            .into_iter()
+            .map(|req| {
+                let details = match req.stdout {
+                    Some(_) => None,
+                    None => fetch_job_details(&req.job_id),
+                };
+                let path = req
+                    .stdout
+                    .as_deref()
+                    .or(details.as_ref().map(|d| d.stdout.as_str()));
+                let bars = path
+                    .and_then(|p| LogWindow::tail(&resolve_path(p, &mappings), SCAN_LINES).ok())
+                    .map(|window| latest_progress(&window.lines))
+                    .unwrap_or_default();
+                ProgressUpdate {
+                    job_id: req.job_id,
+                    details,
+                    bars,
+                }
+            })
+            .collect();
+        // The app may have quit in the meantime
+        let _ = tx.send(updates);
+    });
+    rx
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_tqdm_line() {
+        let p =
+            parse_progress("train:  45%|████▌     | 450/1000 [01:23<01:40,  5.47it/s]").unwrap();
+        assert_eq!(p.desc, "train");
+        assert_eq!(p.percent, 45);
+        assert_eq!((p.n.as_str(), p.total.as_str()), ("450", "1000"));
+        assert_eq!(
+            (p.elapsed.as_str(), p.remaining.as_str()),
+            ("01:23", "01:40")
+        );
+        assert_eq!(p.rate, "5.47it/s");
+        assert_eq!(p.postfix, "");
+    }
+
+    #[test]
+    fn parse_lightning_line_with_metrics() {
+        let p = parse_progress(
+            "Epoch 3:  12%|█▏        | 120/1000 [00:30<03:40, 4.00it/s, v_num=0, train_loss=0.421]",
+        )
+        .unwrap();
+        assert_eq!(p.desc, "Epoch 3");
+        assert_eq!(p.postfix, "v_num=0, train_loss=0.421");
+        assert_eq!(
+            p.summary(),
+            "Epoch 3 12% 120/1000 ETA 03:40 4.00it/s v_num=0, train_loss=0.421"
+        );
+    }
+
+    #[test]
+    fn parse_unknown_remaining_and_no_desc() {
+        let p = parse_progress("  0%|          | 0/3.2k [00:00<?, ?it/s]").unwrap();
+        assert_eq!(p.desc, "");
+        assert_eq!(p.total, "3.2k");
+        assert_eq!(p.remaining, "?");
+        assert_eq!(p.summary(), "progress 0% 0/3.2k");
+        assert!(parse_progress("loss=0.3 acc=91%").is_none());
+    }
+
+    #[test]
+    fn latest_progress_keeps_last_state_per_bar() {
+        let lines = [
+            "Epoch 0: 100%|██████████| 10/10 [00:05<00:00, 2.00it/s]",
+            "some log line",
+            "Epoch 1:  50%|█████     | 5/10 [00:02<00:02, 2.00it/s]",
+            "Validation DataLoader 0:  30%|███       | 3/10 [00:01<00:02, 3.00it/s]",
+            "Epoch 1:  60%|██████    | 6/10 [00:03<00:02, 2.00it/s]",
+        ];
+        let bars = latest_progress(&lines);
+        assert_eq!(bars.len(), 2);
+        assert_eq!(bars[0].desc, "Validation DataLoader 0");
+        assert_eq!(bars[1].desc, "Epoch 1");
+        assert_eq!(bars[1].percent, 60);
+    }
+
+    #[test]
+    fn poll_reads_log_tails_in_the_background() {
+        let path = std::env::temp_dir().join(format!(
+            "ylurm_test_poll_progress_{}.out",
+            std::process::id()
+        ));
+        std::fs::write(
+            &path,
+            "start\nEpoch 1:  30%|███       | 3/10 [00:03<00:07,  1.00it/s]\n",
+        )
+        .unwrap();
+        let requests = vec![
+            ProgressRequest {
+                job_id: "1".into(),
+                stdout: Some(path.display().to_string()),
+            },
+            ProgressRequest {
+                job_id: "2".into(),
+                stdout: Some("/nonexistent/ylurm.out".into()),
+            },
+        ];
+        let updates = poll_progress(requests, HashMap::new()).recv().unwrap();
+        assert_eq!(updates.len(), 2);
+        assert_eq!(updates[0].bars.len(), 1);
+        assert_eq!(updates[0].bars[0].percent, 30);
+        assert!(updates[1].bars.is_empty());
+        let _ = std::fs::remove_file(&path);
+    }
+}
@@ -0,0 +1,490 @@
+use std::collections::HashMap;
+use std::fs::OpenOptions;
+use std::hash::{BuildHasher, RandomState};
+use std::io::{ErrorKind, Write};
+use std::os::unix::fs::OpenOptionsExt;
+use std::path::PathBuf;
+use std::process::Command;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use lazy_static::lazy_static;
+use regex::Regex;
+
+use super::control::validate_time_limit;
+
+lazy_static! {
+    static ref MEMORY_RE: Regex = Regex::new(r"^\d+[KMGT]?$").unwrap();
+    static ref SUBMITTED_RE: Regex = Regex::new(r"Submitted batch job (\d+)").unwrap();
+}
+
+/// Partition names from sinfo, with the default partition first
+pub fn fetch_partitions() -> Vec<String> {
+    let output = match Command::new("sinfo")
+        .args(["--noheader", "--format", "%P"])
+        .output()
+    {
+        Ok(o) if o.status.success() => o,
+        _ => return vec![],
+    };
+    parse_partitions(&String::from_utf8_lossy(&output.stdout))
+}
+
+/// Parse `sinfo -h -o %P`: one partition per line, the default one suffixed with `*`
+pub fn parse_partitions(output: &str) -> Vec<String> {
+    let mut partitions: Vec<String> = Vec::new();
+    for line in output.lines().map(str::trim).filter(|l| !l.is_empty()) {
+        let name = line.trim_end_matches('*').to_string();
+        if partitions.contains(&name) {
+            continue;
+        }
+        if line.ends_with('*') {
+            partitions.insert(0, name);
+        } else {
+            partitions.push(name);
+        }
+    }
+    partitions
+}
+
+/// Everything the submission form collects; empty fields are left to Slurm defaults
+#[derive(Debug, Clone, Default)]
+pub struct SubmitSpec {
+    pub partition: String,
+    pub gpus: String,
+    pub cpus: String,
+    pub mem: String,
+    pub time: String,
+    pub name: String,
+    pub output: String,
+    pub error: String,
+    pub workdir: String,
+    pub script: String,
+    /// Extra raw sbatch options (e.g. `--array=0-9`), inserted before the script
+    pub extra: Vec<String>,
+}
+
+impl SubmitSpec {
+    /// sbatch arguments (without the program name), script path last once it's filled in
+    pub fn sbatch_args(&self) -> Vec<String> {
+        let mut args = self.option_args();
+        if !self.script.trim().is_empty() {
+            args.push(self.script.trim().to_string());
+        }
+        args
+    }
+
+    /// The `--option=value` arguments alone
+    fn option_args(&self) -> Vec<String> {
+        let mut args = Vec::new();
+        let mut opt = |flag: &str, value: &str| {
+            let value = value.trim();
+            if !value.is_empty() {
+                args.push(format!("--{}={}", flag, value));
+            }
+        };
+        opt("partition", &self.partition);
+        if self.gpus.trim() != "0" {
+            opt("gpus", &self.gpus);
+        }
+        opt("cpus-per-task", &self.cpus);
+        opt("mem", &self.mem);
+        opt("time", &self.time);
+        opt("job-name", &self.name);
+        opt("output", &self.output);
+        opt("error", &self.error);
+        opt("chdir", &self.workdir);
+        args.extend(self.extra.iter().cloned());
+        args
+    }
+
+    /// Shell-style preview of the command that will run
+    pub fn command_line(&self) -> String {
+        let mut parts = vec!["sbatch".to_string()];
+        parts.extend(self.sbatch_args().iter().map(|a| shell_quote(a)));
+        parts.join(" ")
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if self.script.trim().is_empty() {
+            return Err("Script path is required".into());
+        }
+        for (label, value) in [("GPUs", &self.gpus), ("CPUs", &self.cpus)] {
+            let v = value.trim();
+            if !v.is_empty() && v.parse::<u32>().is_err() {
+                return Err(format!("{} '{}' must be a whole number", label, v));
+            }
+        }
+        let mem = self.mem.trim();
+        if !mem.is_empty() && !MEMORY_RE.is_match(mem) {
+            return Err(format!(
+                "Memory '{}' must be a number with optional K/M/G/T suffix",
+                mem
+            ));
+        }
+        if !self.time.trim().is_empty() {
+            validate_time_limit(&self.time)?;
+        }
+        Ok(())
+    }
+
+    fn run(&self, test_only: bool) -> Result<std::process::Output, String> {
+        self.validate()?;
+        let mut cmd = Command::new("sbatch");
+        if test_only {
+            cmd.arg("--test-only");
+        }
+        cmd.args(self.sbatch_args());
+        // Resolve a relative script path against the chosen working directory
+        if !self.workdir.trim().is_empty() {
+            cmd.current_dir(self.workdir.trim());
+        }
+        let output = cmd
+            .output()
+            .map_err(|e| format!("Failed to run sbatch: {}", e))?;
+        if output.status.success() {
+            Ok(output)
+        } else {
+            let err = String::from_utf8_lossy(&output.stderr);
+            Err(format!("sbatch: {}", err.trim()))
+        }
+    }
+
+    /// `sbatch --test-only`: Slurm's estimated start time (printed on stderr)
+    pub fn test_only(&self) -> Result<String, String> {
+        let output = self.run(true)?;
+        let msg = String::from_utf8_lossy(&output.stderr);
+        Ok(msg.trim().trim_start_matches("sbatch: ").to_string())
+    }
+
+    /// Submit the job, returning the new job ID
+    pub fn submit(&self) -> Result<String, String> {
+        let output = self.run(false)?;
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        parse_submitted_job_id(&stdout)
+            .ok_or_else(|| format!("sbatch: unexpected output '{}'", stdout.trim()))
+    }
+}
+
+/// Extract the job ID from "Submitted batch job 12345"
+pub fn parse_submitted_job_id(output: &str) -> Option<String> {
+    SUBMITTED_RE.captures(output).map(|c| c[1].to_string())
+}
+
+/// Original batch script via `scontrol write batch_script <id> -`
+pub fn fetch_batch_script(job_id: &str) -> Result<String, String> {
+    let output = Command::new("scontrol")
+        .args(["write", "batch_script", job_id, "-"])
+        .output()
+        .map_err(|e| format!("Failed to run scontrol: {}", e))?;
+    if output.status.success() {
+        Ok(String::from_utf8_lossy(&output.stdout).to_string())
+    } else {
+        let err = String::from_utf8_lossy(&output.stderr);
+        Err(format!("scontrol: {}", err.trim()))
+    }
+}
+
+/// Rebuild submission parameters from `scontrol show job` fields.
+/// Output file names get the old job ID replaced with `%j` so the copy doesn't overwrite them.
+pub fn resubmit_spec(job_id: &str, fields: &HashMap<String, String>) -> SubmitSpec {
+    let get = |key: &str| -> String {
+        match fields.get(key).map(String::as_str) {
+            None | Some("(null)") | Some("N/A") => String::new(),
+            Some(v) => v.to_string(),
+        }
+    };
+    let templated = |path: String| template_job_id(&path, job_id);
+
+    let mut extra = Vec::new();
+    let nodes = get("NumNodes");
+    if let Some(min_nodes) = nodes.split('-').next()
+        && !min_nodes.is_empty()
+        && min_nodes != "1"
+    {
+        extra.push(format!("--nodes={}", min_nodes));
+    }
+    let gres = get("TresPerNode");
+    if let Some(g) = gres.strip_prefix("gres/").or(gres.strip_prefix("gres:")) {
+        extra.push(format!("--gres={}", g));
+    }
+    let mem_per_cpu = get("MinMemoryCPU");
+    if !mem_per_cpu.is_empty() {
+        extra.push(format!("--mem-per-cpu={}", mem_per_cpu));
+    }
+    // Only array jobs carry ArrayTaskId; a pending array shows the full spec (e.g. 0-9%2)
+    let array = get("ArrayTaskId");
+    if !array.is_empty() {
+        extra.push(format!("--array={}", array));
+    }
+
+    let mut stdout = get("StdOut");
+    let mut stderr = get("StdErr");
+    let array_job = get("ArrayJobId");
+    if !array_job.is_empty() && !array.is_empty() {
+        let pattern = format!("{}_{}", array_job, array);
+        stdout = stdout.replace(&pattern, "%A_%a");
+        stderr = stderr.replace(&pattern, "%A_%a");
+    }
+    if stderr == stdout {
+        stderr.clear();
+    }
+
+    SubmitSpec {
+        partition: get("Partition"),
+        gpus: gpu_request(&get("TresPerJob")).unwrap_or_default(),
+        cpus: get("CPUs/Task"),
+        mem: get("MinMemoryNode"),
+        time: get("TimeLimit"),
+        name: get("JobName"),
+        output: templated(stdout),
+        error: templated(stderr),
+        workdir: get("WorkDir"),
+        script: String::new(),
+        extra,
+    }
+}
+
+/// Replace the job ID with `%j` where it stands alone in the file name (`out-12.log`, `12.err`),
+/// leaving directories alone: `run12/` isn't the ID, and sbatch doesn't create directories
+fn template_job_id(path: &str, job_id: &str) -> String {
+    let name_start = path.rfind('/').map_or(0, |i| i + 1);
+    let (dir, name) = path.split_at(name_start);
+    let is_delim = |c: Option<char>| c.is_none_or(|c| matches!(c, '-' | '_' | '.'));
+    let mut out = String::from(dir);
+    let mut rest = name;
+    while let Some(i) = rest.find(job_id) {
+        let end = i + job_id.len();
+        let standalone =
+            is_delim(rest[..i].chars().next_back()) && is_delim(rest[end..].chars().next());
+        out.push_str(&rest[..i]);
+        out.push_str(if standalone { "%j" } else { job_id });
+        rest = &rest[end..];
+    }
+    out.push_str(rest);
+    out
+}
+
+/// GPUs of a job-wide TRES request (`gres/gpu:2`, `gres:gpu:a100:2`) as `--gpus` takes them
+fn gpu_request(tres: &str) -> Option<String> {
+    tres.split(',').find_map(|t| {
+        let t = t.strip_prefix("gres/").or(t.strip_prefix("gres:"))?;
+        let gpus = t.strip_prefix("gpu:").or(t.strip_prefix("gpu="))?;
+        (!gpus.is_empty()).then(|| gpus.to_string())
+    })
+}
+
+/// Write a script to a new file only the user can read, under an unguessable name in the
+/// temp dir. `create_new` refuses an existing path, so a file or symlink planted there by
+/// someone else on a shared node is never written through.
+pub fn write_temp_script(prefix: &str, contents: &str) -> Result<PathBuf, String> {
+    let dir = std::env::temp_dir();
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_nanos())
+        .unwrap_or(0);
+    for attempt in 0..16u32 {
+        // RandomState is seeded from the OS, which makes the name hard to predict
+        let suffix = RandomState::new().hash_one((std::process::id(), nanos, attempt));
+        let path = dir.join(format!("{}-{:016x}.sh", prefix, suffix));
+        match OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .mode(0o600)
+            .open(&path)
+        {
+            Ok(mut file) => {
+                file.write_all(contents.as_bytes())
+                    .map_err(|e| format!("Cannot write {}: {}", path.display(), e))?;
+                return Ok(path);
+            }
+            Err(e) if e.kind() == ErrorKind::AlreadyExists => continue,
+            Err(e) => return Err(format!("Cannot create {}: {}", path.display(), e)),
+        }
+    }
+    Err(format!(
+        "Cannot create a temporary script in {}",
+        dir.display()
+    ))
+}
+
+/// Insert the spec as `#SBATCH` lines after the script's own header.
+/// sbatch lets later directives win, so the inserted block overrides the originals,
+/// and it stops reading directives at the first command, so it must go before that.
+pub fn script_with_directives(script: &str, spec: &SubmitSpec, job_id: &str) -> String {
+    let lines: Vec<&str> = script.lines().collect();
+    let header_end = lines
+        .iter()
+        .position(|l| {
+            let t = l.trim();
+            !(t.is_empty() || t.starts_with('#'))
+        })
+        .unwrap_or(lines.len());
+
+    let args = spec.option_args();
+    let mut out: Vec<String> = lines[..header_end].iter().map(|l| l.to_string()).collect();
+    out.push(format!(
+        "# --- ylurm: resubmit of job {} (overrides directives above) ---",
+        job_id
+    ));
+    out.extend(args.iter().map(|a| format!("#SBATCH {}", a)));
+    out.push("# ---".to_string());
+    out.extend(lines[header_end..].iter().map(|l| l.to_string()));
+    out.join("\n") + "\n"
+}
+
+/// Quote an argument for a POSIX shell, leaving plain words as they are
+pub fn shell_quote(arg: &str) -> String {
+    if arg
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || "-_=./:%,@+".contains(c))
+    {
+        arg.to_string()
+    } else {
+        format!("'{}'", arg.replace('\'', r"'\''"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::os::unix::fs::PermissionsExt;
+
+    #[test]
+    fn parse_partitions_default_first() {
+        let parts = parse_partitions("cpu\na100*\nh100\na100*\n");
+        assert_eq!(parts, vec!["a100", "cpu", "h100"]);
+    }
+
+    #[test]
+    fn parse_submitted_job_id_from_sbatch() {
+        assert_eq!(
+            parse_submitted_job_id("Submitted batch job 4242\n"),
+            Some("4242".to_string())
+        );
+        assert_eq!(parse_submitted_job_id("error"), None);
+    }
+
+    #[test]
+    fn command_line_skips_empty_fields_and_quotes() {
+        let spec = SubmitSpec {
+            partition: "a100".into(),
+            gpus: "0".into(),
+            time: "2:00:00".into(),
+            name: "my run".into(),
+            script: "train.sh".into(),
+            ..Default::default()
+        };
+        assert_eq!(
+            spec.command_line(),
+            "sbatch --partition=a100 --time=2:00:00 '--job-name=my run' train.sh"
+        );
+    }
+
+    #[test]
+    fn shell_quote_escapes_single_quotes() {
+        assert_eq!(shell_quote("/logs/out-1.log"), "/logs/out-1.log");
+        assert_eq!(
+            shell_quote("/logs/it's; rm -rf ~"),
+            r"'/logs/it'\''s; rm -rf ~'"
+        );
+    }
+
+    #[test]
+    fn command_line_without_script_has_no_empty_argument() {
+        let spec = SubmitSpec {
+            partition: "a100".into(),
+            ..Default::default()
+        };
+        assert_eq!(spec.command_line(), "sbatch --partition=a100");
+    }
+
+    #[test]
+    fn resubmit_spec_from_scontrol_fields() {
+        let text = "JobId=77 JobName=train Partition=a100 TimeLimit=1-00:00:00 NumNodes=2-2 \
+                    CPUs/Task=8 MinMemoryNode=64G TresPerNode=gres/gpu:4 \
+                    StdOut=/logs/train-77.out StdErr=/logs/train-77.out WorkDir=/home/u/proj";
+        let fields = crate::slurm::parser::parse_scontrol_fields(text);
+        let spec = resubmit_spec("77", &fields);
+        assert_eq!(spec.partition, "a100");
+        assert_eq!(spec.cpus, "8");
+        assert_eq!(spec.mem, "64G");
+        assert_eq!(spec.output, "/logs/train-%j.out");
+        assert_eq!(spec.error, "");
+        assert_eq!(spec.extra, vec!["--nodes=2", "--gres=gpu:4"]);
+    }
+
+    #[test]
+    fn resubmit_templates_only_the_file_name() {
+        let mut fields = HashMap::new();
+        fields.insert(
+            "StdOut".to_string(),
+            "/scratch/run12/12/out-12.log".to_string(),
+        );
+        fields.insert("StdErr".to_string(), "/scratch/run12/12.err".to_string());
+        let spec = resubmit_spec("12", &fields);
+        assert_eq!(spec.output, "/scratch/run12/12/out-%j.log");
+        assert_eq!(spec.error, "/scratch/run12/%j.err");
+        assert_eq!(
+            template_job_id("/logs/run120_12_x12.out", "12"),
+            "/logs/run120_%j_x12.out"
+        );
+    }
+
+    #[test]
+    fn resubmit_reads_job_wide_gpus() {
+        let text = "JobId=5 TresPerJob=gres/gpu:a100:2 TresPerNode=gres/gpu:4";
+        let fields = crate::slurm::parser::parse_scontrol_fields(text);
+        let spec = resubmit_spec("5", &fields);
+        assert_eq!(spec.gpus, "a100:2");
+        assert_eq!(spec.extra, vec!["--gres=gpu:4"]);
+        assert_eq!(gpu_request("cpu=4,gres:gpu:2"), Some("2".to_string()));
+        assert_eq!(gpu_request("gres/shard:1"), None);
+    }
+
+    #[test]
+    fn temp_scripts_are_private_and_unique() {
+        let a = write_temp_script("ylurm-test", "echo a\n").unwrap();
+        let b = write_temp_script("ylurm-test", "echo b\n").unwrap();
+        assert_ne!(a, b);
+        let mode = std::fs::metadata(&a).unwrap().permissions().mode() & 0o777;
+        assert_eq!(mode, 0o600);
+        assert_eq!(std::fs::read_to_string(&b).unwrap(), "echo b\n");
+        let _ = std::fs::remove_file(a);
+        let _ = std::fs::remove_file(b);
+    }
+
+    #[test]
+    fn script_directives_go_after_header() {
+        let script = "#!/bin/bash\n#SBATCH --time=1:00:00\n\npython train.py\n";
+        let spec = SubmitSpec {
+            time: "2:00:00".into(),
+            ..Default::default()
+        };
+        let out = script_with_directives(script, &spec, "9");
+        let lines: Vec<&str> = out.lines().collect();
+        assert_eq!(lines[0], "#!/bin/bash");
+        assert_eq!(lines[1], "#SBATCH --time=1:00:00");
+        assert!(lines[3].starts_with("# --- ylurm: resubmit of job 9"));
+        assert_eq!(lines[4], "#SBATCH --time=2:00:00");
+        assert_eq!(lines[6], "python train.py");
+    }
+
+    #[test]
+    fn validate_rejects_bad_values() {
+        let base = SubmitSpec {
+            script: "run.sh".into(),
+            ..Default::default()
+        };
+        let with = |edit: fn(&mut SubmitSpec)| {
+            let mut spec = base.clone();
+            edit(&mut spec);
+            spec.validate()
+        };
+        assert!(base.validate().is_ok());
+        assert!(with(|s| s.script.clear()).is_err());
+        assert!(with(|s| s.cpus = "four".into()).is_err());
+        assert!(with(|s| s.mem = "16GB".into()).is_err());
+        assert!(with(|s| s.mem = "16G".into()).is_ok());
+        assert!(with(|s| s.time = "soon".into()).is_err());
+    }
+}
@@ -0,0 +1,215 @@
+use std::ops::Range;
+
+use ratatui::{
+    style::{Color, Modifier, Style},
+    text::Span,
+};
+
+/// Convert text containing ANSI SGR sequences (`ESC [ ... m`) into styled spans.
+/// The spans' text is the line without escapes, so byte ranges found by searching
+/// the plain line apply to it directly.
+pub fn parse_sgr(text: &str) -> Vec<Span<'static>> {
+    let mut spans = Vec::new();
+    let mut style = Style::default();
+    let mut rest = text;
+
+    while let Some(esc) = rest.find("\x1b[") {
+        if esc > 0 {
+            spans.push(Span::styled(rest[..esc].to_string(), style));
+        }
+        let seq = &rest[esc + 2..];
+        match seq.find('m') {
+            Some(end) => {
+                style = apply_sgr(style, &seq[..end]);
+                rest = &seq[end + 1..];
+            }
+            None => {
+                rest = "";
+            }
+        }
+    }
+    if !rest.is_empty() {
+        spans.push(Span::styled(rest.to_string(), style));
+    }
+    spans
+}
+
+/// Apply one SGR parameter list (e.g. "1;38;5;208") to `style`
+fn apply_sgr(mut style: Style, params: &str) -> Style {
+    let groups: Vec<&str> = params.split(';').collect();
+    let codes: Vec<u16> = groups.iter().map(|p| p.parse().unwrap_or(0)).collect();
+
+    let mut i = 0;
+    while i < codes.len() {
+        // Colon sub-parameters belong to one code: 38:5:n, 38:2::r:g:b, 4:3 (curly underline)
+        if groups[i].contains(':') {
+            let sub: Vec<u16> = groups[i]
+                .split(':')
+                .map(|p| p.parse().unwrap_or(0))
+                .collect();
+            style = match sub[0] {
+                c @ (38 | 48) => match sub_color(&sub[1..]) {
+                    Some(color) if c == 38 => style.fg(color),
+                    Some(color) => style.bg(color),
+                    None => style,
+                },
+                4 if sub.get(1) == Some(&0) => style.remove_modifier(Modifier::UNDERLINED),
+                4 => style.add_modifier(Modifier::UNDERLINED),
+                _ => style,
+            };
+            i += 1;
+            continue;
+        }
+        match codes[i] {
+            0 => style = Style::default(),
+            1 => style = style.add_modifier(Modifier::BOLD),
+            2 => style = style.add_modifier(Modifier::DIM),
+            3 => style = style.add_modifier(Modifier::ITALIC),
+            4 => style = style.add_modifier(Modifier::UNDERLINED),
+            5 | 6 => style = style.add_modifier(Modifier::SLOW_BLINK),
+            7 => style = style.add_modifier(Modifier::REVERSED),
+            8 => style = style.add_modifier(Modifier::HIDDEN),
+            9 => style = style.add_modifier(Modifier::CROSSED_OUT),
+            21 | 22 => style = style.remove_modifier(Modifier::BOLD | Modifier::DIM),
+            23 => style = style.remove_modifier(Modifier::ITALIC),
+            24 => style = style.remove_modifier(Modifier::UNDERLINED),
+            25 => style = style.remove_modifier(Modifier::SLOW_BLINK),
+            27 => style = style.remove_modifier(Modifier::REVERSED),
+            28 => style = style.remove_modifier(Modifier::HIDDEN),
+            29 => style = style.remove_modifier(Modifier::CROSSED_OUT),
+            c @ 30..=37 => style = style.fg(Color::Indexed((c - 30) as u8)),
+            c @ 90..=97 => style = style.fg(Color::Indexed((c - 90 + 8) as u8)),
+            c @ 40..=47 => style = style.bg(Color::Indexed((c - 40) as u8)),
+            c @ 100..=107 => style = style.bg(Color::Indexed((c - 100 + 8) as u8)),
+            39 => style.fg = None,
+            49 => style.bg = None,
+            c @ (38 | 48) => {
+                // Semicolon form: 38;5;n or 38;2;r;g;b
+                let (color, used) = match codes[i + 1..] {
+                    [5, n, ..] => (Some(indexed_color(n)), 2),
+                    [2, r, g, b, ..] => (Some(rgb(r, g, b)), 4),
+                    _ => (None, codes.len() - i - 1),
+                };
+                if let Some(color) = color {
+                    style = if c == 38 {
+                        style.fg(color)
+                    } else {
+                        style.bg(color)
+                    };
+                }
+                i += used;
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+    style
+}
+
+/// Color from colon sub-parameters after 38/48: `5:n`, `2:r:g:b` or `2:<colorspace>:r:g:b`
+fn sub_color(args: &[u16]) -> Option<Color> {
+    match *args {
+        [5, n] => Some(indexed_color(n)),
+        [2, _, r, g, b, ..] => Some(rgb(r, g, b)),
+        [2, r, g, b] => Some(rgb(r, g, b)),
+        _ => None,
+    }
+}
+
+fn rgb(r: u16, g: u16, b: u16) -> Color {
+    Color::Rgb(r.min(255) as u8, g.min(255) as u8, b.min(255) as u8)
+}
+
+/// 256-color palette entry; the first 16 map onto the terminal's named colors
+fn indexed_color(n: u16) -> Color {
+    Color::Indexed(n.min(255) as u8)
+}
+
+/// Patch `style` onto the parts of `spans` covered by `ranges` (byte ranges into
+/// the spans' concatenated text, sorted and non-overlapping)
+pub fn overlay(
+    spans: Vec<Span<'static>>,
+    ranges: &[Range<usize>],
+    style: Style,
+) -> Vec<Span<'static>> {
+    if ranges.is_empty() {
+        return spans;
+    }
+    let mut out = Vec::new();
+    let mut pos = 0;
+    for span in spans {
+        let text = span.content.into_owned();
+        let span_end = pos + text.len();
+        let mut cut = 0;
+        for r in ranges.iter().filter(|r| r.start < span_end && r.end > pos) {
+            let start = r.start.max(pos) - pos;
+            let end = r.end.min(span_end) - pos;
+            if start > cut {
+                out.push(Span::styled(text[cut..start].to_string(), span.style));
+            }
+            out.push(Span::styled(
+                text[start..end].to_string(),
+                span.style.patch(style),
+            ));
+            cut = end;
+        }
+        if cut < text.len() {
+            out.push(Span::styled(text[cut..].to_string(), span.style));
+        }
+        pos = span_end;
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text(spans: &[Span]) -> String {
+        spans.iter().map(|s| s.content.as_ref()).collect()
+    }
+
+    #[test]
+    fn parse_basic_and_bright_colors() {
+        let spans = parse_sgr("\x1b[1;32mPASSED\x1b[0m tests/a.py \x1b[91mFAIL\x1b[39m");
+        assert_eq!(text(&spans), "PASSED tests/a.py FAIL");
+        assert_eq!(
+            spans[0].style,
+            Style::default()
+                .fg(Color::Indexed(2))
+                .add_modifier(Modifier::BOLD)
+        );
+        assert_eq!(spans[1].style, Style::default());
+        assert_eq!(spans[2].style.fg, Some(Color::Indexed(9)));
+    }
+
+    #[test]
+    fn parse_256_and_truecolor() {
+        let spans = parse_sgr("\x1b[38;5;208mwarn\x1b[48;2;10;20;30;3m bg\x1b[38:2::1:2:3mitu");
+        assert_eq!(spans[0].style.fg, Some(Color::Indexed(208)));
+        assert_eq!(spans[1].style.bg, Some(Color::Rgb(10, 20, 30)));
+        assert!(spans[1].style.add_modifier.contains(Modifier::ITALIC));
+        assert_eq!(spans[2].style.fg, Some(Color::Rgb(1, 2, 3)));
+    }
+
+    #[test]
+    fn empty_params_reset() {
+        let spans = parse_sgr("\x1b[4mu\x1b[mplain");
+        assert_eq!(spans[1].style, Style::default());
+    }
+
+    #[test]
+    fn overlay_splits_spans_at_range_edges() {
+        let spans = parse_sgr("\x1b[31mred\x1b[0m plain");
+        let hl = Style::default().bg(Color::Yellow);
+        let out = overlay(spans, &[2..5, 7..8], hl);
+        assert_eq!(text(&out), "red plain");
+        let parts: Vec<&str> = out.iter().map(|s| s.content.as_ref()).collect();
+        assert_eq!(parts, vec!["re", "d", " p", "la", "i", "n"]);
+        assert_eq!(
+            out[1].style,
+            Style::default().fg(Color::Indexed(1)).bg(Color::Yellow)
+        );
+        assert_eq!(out[2].style, hl);
+    }
+}
@@ -0,0 +1,247 @@
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use ratatui::{
+    Frame,
+    layout::{Constraint, Flex, Layout, Rect},
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, Paragraph, Wrap},
+};
+
+use crate::ui::theme::Theme;
+
+#[derive(Debug, Clone)]
+pub enum FieldKind {
+    /// Free text input
+    Text,
+    /// Cycle through fixed options with ←/→
+    Choice(Vec<String>),
+}
+
+#[derive(Debug, Clone)]
+pub struct FormField {
+    /// Key used by the caller (e.g. the scontrol field name)
+    pub key: String,
+    pub label: String,
+    pub value: String,
+    /// Value the form was opened with, used to report changes
+    pub original: String,
+    pub kind: FieldKind,
+}
+
+impl FormField {
+    pub fn text(key: &str, value: &str) -> Self {
+        Self {
+            key: key.to_string(),
+            label: key.to_string(),
+            value: value.to_string(),
+            original: value.to_string(),
+            kind: FieldKind::Text,
+        }
+    }
+
+    pub fn choice(key: &str, value: &str, options: Vec<String>) -> Self {
+        Self {
+            kind: FieldKind::Choice(options),
+            ..Self::text(key, value)
+        }
+    }
+
+    pub fn with_label(mut self, label: &str) -> Self {
+        self.label = label.to_string();
+        self
+    }
+
+    pub fn is_changed(&self) -> bool {
+        self.value != self.original
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FormOutcome {
+    /// Key consumed, keep editing
+    Continue,
+    Submit,
+    Cancel,
+}
+
+/// A modal list of labelled input fields
+#[derive(Debug, Clone)]
+pub struct Form {
+    pub title: String,
+    pub fields: Vec<FormField>,
+    pub selected: usize,
+    /// Validation or Slurm error shown under the fields
+    pub error: Option<String>,
+    /// Extra read-only lines under the fields (command previews, estimates)
+    pub footer: Vec<String>,
+    /// Key hints shown in the bottom border
+    pub hints: String,
+}
+
+impl Form {
+    pub fn new(title: &str, fields: Vec<FormField>) -> Self {
+        Self {
+            title: title.to_string(),
+            fields,
+            selected: 0,
+            error: None,
+            footer: vec![],
+            hints: "Enter apply  Esc cancel  ↑↓ field".to_string(),
+        }
+    }
+
+    pub fn with_hints(mut self, hints: &str) -> Self {
+        self.hints = hints.to_string();
+        self
+    }
+
+    pub fn value(&self, key: &str) -> &str {
+        self.fields
+            .iter()
+            .find(|f| f.key == key)
+            .map(|f| f.value.as_str())
+            .unwrap_or("")
+    }
+
+    /// (key, value) pairs for fields edited since the form was opened
+    pub fn changed(&self) -> Vec<(String, String)> {
+        self.fields
+            .iter()
+            .filter(|f| f.is_changed())
+            .map(|f| (f.key.clone(), f.value.clone()))
+            .collect()
+    }
+
+    pub fn handle_key(&mut self, key: KeyEvent) -> FormOutcome {
+        let len = self.fields.len();
+        if len == 0 {
+            return match key.code {
+                KeyCode::Enter => FormOutcome::Submit,
+                KeyCode::Esc => FormOutcome::Cancel,
+                _ => FormOutcome::Continue,
+            };
+        }
+        let field = &mut self.fields[self.selected];
+
+        match key.code {
+            KeyCode::Esc => return FormOutcome::Cancel,
+            KeyCode::Enter => return FormOutcome::Submit,
+            KeyCode::Down | KeyCode::Tab => self.selected = (self.selected + 1) % len,
+            KeyCode::Up | KeyCode::BackTab => self.selected = (self.selected + len - 1) % len,
+            KeyCode::Left | KeyCode::Right => {
+                if let FieldKind::Choice(ref options) = field.kind
+                    && !options.is_empty()
+                {
+                    let current = options.iter().position(|o| *o == field.value);
+                    let next = match (current, key.code) {
+                        (None, _) => 0,
+                        (Some(i), KeyCode::Right) => (i + 1) % options.len(),
+                        (Some(i), _) => (i + options.len() - 1) % options.len(),
+                    };
+                    field.value = options[next].clone();
+                }
+            }
+            KeyCode::Backspace => {
+                if matches!(field.kind, FieldKind::Text) {
+                    field.value.pop();
+                }
+            }
+            KeyCode::Char('u') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                if matches!(field.kind, FieldKind::Text) {
+                    field.value.clear();
+                }
+            }
+            KeyCode::Char(c) if !key.modifiers.contains(KeyModifiers::CONTROL) => {
+                if matches!(field.kind, FieldKind::Text) {
+                    field.value.push(c);
+                }
+            }
+            _ => {}
+        }
+        self.error = None;
+        FormOutcome::Continue
+    }
+}
+
+/// Rect of the given size centered in `area`, clamped to fit
+pub fn centered_rect(width: u16, height: u16, area: Rect) -> Rect {
+    let [row] = Layout::vertical([Constraint::Length(height.min(area.height))])
+        .flex(Flex::Center)
+        .areas(area);
+    let [rect] = Layout::horizontal([Constraint::Length(width.min(area.width))])
+        .flex(Flex::Center)
+        .areas(row);
+    rect
+}
+
+pub fn draw_form(f: &mut Frame, form: &Form, theme: &Theme, area: Rect) {
+    let label_width = form
+        .fields
+        .iter()
+        .map(|fl| fl.label.len())
+        .max()
+        .unwrap_or(0)
+        + 2;
+    let key_style = Style::default().fg(theme.key);
+
+    let mut lines: Vec<Line> = form
+        .fields
+        .iter()
+        .enumerate()
+        .map(|(i, field)| {
+            let selected = i == form.selected;
+            let marker = if field.is_changed() { "*" } else { " " };
+            let value = match field.kind {
+                FieldKind::Text if selected => format!("{}▏", field.value),
+                FieldKind::Text => field.value.clone(),
+                FieldKind::Choice(_) => format!("◀ {} ▶", field.value),
+            };
+            let value_style = if selected {
+                Style::default()
+                    .bg(theme.selection)
+                    .add_modifier(Modifier::BOLD)
+            } else {
+                Style::default()
+            };
+            Line::from(vec![
+                Span::styled(marker, Style::default().fg(theme.mark)),
+                Span::styled(
+                    format!("{:<width$}", field.label, width = label_width),
+                    key_style,
+                ),
+                Span::styled(value, value_style),
+            ])
+        })
+        .collect();
+
+    if !form.footer.is_empty() {
+        lines.push(Line::from(""));
+        lines.extend(
+            form.footer
+                .iter()
+                .map(|l| Line::styled(l.clone(), Style::default().fg(theme.accent))),
+        );
+    }
+    if let Some(ref error) = form.error {
+        lines.push(Line::from(""));
+        lines.push(Line::styled(
+            error.clone(),
+            Style::default().fg(theme.error),
+        ));
+    }
+
+    // Leave room for wrapped footer/error lines
+    let height = (lines.len() as u16 + 4).min(area.height);
+    let popup = centered_rect(area.width.saturating_sub(8).min(90), height, area);
+
+    let widget = Paragraph::new(lines).wrap(Wrap { trim: false }).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .title(format!(" {} ", form.title))
+            .title_bottom(format!(" {} ", form.hints))
+            .border_style(Style::default().fg(theme.accent)),
+    );
+
+    f.render_widget(Clear, popup);
+    f.render_widget(widget, popup);
+}
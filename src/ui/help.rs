@@ -0,0 +1,81 @@
+use ratatui::{
+    Frame,
+    layout::Rect,
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, Paragraph},
+};
+
+use crate::action::{Panel, REGISTRY};
+use crate::app::App;
+use crate::ui::form::centered_rect;
+
+/// Width of the key column
+const KEYS_WIDTH: usize = 18;
+
+/// Every binding from the action registry, the focused panel's first
+fn help_lines(app: &App) -> Vec<Line<'static>> {
+    let current = app.key_panel();
+    let mut panels = vec![current, Panel::Global];
+    panels.extend(
+        Panel::ALL
+            .into_iter()
+            .filter(|p| *p != current && *p != Panel::Global),
+    );
+
+    let theme = app.theme();
+    let heading = Style::default()
+        .fg(theme.accent)
+        .add_modifier(Modifier::BOLD);
+    let key = Style::default().fg(theme.key).add_modifier(Modifier::BOLD);
+    let dim = Style::default().fg(theme.dim);
+    let mut lines = Vec::new();
+    for panel in panels {
+        if !lines.is_empty() {
+            lines.push(Line::default());
+        }
+        let title = if panel == current {
+            format!(" {} (focused)", panel.title())
+        } else {
+            format!(" {}", panel.title())
+        };
+        lines.push(Line::styled(title, heading));
+        for e in REGISTRY.iter().filter(|e| e.panel == panel) {
+            let keys = app.keymap.labels(panel, e.action).join(" ");
+            let mut spans = vec![
+                Span::styled(format!("   {:<width$}", keys, width = KEYS_WIDTH), key),
+                Span::raw(e.description),
+            ];
+            spans.push(Span::styled(format!("  ({})", e.action.config_name()), dim));
+            lines.push(Line::from(spans));
+        }
+    }
+    lines
+}
+
+/// Key reference popup; the scroll offset is clamped to the content here
+pub fn draw_help(f: &mut Frame, app: &mut App, area: Rect) {
+    let lines = help_lines(app);
+    let popup = centered_rect(
+        area.width.saturating_sub(4).min(100),
+        area.height.saturating_sub(2),
+        area,
+    );
+    let visible = popup.height.saturating_sub(2) as usize;
+    let max_scroll = lines.len().saturating_sub(visible);
+    let scroll = app.help_scroll.map_or(0, |s| s.min(max_scroll));
+    app.help_scroll = Some(scroll);
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(app.theme().accent))
+        .title(" Keys ")
+        .title_bottom(" j/k scroll  Esc close  names in () are [keybindings] entries ");
+    f.render_widget(Clear, popup);
+    f.render_widget(
+        Paragraph::new(lines)
+            .block(block)
+            .scroll((scroll as u16, 0)),
+        popup,
+    );
+}
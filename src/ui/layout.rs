@@ -1,21 +1,34 @@
 use ratatui::{
     Frame,
-    layout::{Constraint, Direction, Layout},
+    layout::{Constraint, Direction, Layout, Margin, Rect},
     style::{Color, Modifier, Style},
+    symbols,
     text::{Line, Span},
-    widgets::{Block, Borders, Cell, Paragraph, Row, Scrollbar, ScrollbarOrientation, ScrollbarState, Table},
+    widgets::{
+        Axis, Block, Borders, Cell, Chart, Dataset, Gauge, GraphType, Paragraph, Row, Scrollbar,
+        ScrollbarOrientation, ScrollbarState, Sparkline, Table, Tabs, Wrap,
+    },
 };
+use unicode_width::UnicodeWidthStr;
 
-use crate::app::{App, FocusPanel};
-use crate::slurm::JobState;
+use crate::action::{self, Action, Entry, Panel};
+use crate::app::{App, FocusPanel, LogMode, PendingAction, PreviewTab, describe_jobs};
+use crate::command;
+use crate::keymap::sequence_label;
+use crate::ui::ansi::{overlay, parse_sgr};
+use crate::ui::form::draw_form;
+use crate::ui::help::draw_help;
+use crate::ui::menu::draw_context_menu;
+use crate::ui::theme::Theme;
+use crate::ui::wrap::{ellipsize_middle, truncate_line, wrap_line};
+
+/// Shortest details panel worth drawing: its borders and two lines
+const MIN_DETAILS_HEIGHT: u16 = 4;
 
 /// Border style for focused vs unfocused panels
-fn border_style(focused: bool) -> Style {
-    if focused {
-        Style::default().fg(Color::Cyan)
-    } else {
-        Style::default().fg(Color::DarkGray)
-    }
+fn border_style(app: &App, focused: bool) -> Style {
+    let theme = app.theme();
+    Style::default().fg(if focused { theme.accent } else { theme.border })
 }
 
 pub fn draw_ui(f: &mut Frame, app: &mut App) {
@@ -27,63 +40,218 @@ pub fn draw_ui(f: &mut Frame, app: &mut App) {
         .constraints([Constraint::Min(3), Constraint::Length(2)])
         .split(f.area());
 
-    let main_area = main_and_status[0];
+    let mut main_area = main_and_status[0];
     let status_area = main_and_status[1];
 
-    // Main content: jobs left, details+stdout right
-    let h_chunks = Layout::default()
-        .direction(Direction::Horizontal)
-        .constraints([Constraint::Percentage(45), Constraint::Percentage(55)])
-        .split(main_area);
+    // The pinned pane is only shown with the Log tab
+    if app.focus == FocusPanel::Pinned && !app.pinned_visible() {
+        app.focus = FocusPanel::Log;
+    }
 
-    // Store rects for mouse hit testing
-    app.job_list_area = h_chunks[0];
+    // Narrow terminals show one panel at a time under a row of tabs; zoom hides the tabs
+    let compact_width = app.config.layout.compact_width;
+    app.compact = f.area().width < compact_width;
+    if app.compact && !app.zoomed {
+        let [tabs_area, rest] =
+            Layout::vertical([Constraint::Length(1), Constraint::Min(0)]).areas(main_area);
+        draw_panel_tabs(f, app, tabs_area);
+        main_area = rest;
+    }
+    app.main_area = main_area;
 
-    draw_job_list(f, app, h_chunks[0]);
+    // Main content: jobs left, details+stdout right; a zoomed panel takes all of it
+    let (jobs_area, details_area, preview_area) = match app.zoomed_panel() {
+        Some(FocusPanel::Jobs) => (Some(main_area), None, None),
+        Some(FocusPanel::Details) => (None, Some(main_area), None),
+        Some(FocusPanel::Log | FocusPanel::Pinned) => (None, None, Some(main_area)),
+        None => {
+            let layout = &app.config.layout;
+            let show_details = app.panel_shown(FocusPanel::Details);
+            let show_log = app.panel_shown(FocusPanel::Log);
+            // Jobs beside (or above) the rest, unless the rest is hidden
+            let (jobs, rest) = if show_details || show_log {
+                let direction = if layout.is_vertical() {
+                    Direction::Vertical
+                } else {
+                    Direction::Horizontal
+                };
+                let chunks = Layout::default()
+                    .direction(direction)
+                    .constraints([
+                        Constraint::Percentage(app.jobs_percent),
+                        Constraint::Percentage(100 - app.jobs_percent),
+                    ])
+                    .split(main_area);
+                (chunks[0], Some(chunks[1]))
+            } else {
+                (main_area, None)
+            };
+            // Details on top, log preview below (extra detail rows for a failure cause and one
+            // per progress bar). Short terminals keep at least half the height for the log and
+            // drop the details once they'd be too short to read.
+            let (details, preview) = match rest {
+                Some(rest) if show_details && show_log => {
+                    let cause_rows = if app.likely_cause().is_some() { 2 } else { 0 };
+                    let details_height =
+                        (layout.details_height + cause_rows + app.selected_progress().len() as u16)
+                            .min(rest.height / 2);
+                    if details_height < MIN_DETAILS_HEIGHT {
+                        (None, Some(rest))
+                    } else {
+                        let v_chunks = Layout::default()
+                            .direction(Direction::Vertical)
+                            .constraints([Constraint::Length(details_height), Constraint::Min(5)])
+                            .split(rest);
+                        (Some(v_chunks[0]), Some(v_chunks[1]))
+                    }
+                }
+                Some(rest) if show_details => (Some(rest), None),
+                rest => (None, rest),
+            };
+            (Some(jobs), details, preview)
+        }
+    };
 
-    // Right side: details on top, stdout preview below
-    let v_chunks = Layout::default()
-        .direction(Direction::Vertical)
-        .constraints([Constraint::Length(10), Constraint::Min(5)])
-        .split(h_chunks[1]);
+    // Store rects for mouse hit testing; hidden panels get empty ones
+    app.job_list_area = jobs_area.unwrap_or_default();
+    app.details_area = details_area.unwrap_or_default();
+
+    if let Some(area) = jobs_area {
+        draw_job_list(f, app, area);
+    }
+
+    if let Some(area) = details_area {
+        draw_details(f, app, area);
+    }
+    match preview_area {
+        Some(area) => draw_preview(f, app, area),
+        None => {
+            // Hidden by zoom: zero width so clicks miss the log, but keep the height that scroll
+            // positions (and sticky-bottom following) were computed for
+            app.log.area.width = 0;
+            app.with_pinned(|app| app.log.area.width = 0);
+        }
+    }
+    draw_status_bar(f, app, status_area);
 
-    app.log_area = v_chunks[1];
-    app.details_area = v_chunks[0];
+    if let Some(ref modal) = app.modal {
+        draw_form(f, modal.form(), &app.theme(), f.area());
+    }
+    if app.help_scroll.is_some() {
+        draw_help(f, app, f.area());
+    }
+    // On top of everything, as it takes the keys while open
+    draw_context_menu(f, app);
+}
+
+/// Compact mode's panel switcher; Tab moves along it
+fn draw_panel_tabs(f: &mut Frame, app: &App, area: Rect) {
+    let preview = match app.preview_tab {
+        PreviewTab::Log => "Log",
+        PreviewTab::Script => "Script",
+        PreviewTab::Metrics => "Metrics",
+    };
+    let panels = [
+        (FocusPanel::Jobs, "Jobs"),
+        (FocusPanel::Details, "Details"),
+        (FocusPanel::Log, preview),
+        (FocusPanel::Pinned, "Pinned"),
+    ];
+    let shown: Vec<_> = panels
+        .into_iter()
+        .filter(|(p, _)| app.panel_shown(*p))
+        .collect();
+    let selected = shown.iter().position(|(p, _)| *p == app.focus);
+    let theme = app.theme();
+    let tabs = Tabs::new(shown.into_iter().map(|(_, name)| name))
+        .select(selected)
+        .style(Style::default().fg(theme.dim))
+        .highlight_style(
+            Style::default()
+                .fg(theme.accent)
+                .add_modifier(Modifier::BOLD),
+        );
+    f.render_widget(tabs, area);
+}
+
+/// The Log, Script or Metrics tab; the log area holds a second pane when a job is pinned
+fn draw_preview(f: &mut Frame, app: &mut App, area: Rect) {
+    let (log_area, pinned_area) = if app.pinned_visible() {
+        let direction = if app.pinned_stacked {
+            Direction::Vertical
+        } else {
+            Direction::Horizontal
+        };
+        let panes = Layout::default()
+            .direction(direction)
+            .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+            .split(area);
+        (panes[0], Some(panes[1]))
+    } else {
+        (area, None)
+    };
+
+    app.log.area = log_area;
 
     // Clamp log scroll to valid range now that we know the actual viewport size.
-    // ensure_job_details() above may have set log_scroll using a stale log_area
+    // ensure_job_details() above may have set the log scroll using a stale log area
     // (height=0 on first frame), causing scroll to overshoot past all content.
-    let viewport_lines = v_chunks[1].height.saturating_sub(2) as u16;
-    let max_scroll = (app.log_line_count as u16).saturating_sub(viewport_lines);
-    if app.log_scroll > max_scroll {
-        app.log_scroll = max_scroll;
-    }
+    app.clamp_log_scroll();
 
-    draw_details(f, app, v_chunks[0]);
-    draw_stdout_preview(f, app, v_chunks[1]);
-    draw_status_bar(f, app, status_area);
+    match app.preview_tab {
+        PreviewTab::Log => {
+            draw_stdout_preview(f, app, log_area);
+            if let Some(area) = pinned_area {
+                app.with_pinned(|app| {
+                    app.log.area = area;
+                    app.ensure_log_loaded();
+                    app.clamp_log_scroll();
+                    draw_stdout_preview(f, app, area);
+                });
+            }
+        }
+        PreviewTab::Script => draw_script_preview(f, app, area),
+        PreviewTab::Metrics => {
+            app.ensure_metrics_loaded();
+            draw_metrics_preview(f, app, area);
+        }
+    }
 }
 
 fn draw_job_list(f: &mut Frame, app: &mut App, area: ratatui::layout::Rect) {
     let focused = app.focus == FocusPanel::Jobs;
-    let header_cells = ["", "JobID", "Part", "User", "Time", "Name"]
-        .iter()
-        .map(|h| Cell::from(*h).style(Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)));
+    let show_progress = app.config.display.progress_column;
+    let mut headers = vec!["", "", "JobID", "Part", "User", "Time", "Name"];
+    if show_progress {
+        headers.insert(6, "Prog");
+    }
+    let theme = app.theme();
+    let header_cells = headers.iter().map(|h| {
+        Cell::from(*h).style(
+            Style::default()
+                .fg(theme.header)
+                .add_modifier(Modifier::BOLD),
+        )
+    });
     let header = Row::new(header_cells).height(1);
 
+    let visual_range = app.visual_range();
     let rows: Vec<Row> = app
         .jobs
         .iter()
-        .map(|job| {
-            let state_color = match job.state {
-                JobState::Running => Color::Green,
-                JobState::Pending => Color::Yellow,
-                JobState::Failed => Color::Red,
-                JobState::Cancelled => Color::Gray,
-                _ => Color::White,
+        .enumerate()
+        .map(|(idx, job)| {
+            let state_color = theme.state(&job.state);
+
+            // Gutter shows marked jobs
+            let gutter = if app.is_marked(&job.job_id) {
+                "●"
+            } else {
+                " "
             };
 
-            let cells = vec![
+            let mut cells = vec![
+                Cell::from(gutter).style(Style::default().fg(theme.mark)),
                 Cell::from(job.state.as_str()).style(Style::default().fg(state_color)),
                 Cell::from(job.job_id.as_str()),
                 Cell::from(job.partition.as_str()),
@@ -91,78 +259,191 @@ fn draw_job_list(f: &mut Frame, app: &mut App, area: ratatui::layout::Rect) {
                 Cell::from(job.time.as_str()),
                 Cell::from(job.name.as_str()),
             ];
-            Row::new(cells)
+            if show_progress {
+                // Most recently printed bar from the last read of the job's log
+                let percent = app
+                    .progress
+                    .get(&job.job_id)
+                    .and_then(|bars| bars.last())
+                    .map(|bar| format!("{}%", bar.percent))
+                    .unwrap_or_default();
+                cells.insert(
+                    6,
+                    Cell::from(percent).style(Style::default().fg(theme.success)),
+                );
+            }
+            let in_visual = visual_range.is_some_and(|(start, end)| idx >= start && idx <= end);
+            if in_visual {
+                Row::new(cells).style(Style::default().bg(theme.visual))
+            } else {
+                Row::new(cells)
+            }
         })
         .collect();
 
     let job_count = app.jobs.len();
-    let title = format!(" Jobs ({}) ", job_count);
-
-    let table = Table::new(
-        rows,
-        [
-            Constraint::Length(3),
-            Constraint::Length(10),
-            Constraint::Length(8),
-            Constraint::Length(10),
-            Constraint::Length(10),
-            Constraint::Fill(1),
-        ],
-    )
-    .header(header)
-    .block(
-        Block::default()
-            .borders(Borders::ALL)
-            .title(title)
-            .border_style(border_style(focused)),
-    )
-    .row_highlight_style(Style::default().bg(Color::DarkGray).add_modifier(Modifier::BOLD));
+    let mut title = if app.job_filter.is_some() {
+        format!(" Jobs ({}/{}) ", job_count, app.total_jobs)
+    } else {
+        format!(" Jobs ({}) ", job_count)
+    };
+    if let Some(ref filter) = app.job_filter {
+        title.push_str(&format!("· {} ", filter));
+    }
+    if let Some(sort) = app.job_sort {
+        title.push_str(&format!("· by {} ", sort));
+    }
+    if !app.marked.is_empty() {
+        title.push_str(&format!("· {} marked ", app.marked.len()));
+    }
+    if app.visual_anchor.is_some() {
+        title.push_str("· VISUAL ");
+    }
+
+    let mut widths = vec![
+        Constraint::Length(1),
+        Constraint::Length(3),
+        Constraint::Length(10),
+        Constraint::Length(8),
+        Constraint::Length(10),
+        Constraint::Length(10),
+        Constraint::Fill(1),
+    ];
+    if show_progress {
+        widths.insert(6, Constraint::Length(4));
+    }
+
+    let table = Table::new(rows, widths)
+        .header(header)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(title)
+                .border_style(border_style(app, focused)),
+        )
+        .row_highlight_style(
+            Style::default()
+                .bg(theme.selection)
+                .add_modifier(Modifier::BOLD),
+        );
 
     f.render_stateful_widget(table, area, &mut app.table_state);
 }
 
 fn draw_details(f: &mut Frame, app: &App, area: ratatui::layout::Rect) {
+    let theme = app.theme();
     let detail_text = if let Some(job) = app.selected_job() {
-        let state_color = match job.state {
-            JobState::Running => Color::Green,
-            JobState::Pending => Color::Yellow,
-            JobState::Failed => Color::Red,
-            _ => Color::White,
-        };
+        let state_color = theme.state(&job.state);
+
+        // Values longer than the panel keep both ends, so paths still show their file name
+        let value_width = (area.width as usize).saturating_sub(2 + 9);
+        let fit = |s: &str| ellipsize_middle(s, value_width);
+        let path_width = value_width.saturating_sub(3); // room for the copy icon
 
         let state_str = format!("{:?}", job.state);
-        let stderr_str = job.stderr.clone().unwrap_or_default();
-        let stdout_str = job.stdout.clone().unwrap_or_default();
-
-        // Highlight the currently active log source in cyan
-        let stderr_color = if app.show_stderr { Some(Color::Cyan) } else { None };
-        let stdout_color = if !app.show_stderr { Some(Color::Cyan) } else { None };
-
-        vec![
-            detail_line("State    ", &state_str, Some(state_color)),
-            detail_line("Name     ", &job.name, None),
-            detail_line("Command  ", &job.command, None),
-            detail_line("Nodes    ", &job.nodelist, None),
-            detail_line("TRES     ", &job.tres, None),
-            detail_line("WorkDir  ", &job.work_dir, None),
-            detail_line_with_copy("stderr   ", &stderr_str, stderr_color, app.showing_copy_feedback()),
-            detail_line_with_copy("stdout   ", &stdout_str, stdout_color, app.showing_copy_feedback()),
-        ]
+        let stderr_str = ellipsize_middle(job.stderr.as_deref().unwrap_or_default(), path_width);
+        let stdout_str = ellipsize_middle(job.stdout.as_deref().unwrap_or_default(), path_width);
+
+        // Highlight the currently active log source
+        let stderr_color = (app.log.mode != LogMode::Stdout).then_some(theme.accent);
+        let stdout_color = (app.log.mode != LogMode::Stderr).then_some(theme.accent);
+
+        let mut lines = vec![
+            detail_line(&theme, "State    ", &state_str, Some(state_color)),
+            detail_line(&theme, "Name     ", &fit(&job.name), None),
+            detail_line(&theme, "Command  ", &fit(&job.command), None),
+            detail_line(
+                &theme,
+                "Nodes    ",
+                &fit(&format!("{} ({})", job.nodelist, job.nodes)),
+                None,
+            ),
+            detail_line(&theme, "TRES     ", &fit(&job.tres), None),
+            detail_line(&theme, "WorkDir  ", &fit(&job.work_dir), None),
+            detail_line_with_copy(
+                &theme,
+                "stderr   ",
+                &stderr_str,
+                stderr_color,
+                app.showing_copy_feedback(),
+            ),
+            detail_line_with_copy(
+                &theme,
+                "stdout   ",
+                &stdout_str,
+                stdout_color,
+                app.showing_copy_feedback(),
+            ),
+        ];
+        if let Some(cause) = app.likely_cause() {
+            lines.push(detail_line(
+                &theme,
+                "Cause    ",
+                &fit(&cause.cause),
+                Some(theme.error),
+            ));
+            lines.push(Line::styled(
+                format!("          {}", fit(&cause.line)),
+                Style::default().fg(theme.dim),
+            ));
+        }
+        lines
     } else {
         vec![Line::from("No job selected")]
     };
 
-    let details = Paragraph::new(detail_text)
-        .block(Block::default().borders(Borders::ALL).title(" Details "));
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(" Details ")
+        .border_style(border_style(app, app.focus == FocusPanel::Details));
+    let inner = block.inner(area);
+    f.render_widget(block, area);
 
-    f.render_widget(details, area);
+    // Progress bars from the job's log sit under the text, one row each
+    let bars = app.selected_progress();
+    let [text_area, bars_area] =
+        Layout::vertical([Constraint::Min(0), Constraint::Length(bars.len() as u16)]).areas(inner);
+    f.render_widget(Paragraph::new(detail_text), text_area);
+    for (bar, row) in bars.iter().zip(bars_area.rows()) {
+        let gauge = Gauge::default()
+            .gauge_style(Style::default().fg(theme.success).bg(theme.inverse))
+            .percent(bar.percent)
+            .label(bar.summary());
+        f.render_widget(gauge, row);
+    }
 }
 
-fn draw_stdout_preview(f: &mut Frame, app: &App, area: ratatui::layout::Rect) {
-    let focused = app.focus == FocusPanel::Log;
-    let label = if app.show_stderr { "stderr" } else { "stdout" };
-    let path_str = if let Some(job) = app.selected_job() {
-        if app.show_stderr {
+fn draw_stdout_preview(f: &mut Frame, app: &mut App, area: ratatui::layout::Rect) {
+    let theme = app.theme();
+    // Widest visible line, to bound horizontal scrolling when lines aren't wrapped
+    let text_width = app.log_text_width();
+    let widest = match app.log.window {
+        Some(ref window) if !app.wrap_logs => {
+            let end = (app.last_visible_log_line() + 1).min(window.lines.len());
+            let start = app.log.scroll.min(end);
+            window.lines[start..end]
+                .iter()
+                .map(|l| l.width())
+                .max()
+                .unwrap_or(0)
+        }
+        _ => 0,
+    };
+    app.log.hscroll = app.log.hscroll.min(widest.saturating_sub(text_width));
+
+    let search_info = match app.search_status() {
+        Some(status) => format!(" {}", status),
+        None => String::new(),
+    };
+    let error_info = match app.error_lines().len() {
+        0 => String::new(),
+        1 => " ⚠ 1 error".to_string(),
+        n => format!(" ⚠ {} errors", n),
+    };
+    let focused = app.log_pane_focused();
+    let label = app.log.mode.label();
+    let path_str = if let Some(job) = app.log_job() {
+        if app.log.mode == LogMode::Stderr {
             job.stderr.as_deref().unwrap_or("stderr")
         } else {
             job.stdout.as_deref().unwrap_or("stdout")
@@ -170,52 +451,162 @@ fn draw_stdout_preview(f: &mut Frame, app: &App, area: ratatui::layout::Rect) {
     } else {
         label
     };
+    let note = app
+        .log_mode_note()
+        .map(|n| format!(" ({})", n))
+        .unwrap_or_default();
 
-    // Line range indicator: [L1-30/500] showing visible range
+    // Line range indicator: [L1-30/500] showing visible range. Line numbers are
+    // absolute once the window reaches the file start, otherwise relative to the
+    // loaded window with the file position as a percentage.
     let viewport_lines = area.height.saturating_sub(2) as usize;
-    let scroll_info = if app.log_line_count > 0 {
-        let first_visible = app.log_scroll as usize + 1;
-        let last_visible = (app.log_scroll as usize + viewport_lines).min(app.log_line_count);
-        format!(" [L{}-{}/{}]", first_visible, last_visible, app.log_line_count)
+    let line_count = app.log_line_count();
+    let scroll_info = match app.log.window {
+        Some(ref window) if line_count > 0 => {
+            let first_visible = app.log.scroll + 1;
+            let last_visible = (app.last_visible_log_line() + 1).min(line_count);
+            match window.first_line {
+                Some(base) if window.at_end() => format!(
+                    " [L{}-{}/{}]",
+                    base + first_visible - 1,
+                    base + last_visible - 1,
+                    base + line_count - 1
+                ),
+                Some(base) => format!(
+                    " [L{}-{}]",
+                    base + first_visible - 1,
+                    base + last_visible - 1
+                ),
+                None => format!(
+                    " [L{}-{}/{} {:.0}%]",
+                    first_visible,
+                    last_visible,
+                    line_count,
+                    window.fraction_at(last_visible - 1) * 100.0
+                ),
+            }
+        }
+        _ => String::new(),
+    };
+    let pin = match app.log_job() {
+        Some(job) if app.is_pinned_pane() => format!("pinned {} · ", job.job_id),
+        _ => String::new(),
+    };
+    let sync = if app.sync_scroll && app.pinned.is_some() {
+        " ⇅"
     } else {
-        String::new()
+        ""
+    };
+    // The path gives way first when the title doesn't fit
+    let title_width = |path: &str| {
+        format!(
+            " {}{}: {}{}{}{}{}{} ",
+            pin, label, path, note, scroll_info, search_info, error_info, sync
+        )
+        .width()
     };
-    let title = format!(" {}: {}{} ", label, path_str, scroll_info);
+    let path_room = (area.width as usize).saturating_sub(2 + title_width(""));
+    let path_str = ellipsize_middle(path_str, path_room);
+    let title = format!(
+        " {}{}: {}{}{}{}{}{} ",
+        pin, label, path_str, note, scroll_info, search_info, error_info, sync
+    );
+    let current_match = app.current_match_index();
+    let current_error = app.current_error_index();
+    let ansi_colors = app.config.display.ansi_colors;
+    let sources = app.merged_sources();
 
-    let (content, style) = if let Some(ref error) = app.log_error {
+    // Only the visible slice is rendered, so window size doesn't matter for drawing
+    let (content, style): (Vec<Line>, Style) = if let Some(ref error) = app.log.error {
         (
-            format!("Read error: {}", error),
-            Style::default().fg(Color::Red),
+            vec![Line::from(format!("Read error: {}", error))],
+            Style::default().fg(theme.error),
         )
-    } else if let Some(ref log) = app.log_preview {
-        if log.is_empty() {
-            ("(empty file)".to_string(), Style::default().fg(Color::Yellow))
+    } else if let Some(ref window) = app.log.window {
+        if window.lines.is_empty() {
+            (
+                vec![Line::from("(empty file)")],
+                Style::default().fg(theme.warning),
+            )
         } else {
+            let end = (app.last_visible_log_line() + 1).min(window.lines.len());
+            let start = app.log.scroll.min(end);
+            let styled = window.lines[start..end].iter().enumerate().map(|(i, l)| {
+                let colored = window.ansi[start + i].as_deref().filter(|_| ansi_colors);
+                let line = log_line(
+                    &theme,
+                    l,
+                    colored,
+                    app.highlight_regex(),
+                    current_match == Some(start + i),
+                );
+                // Mark lines matching an error signature; the one last jumped to stands out
+                if current_error == Some(start + i) {
+                    line.style(
+                        Style::default()
+                            .fg(theme.text)
+                            .bg(theme.error)
+                            .add_modifier(Modifier::BOLD),
+                    )
+                } else if app.analyzer.is_error(l) {
+                    line.style(
+                        Style::default()
+                            .fg(theme.log_error)
+                            .add_modifier(Modifier::BOLD),
+                    )
+                } else if sources.is_some_and(|s| s.get(start + i) == Some(&true)) {
+                    // stderr lines in the merged view
+                    line.style(Style::default().fg(theme.stderr))
+                } else {
+                    line
+                }
+            });
+            // Wrapped lines are split into screen rows here, so the top line can start mid-way
+            let visible = if app.wrap_logs {
+                styled
+                    .flat_map(|line| wrap_line(line, text_width))
+                    .skip(app.log_top_row())
+                    .take(viewport_lines)
+                    .collect()
+            } else {
+                styled.collect()
+            };
             // Keep default foreground so logs are readable on both light and dark themes.
-            (log.clone(), Style::default())
+            (visible, Style::default())
         }
     } else {
-        ("Loading...".to_string(), Style::default().fg(Color::DarkGray))
+        (
+            vec![Line::from("Loading...")],
+            Style::default().fg(theme.dim),
+        )
     };
 
     let log_widget = Paragraph::new(content)
         .style(style)
+        .scroll((0, app.log.hscroll as u16))
         .block(
             Block::default()
                 .borders(Borders::ALL)
                 .title(title)
-                .border_style(border_style(focused)),
-        )
-        .scroll((app.log_scroll, 0));
+                .border_style(border_style(app, focused)),
+        );
 
     f.render_widget(log_widget, area);
 
-    // Scrollbar on the right edge of the log panel
-    if app.log_line_count > viewport_lines {
-        let max_scroll = app.log_line_count.saturating_sub(viewport_lines);
-        let mut scrollbar_state = ScrollbarState::new(max_scroll)
-            .position(app.log_scroll as usize);
-
+    // Scrollbar on the right edge of the log panel. When only part of the file is
+    // loaded it tracks the byte position in the whole file instead of the window.
+    let scrollbar_state = match app.log.window {
+        Some(ref window) if !window.is_complete() => {
+            let permille = (window.fraction_at(app.log.scroll) * 1000.0) as usize;
+            Some(ScrollbarState::new(1000).position(permille))
+        }
+        _ if line_count > viewport_lines => {
+            let max_scroll = line_count.saturating_sub(viewport_lines);
+            Some(ScrollbarState::new(max_scroll).position(app.log.scroll))
+        }
+        _ => None,
+    };
+    if let Some(mut scrollbar_state) = scrollbar_state {
         let scrollbar = Scrollbar::new(ScrollbarOrientation::VerticalRight)
             .begin_symbol(None)
             .end_symbol(None)
@@ -225,96 +616,624 @@ fn draw_stdout_preview(f: &mut Frame, app: &App, area: ratatui::layout::Rect) {
         // Render scrollbar inside the block's border area
         f.render_stateful_widget(scrollbar, area, &mut scrollbar_state);
     }
+
+    // Horizontal scrollbar on the bottom border when unwrapped lines run past the edge
+    if widest > text_width {
+        let mut hscroll_state = ScrollbarState::new(widest - text_width).position(app.log.hscroll);
+        let hscrollbar = Scrollbar::new(ScrollbarOrientation::HorizontalBottom)
+            .begin_symbol(None)
+            .end_symbol(None)
+            .track_symbol(Some("─"))
+            .thumb_symbol("■");
+        f.render_stateful_widget(
+            hscrollbar,
+            area.inner(Margin {
+                horizontal: 1,
+                vertical: 0,
+            }),
+            &mut hscroll_state,
+        );
+    }
+}
+
+fn draw_script_preview(f: &mut Frame, app: &App, area: ratatui::layout::Rect) {
+    let focused = app.focus == FocusPanel::Log;
+    let theme = app.theme();
+    let (content, style, job_id) = match app.script {
+        Some((ref id, Ok(ref text))) => (text.clone(), Style::default(), id.as_str()),
+        Some((ref id, Err(ref e))) => (
+            format!("Read error: {}", e),
+            Style::default().fg(theme.error),
+            id.as_str(),
+        ),
+        None => ("Loading...".to_string(), Style::default().fg(theme.dim), ""),
+    };
+    let title = format!(
+        " script: job {} ({} log) ",
+        job_id,
+        first_key(app, Panel::Script, Action::NextTab)
+    );
+
+    let widget = Paragraph::new(content)
+        .style(style)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(title)
+                .border_style(border_style(app, focused)),
+        )
+        .scroll((app.script_scroll, 0));
+    f.render_widget(widget, area);
+}
+
+/// Chart of the selected metric above one sparkline row per metric
+fn draw_metrics_preview(f: &mut Frame, app: &App, area: ratatui::layout::Rect) {
+    let theme = app.theme();
+    let focused = app.focus == FocusPanel::Log;
+    let label = app.log.mode.label();
+    let job_id = app.selected_job().map(|j| j.job_id.as_str()).unwrap_or("");
+    let scan = app.metrics.as_ref().map(|(_, m)| m);
+    let series = scan.map(|m| m.series.as_slice()).unwrap_or(&[]);
+    let truncated = if scan.is_some_and(|m| m.truncated) {
+        " · last 64 MB"
+    } else {
+        ""
+    };
+    let title = format!(
+        " metrics: job {} {}{} ({} log) ",
+        job_id,
+        label,
+        truncated,
+        first_key(app, Panel::Metrics, Action::NextTab)
+    );
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(title)
+        .border_style(border_style(app, focused));
+    let inner = block.inner(area);
+    f.render_widget(block, area);
+
+    if series.is_empty() {
+        let text = vec![
+            Line::styled(
+                "No metrics found in this log.",
+                Style::default().fg(theme.dim),
+            ),
+            Line::from(""),
+            Line::from(
+                "Recognized: key=value pairs, Lightning progress postfixes, HF Trainer dicts.",
+            ),
+            Line::from("Add regexes with named captures under [metrics] patterns in the config."),
+        ];
+        f.render_widget(Paragraph::new(text).wrap(Wrap { trim: false }), inner);
+        return;
+    }
+
+    let selected = app.metrics_selected.min(series.len() - 1);
+    let list_height = (series.len() as u16).min(inner.height / 2);
+    let [chart_area, list_area] =
+        Layout::vertical([Constraint::Min(3), Constraint::Length(list_height)]).areas(inner);
+
+    // Chart of the selected metric, thinned to about two points per braille column
+    let metric = &series[selected];
+    let stride = (metric.points.len() / (chart_area.width as usize * 2).max(1)).max(1);
+    let mut points: Vec<(f64, f64)> = metric.points.iter().step_by(stride).copied().collect();
+    if let Some(&last) = metric.points.last()
+        && points.last() != Some(&last)
+    {
+        points.push(last);
+    }
+    let (lo, hi) = metric.bounds().unwrap_or((0.0, 1.0));
+    let pad = if hi > lo {
+        (hi - lo) * 0.05
+    } else {
+        lo.abs().max(1.0) * 0.05
+    };
+    let (x0, x1) = match (points.first(), points.last()) {
+        (Some(a), Some(b)) if b.0 > a.0 => (a.0, b.0),
+        (Some(a), _) => (a.0, a.0 + 1.0),
+        _ => (0.0, 1.0),
+    };
+    let mut chart_title = vec![Span::styled(
+        format!(
+            " {} ({}/{})  last {} ",
+            metric.name,
+            selected + 1,
+            series.len(),
+            fmt_metric(metric.last())
+        ),
+        Style::default()
+            .fg(theme.accent)
+            .add_modifier(Modifier::BOLD),
+    )];
+    if let Some(step) = metric.first_nan {
+        chart_title.push(Span::styled(
+            format!(" NaN/inf at step {} ", fmt_metric(Some(step))),
+            Style::default()
+                .fg(theme.text)
+                .bg(theme.error)
+                .add_modifier(Modifier::BOLD),
+        ));
+    }
+    let dataset = Dataset::default()
+        .marker(symbols::Marker::Braille)
+        .graph_type(GraphType::Line)
+        .style(Style::default().fg(theme.accent))
+        .data(&points);
+    let chart = Chart::new(vec![dataset])
+        .block(Block::default().title(Line::from(chart_title)))
+        .x_axis(
+            Axis::default()
+                .style(Style::default().fg(theme.dim))
+                .bounds([x0, x1])
+                .labels([fmt_metric(Some(x0)), fmt_metric(Some(x1))]),
+        )
+        .y_axis(
+            Axis::default()
+                .style(Style::default().fg(theme.dim))
+                .bounds([lo - pad, hi + pad])
+                .labels([fmt_metric(Some(lo)), fmt_metric(Some(hi))]),
+        );
+    f.render_widget(chart, chart_area);
+
+    // One row per metric: name, last value and a sparkline of its recent values
+    let first_row = selected.saturating_sub(list_height.saturating_sub(1) as usize);
+    for (offset, row) in list_area.rows().enumerate() {
+        let idx = first_row + offset;
+        let metric = match series.get(idx) {
+            Some(m) => m,
+            None => break,
+        };
+        let [name_area, spark_area] =
+            Layout::horizontal([Constraint::Length(32), Constraint::Fill(1)]).areas(row);
+        let style = if idx == selected {
+            Style::default().fg(theme.inverse).bg(theme.accent)
+        } else {
+            Style::default()
+        };
+        let nan = if metric.first_nan.is_some() {
+            " NaN"
+        } else {
+            ""
+        };
+        let name = format!(
+            "{:<18} {:>9}{}",
+            metric.name,
+            fmt_metric(metric.last()),
+            nan
+        );
+        f.render_widget(Paragraph::new(name).style(style), name_area);
+
+        // Sparkline needs non-negative integers: scale the visible tail to 0..100
+        let tail = &metric.points[metric
+            .points
+            .len()
+            .saturating_sub(spark_area.width as usize)..];
+        let (lo, hi) = tail
+            .iter()
+            .fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), p| {
+                (lo.min(p.1), hi.max(p.1))
+            });
+        let span = if hi > lo { hi - lo } else { 1.0 };
+        let data: Vec<u64> = tail
+            .iter()
+            .map(|p| ((p.1 - lo) / span * 100.0) as u64 + 1)
+            .collect();
+        let sparkline = Sparkline::default()
+            .data(&data)
+            .max(101)
+            .style(Style::default().fg(if metric.first_nan.is_some() {
+                theme.error
+            } else {
+                theme.success
+            }));
+        f.render_widget(sparkline, spark_area);
+    }
+}
+
+/// Compact number formatting for axis labels and values
+fn fmt_metric(value: Option<f64>) -> String {
+    match value {
+        None => "-".to_string(),
+        Some(v) if v == v.trunc() && v.abs() < 1e9 => format!("{}", v as i64),
+        Some(v) if v.abs() >= 1e5 || v.abs() < 1e-3 => format!("{:.3e}", v),
+        Some(v) => format!("{:.4}", v),
+    }
+}
+
+/// Build a log line: ANSI colors when enabled, with every regex match highlighted
+fn log_line(
+    theme: &Theme,
+    plain: &str,
+    colored: Option<&str>,
+    re: Option<&regex::Regex>,
+    is_current: bool,
+) -> Line<'static> {
+    let spans = match colored {
+        Some(text) => parse_sgr(text),
+        None => vec![Span::raw(plain.to_string())],
+    };
+    let re = match re {
+        Some(re) => re,
+        None => return Line::from(spans),
+    };
+    let match_style = if is_current {
+        Style::default()
+            .fg(theme.inverse)
+            .bg(theme.search_current)
+            .add_modifier(Modifier::BOLD)
+    } else {
+        Style::default().fg(theme.inverse).bg(theme.search)
+    };
+    let ranges: Vec<_> = re
+        .find_iter(plain)
+        .filter(|m| !m.is_empty())
+        .map(|m| m.range())
+        .collect();
+    Line::from(overlay(spans, &ranges, match_style))
 }
 
 fn draw_status_bar(f: &mut Frame, app: &App, area: ratatui::layout::Rect) {
-    // Show copy feedback if active
-    if app.showing_copy_feedback() {
+    let theme = app.theme();
+    let key = Style::default().fg(theme.key).add_modifier(Modifier::BOLD);
+    // Confirmation prompts take over the status bar until answered
+    if let Some(ref action) = app.pending_confirm {
+        let (question, detail) = match action {
+            PendingAction::Cancel(ids) => {
+                (format!("Cancel {}?", describe_jobs(ids)), ids.join(" "))
+            }
+            PendingAction::Resubmit {
+                job_id,
+                script_path,
+                ..
+            } => (
+                format!("Submit edited copy of job {}?", job_id),
+                script_path.clone(),
+            ),
+        };
         let lines = vec![
             Line::from(vec![
-                Span::styled(" ✓ Copied to clipboard!", Style::default().fg(Color::Green).add_modifier(Modifier::BOLD)),
+                Span::styled(
+                    format!(" {} ", question),
+                    Style::default()
+                        .fg(theme.error)
+                        .add_modifier(Modifier::BOLD),
+                ),
+                Span::styled("y", key),
+                Span::raw(" confirm  any other key aborts"),
             ]),
+            Line::from(format!(" {}", detail)),
+        ];
+        let status = Paragraph::new(lines).style(Style::default().bg(theme.status_bg));
+        f.render_widget(status, area);
+        return;
+    }
+
+    // The command line shows completions, or the usage of the command being typed
+    if let Some(ref input) = app.command_input {
+        let first = Line::from(vec![
+            Span::styled(" :", key),
+            Span::raw(format!("{}▏", input.text)),
+        ]);
+        let second = if input.completions.len() > 1 {
+            let mut spans = vec![Span::raw(" ")];
+            for (i, completion) in input.completions.iter().enumerate() {
+                let word = completion
+                    .rsplit(' ')
+                    .find(|w| !w.is_empty())
+                    .unwrap_or(completion);
+                let style = if input.completion == Some(i) {
+                    key.add_modifier(Modifier::REVERSED)
+                } else {
+                    Style::default()
+                };
+                spans.push(Span::styled(word.to_string(), style));
+                spans.push(Span::raw(" "));
+            }
+            Line::from(spans)
+        } else {
+            let name = input.text.split_whitespace().next().unwrap_or_default();
+            let usage = command::BUILTINS
+                .iter()
+                .find(|(n, _)| *n == name)
+                .map(|(_, usage)| *usage);
+            Line::from(format!(
+                " {}",
+                usage.unwrap_or("Tab complete  ↑↓ history  Enter run  Esc cancel")
+            ))
+        };
+        let lines = vec![
+            truncate_line(first, area.width as usize),
+            truncate_line(second, area.width as usize),
+        ];
+        let status = Paragraph::new(lines).style(Style::default().bg(theme.status_bg));
+        f.render_widget(status, area);
+        return;
+    }
+
+    // The search prompt replaces the hints while typing, like vim's command line
+    if let Some(ref input) = app.search_input {
+        let prefix = if input.backward { "?" } else { "/" };
+        let mut first = vec![
+            Span::styled(format!(" {}", prefix), key),
+            Span::raw(format!("{}▏", input.query)),
+        ];
+        if let Err(ref e) = input.regex
+            && !e.is_empty()
+        {
+            first.push(Span::styled(
+                format!("  {}", e),
+                Style::default().fg(theme.error),
+            ));
+        }
+        let lines = vec![
+            Line::from(first),
+            Line::from(" Enter search  Esc cancel  (regex; (?i) for case-insensitive)"),
+        ];
+        let status = Paragraph::new(lines).style(Style::default().bg(theme.status_bg));
+        f.render_widget(status, area);
+        return;
+    }
+
+    if let Some(flash) = app.active_flash() {
+        let color = if flash.is_error {
+            theme.error
+        } else {
+            theme.success
+        };
+        let lines = vec![
+            Line::from(vec![Span::styled(
+                format!(" {}", flash.text),
+                Style::default().fg(color).add_modifier(Modifier::BOLD),
+            )]),
             Line::from(""),
         ];
-        let status = Paragraph::new(lines).style(Style::default().bg(Color::DarkGray));
+        let status = Paragraph::new(lines).style(Style::default().bg(theme.status_bg));
         f.render_widget(status, area);
         return;
     }
 
-    let key = Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD);
-    let sep = Span::raw("  ");
+    // Show copy feedback if active
+    if app.showing_copy_feedback() {
+        let lines = vec![
+            Line::from(vec![Span::styled(
+                " ✓ Copied to clipboard!",
+                Style::default()
+                    .fg(theme.success)
+                    .add_modifier(Modifier::BOLD),
+            )]),
+            Line::from(""),
+        ];
+        let status = Paragraph::new(lines).style(Style::default().bg(theme.status_bg));
+        f.render_widget(status, area);
+        return;
+    }
 
-    let lines = match app.focus {
-        FocusPanel::Jobs => {
-            let toggle_label = if app.show_stderr { "stdout" } else { "stderr" };
-            vec![
-                Line::from(vec![
-                    Span::styled(" q", key), Span::raw(" quit"), sep.clone(),
-                    Span::styled("j/k", key), Span::raw(" navigate"), sep.clone(),
-                    Span::styled("g/G", key), Span::raw(" top/bottom"), sep.clone(),
-                    Span::styled(&app.config.keybindings.toggle_logs, key),
-                    Span::raw(format!(" toggle {}", toggle_label)), sep.clone(),
-                    Span::styled(&app.config.keybindings.refresh, key), Span::raw(" refresh"),
-                ]),
-                Line::from(vec![
-                    Span::styled(" Tab", key), Span::raw("/"),
-                    Span::styled("Enter", key), Span::raw(" focus log"), sep.clone(),
-                    Span::styled("^d/^u", key), Span::raw(" scroll log"), sep.clone(),
-                    Span::raw("mouse: click panel or scroll wheel"),
-                ]),
-            ]
+    let pane = match app.focus {
+        FocusPanel::Jobs => None,
+        FocusPanel::Details => Some(" DETAILS FOCUS"),
+        FocusPanel::Log => Some(" LOG FOCUS"),
+        FocusPanel::Pinned => Some(" PINNED LOG"),
+    };
+    let mut first = Vec::new();
+    if let Some(pane) = pane {
+        first.push(Span::styled(
+            pane,
+            Style::default()
+                .fg(theme.accent)
+                .add_modifier(Modifier::BOLD),
+        ));
+    }
+    // Half-typed input, like vim's showcmd: a count, the start of a key sequence, a mark key
+    let mut typed: String = app.count.map(|n| n.to_string()).unwrap_or_default();
+    typed.push_str(&sequence_label(&app.pending_keys));
+    if let Some(mark) = app.awaiting_mark {
+        typed.push_str(&first_key(app, Panel::Log, mark));
+    }
+    if !typed.is_empty() {
+        first.push(Span::styled(format!(" {}", typed), key));
+    }
+    let lines = fill_lines(
+        first,
+        status_hints(app),
+        area.width as usize,
+        area.height as usize,
+    );
+    let lines: Vec<Line> = lines
+        .into_iter()
+        .map(|l| truncate_line(l, area.width as usize))
+        .collect();
+
+    let status = Paragraph::new(lines).style(Style::default().bg(theme.status_bg));
+
+    f.render_widget(status, area);
+}
+
+/// "key label" hints for the focused panel, built from the action registry so they always
+/// show the configured keys. Neighbouring entries with the same label share one hint.
+fn status_hints(app: &App) -> Vec<Vec<Span<'static>>> {
+    let key = Style::default()
+        .fg(app.theme().key)
+        .add_modifier(Modifier::BOLD);
+    let panel = app.key_panel();
+    let mut entries: Vec<&Entry> = action::entries_for(panel)
+        .filter(|e| e.hint.is_some() && !app.keymap.labels(panel, e.action).is_empty())
+        .filter(|e| {
+            app.pinned.is_some() || !matches!(e.action, Action::PinSplit | Action::SyncScroll)
+        })
+        .collect();
+    // Help comes first so it survives truncation on small terminals
+    entries.sort_by_key(|e| e.action != Action::Help);
+
+    let mut hints: Vec<(String, Vec<String>)> = Vec::new();
+    for e in entries {
+        let label = match e.action {
+            Action::ToggleLogs => format!("toggle {}", app.focused_log_mode().next().label()),
+            Action::Zoom if app.zoomed => "unzoom".to_string(),
+            Action::ToggleWrap if app.wrap_logs => "no wrap".to_string(),
+            Action::PinLog if app.pinned.is_some() => "pin/unpin".to_string(),
+            _ => e.hint.unwrap_or_default().to_string(),
+        };
+        let key_label = first_key(app, panel, e.action);
+        match hints.last_mut() {
+            Some((last, keys)) if *last == label => keys.push(key_label),
+            _ => hints.push((label, vec![key_label])),
         }
-        FocusPanel::Log => {
-            let toggle_label = if app.show_stderr { "stdout" } else { "stderr" };
+    }
+    hints
+        .into_iter()
+        .map(|(label, keys)| {
             vec![
-                Line::from(vec![
-                    Span::styled(" LOG FOCUS", Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD)),
-                    sep.clone(),
-                    Span::styled("j/k", key), Span::raw("/"),
-                    Span::styled("↑↓", key), Span::raw(" scroll"), sep.clone(),
-                    Span::styled("g/G", key), Span::raw(" top/bottom"), sep.clone(),
-                    Span::styled("PgUp/PgDn", key), Span::raw(" page"), sep.clone(),
-                    Span::styled("^d/^u", key), Span::raw(" half-page"),
-                ]),
-                Line::from(vec![
-                    Span::styled(" Esc", key), Span::raw("/"),
-                    Span::styled("Tab", key), Span::raw(" back to jobs"), sep.clone(),
-                    Span::styled(&app.config.keybindings.toggle_logs, key),
-                    Span::raw(format!(" toggle {}", toggle_label)), sep.clone(),
-                    Span::styled("q", key), Span::raw(" quit"),
-                ]),
+                Span::styled(keys.join("/"), key),
+                Span::raw(format!(" {}", label)),
             ]
-        }
-    };
+        })
+        .collect()
+}
 
-    let status = Paragraph::new(lines)
-        .style(Style::default().bg(Color::DarkGray));
+/// Short label of the first key bound to `action`, with "C-" shown as "^"
+fn first_key(app: &App, panel: Panel, action: Action) -> String {
+    app.keymap
+        .labels(panel, action)
+        .into_iter()
+        .next()
+        .unwrap_or_default()
+        .replace("C-", "^")
+}
 
-    f.render_widget(status, area);
+/// Lay hints out over `rows` lines, starting after `first`; whatever doesn't fit ends up
+/// on the last line to be cut off with an ellipsis
+fn fill_lines(
+    first: Vec<Span<'static>>,
+    hints: Vec<Vec<Span<'static>>>,
+    width: usize,
+    rows: usize,
+) -> Vec<Line<'static>> {
+    let mut lines = vec![Line::from(first)];
+    for hint in hints {
+        let hint_width: usize = hint.iter().map(|s| s.width()).sum::<usize>() + 2;
+        let line = lines.last_mut().expect("at least one line");
+        if line.width() + hint_width > width && line.width() > 0 && lines.len() < rows {
+            lines.push(Line::default());
+        }
+        let line = lines.last_mut().expect("at least one line");
+        line.spans
+            .push(Span::raw(if line.spans.is_empty() { " " } else { "  " }));
+        line.spans.extend(hint);
+    }
+    lines
 }
 
-fn detail_line(label: &str, value: &str, value_color: Option<Color>) -> Line<'static> {
+fn detail_line(
+    theme: &Theme,
+    label: &str,
+    value: &str,
+    value_color: Option<Color>,
+) -> Line<'static> {
     let val_style = match value_color {
         Some(c) => Style::default().fg(c),
         None => Style::default(),
     };
     Line::from(vec![
-        Span::styled(label.to_string(), Style::default().fg(Color::Yellow)),
+        Span::styled(label.to_string(), Style::default().fg(theme.key)),
         Span::styled(value.to_string(), val_style),
     ])
 }
 
-fn detail_line_with_copy(label: &str, value: &str, value_color: Option<Color>, _feedback: bool) -> Line<'static> {
+fn detail_line_with_copy(
+    theme: &Theme,
+    label: &str,
+    value: &str,
+    value_color: Option<Color>,
+    _feedback: bool,
+) -> Line<'static> {
     let val_style = match value_color {
         Some(c) => Style::default().fg(c),
         None => Style::default(),
     };
     if value.is_empty() {
-        return detail_line(label, value, value_color);
+        return detail_line(theme, label, value, value_color);
     }
     Line::from(vec![
-        Span::styled(label.to_string(), Style::default().fg(Color::Yellow)),
+        Span::styled(label.to_string(), Style::default().fg(theme.key)),
         Span::styled(value.to_string(), val_style),
-        Span::styled(" 📋", Style::default().fg(Color::DarkGray)),
+        Span::styled(" 📋", Style::default().fg(theme.dim)),
     ])
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+    use crate::slurm::Job;
+    use ratatui::{Terminal, backend::TestBackend};
+
+    fn app_with_job(config: Config) -> App {
+        let mut app = App::without_jobs(config, None);
+        let log = "/nonexistent/ylurm-test.out".to_string();
+        app.jobs = vec![Job {
+            tres: "cpu=1".to_string(),
+            stderr: Some(log.clone()),
+            stdout: Some(log),
+            ..Job::test("1")
+        }];
+        app
+    }
+
+    fn draw(app: &mut App, width: u16, height: u16) -> Terminal<TestBackend> {
+        let mut terminal = Terminal::new(TestBackend::new(width, height)).unwrap();
+        terminal.draw(|f| draw_ui(f, app)).unwrap();
+        terminal
+    }
+
+    #[test]
+    fn small_terminals_get_a_usable_layout() {
+        // 80x24 shows one panel at a time under a row of tabs
+        let mut app = app_with_job(Config::default());
+        draw(&mut app, 80, 24);
+        assert!(app.compact);
+        assert_eq!(app.job_list_area.height, 21);
+
+        // Wide but short: the details panel gives the log at least half the height
+        draw(&mut app, 120, 24);
+        assert!(!app.compact);
+        assert_eq!(app.details_area.height, 10);
+        assert!(app.log.area.height >= 10);
+        let mut tall_details = Config::default();
+        tall_details.layout.details_height = 20;
+        let mut app = app_with_job(tall_details);
+        draw(&mut app, 120, 24);
+        assert_eq!(app.details_area.height, 11);
+
+        // Too short for a readable details panel: it's dropped for the log
+        draw(&mut app, 120, 8);
+        assert_eq!(app.details_area, Rect::default());
+        assert!(app.log.area.height > 0);
+    }
+
+    #[test]
+    fn widgets_take_their_colors_from_the_theme() {
+        let mut config = Config::default();
+        config.display.theme = "solarized".to_string();
+        let theme = Theme::named("solarized").unwrap();
+        let mut app = app_with_job(config);
+        app.pending_confirm = Some(PendingAction::Cancel(vec!["1".to_string()]));
+        let terminal = draw(&mut app, 80, 24);
+        let buffer = terminal.backend().buffer();
+        let fg_of = |y: u16, text: &str| {
+            let x = (0..80).find(|&x| {
+                (x..80)
+                    .map(|x| buffer[(x, y)].symbol())
+                    .collect::<String>()
+                    .starts_with(text)
+            });
+            buffer[(x.unwrap(), y)].fg
+        };
+        // Compact tabs, the job state and the confirmation prompt
+        assert_eq!(fg_of(0, "Jobs"), theme.accent);
+        assert_eq!(fg_of(0, "Details"), theme.dim);
+        assert_eq!(fg_of(3, "R   1"), theme.success);
+        assert_eq!(fg_of(22, "Cancel"), theme.error);
+        assert_eq!(buffer[(0, 23)].bg, theme.status_bg);
+    }
+}
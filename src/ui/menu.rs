@@ -0,0 +1,70 @@
+use ratatui::{
+    Frame,
+    layout::Rect,
+    style::Style,
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, Paragraph},
+};
+
+use crate::action::{JOB_MENU, Panel};
+use crate::app::App;
+
+/// Right-click menu of job actions, opened at the cursor and kept on screen
+pub fn draw_context_menu(f: &mut Frame, app: &mut App) {
+    let Some(menu) = app.context_menu.as_ref() else {
+        return;
+    };
+    let keys: Vec<String> = JOB_MENU
+        .iter()
+        .map(|(action, _)| {
+            app.keymap
+                .labels(Panel::Jobs, *action)
+                .first()
+                .cloned()
+                .unwrap_or_default()
+        })
+        .collect();
+    let label_width = JOB_MENU
+        .iter()
+        .map(|(_, label)| label.len())
+        .max()
+        .unwrap_or(0);
+    let key_width = keys.iter().map(|k| k.chars().count()).max().unwrap_or(0);
+
+    let screen = f.area();
+    let width = ((label_width + key_width + 5) as u16).min(screen.width);
+    let height = (JOB_MENU.len() as u16 + 2).min(screen.height);
+    let x = menu.col.min(screen.right().saturating_sub(width));
+    let y = menu.row.min(screen.bottom().saturating_sub(height));
+    let area = Rect::new(x, y, width, height);
+
+    let theme = app.theme();
+    let lines: Vec<Line> = JOB_MENU
+        .iter()
+        .zip(&keys)
+        .enumerate()
+        .map(|(i, ((_, label), key))| {
+            let style = if i == menu.selected {
+                Style::default().bg(theme.selection)
+            } else {
+                Style::default()
+            };
+            Line::from(vec![
+                Span::styled(format!(" {:<width$}  ", label, width = label_width), style),
+                Span::styled(
+                    format!("{:>width$} ", key, width = key_width),
+                    style.fg(theme.key),
+                ),
+            ])
+        })
+        .collect();
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(theme.accent));
+    f.render_widget(Clear, area);
+    f.render_widget(Paragraph::new(lines).block(block), area);
+    if let Some(menu) = app.context_menu.as_mut() {
+        menu.area = area;
+    }
+}
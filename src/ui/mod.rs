@@ -1,3 +1,12 @@
+mod ansi;
+mod form;
+mod help;
 mod layout;
+mod menu;
+mod theme;
+mod wrap;
 
+pub use form::{Form, FormField, FormOutcome};
 pub use layout::draw_ui;
+pub use theme::{THEMES, Theme};
+pub use wrap::wrapped_rows;
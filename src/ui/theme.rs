@@ -0,0 +1,131 @@
+use ratatui::style::Color;
+
+use crate::slurm::JobState;
+
+/// Names accepted by `display.theme` and `:theme`
+pub const THEMES: &[&str] = &["default", "minimal", "solarized"];
+
+/// Colors of the panels, popups and status bar (logs keep their own ANSI colors)
+#[derive(Debug, Clone, Copy)]
+pub struct Theme {
+    /// Focused borders and headings
+    pub accent: Color,
+    /// Unfocused borders
+    pub border: Color,
+    /// Job table header
+    pub header: Color,
+    /// Background of the selected row
+    pub selection: Color,
+    /// Background of the status bar
+    pub status_bg: Color,
+    /// Key names in hints, help and menus, and field labels
+    pub key: Color,
+    /// Secondary text: inactive tabs, placeholders, axes
+    pub dim: Color,
+    /// Plain text where a color is needed, such as other job states
+    pub text: Color,
+    /// Text drawn on an accent or search highlight background
+    pub inverse: Color,
+    /// Running jobs, progress and confirmations
+    pub success: Color,
+    /// Pending jobs and notices
+    pub warning: Color,
+    /// Failed jobs, errors and NaN metrics
+    pub error: Color,
+    /// Log lines matching an error signature
+    pub log_error: Color,
+    /// stderr lines in the merged log view
+    pub stderr: Color,
+    /// Job marks and changed form fields
+    pub mark: Color,
+    /// Background of rows in a visual selection
+    pub visual: Color,
+    /// Background of search matches, and of the current one
+    pub search: Color,
+    pub search_current: Color,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self {
+            accent: Color::Cyan,
+            border: Color::DarkGray,
+            header: Color::Yellow,
+            selection: Color::DarkGray,
+            status_bg: Color::DarkGray,
+            key: Color::Yellow,
+            dim: Color::DarkGray,
+            text: Color::White,
+            inverse: Color::Black,
+            success: Color::Green,
+            warning: Color::Yellow,
+            error: Color::Red,
+            log_error: Color::LightRed,
+            stderr: Color::LightMagenta,
+            mark: Color::Magenta,
+            visual: Color::Blue,
+            search: Color::Yellow,
+            search_current: Color::LightRed,
+        }
+    }
+}
+
+impl Theme {
+    pub fn named(name: &str) -> Option<Self> {
+        match name {
+            "default" => Some(Self::default()),
+            "minimal" => Some(Self {
+                accent: Color::White,
+                border: Color::DarkGray,
+                header: Color::White,
+                selection: Color::DarkGray,
+                status_bg: Color::Reset,
+                key: Color::White,
+                dim: Color::DarkGray,
+                text: Color::Reset,
+                inverse: Color::Black,
+                success: Color::Reset,
+                warning: Color::Reset,
+                error: Color::Red,
+                log_error: Color::Red,
+                stderr: Color::Gray,
+                mark: Color::White,
+                visual: Color::DarkGray,
+                search: Color::Gray,
+                search_current: Color::White,
+            }),
+            "solarized" => Some(Self {
+                accent: Color::Rgb(0x26, 0x8b, 0xd2),
+                border: Color::Rgb(0x58, 0x6e, 0x75),
+                header: Color::Rgb(0xb5, 0x89, 0x00),
+                selection: Color::Rgb(0x07, 0x36, 0x42),
+                status_bg: Color::Rgb(0x07, 0x36, 0x42),
+                key: Color::Rgb(0xb5, 0x89, 0x00),
+                dim: Color::Rgb(0x58, 0x6e, 0x75),
+                text: Color::Rgb(0x93, 0xa1, 0xa1),
+                inverse: Color::Rgb(0x00, 0x2b, 0x36),
+                success: Color::Rgb(0x85, 0x99, 0x00),
+                warning: Color::Rgb(0xb5, 0x89, 0x00),
+                error: Color::Rgb(0xdc, 0x32, 0x2f),
+                log_error: Color::Rgb(0xcb, 0x4b, 0x16),
+                stderr: Color::Rgb(0x2a, 0xa1, 0x98),
+                mark: Color::Rgb(0xd3, 0x36, 0x82),
+                visual: Color::Rgb(0x6c, 0x71, 0xc4),
+                search: Color::Rgb(0xb5, 0x89, 0x00),
+                search_current: Color::Rgb(0xcb, 0x4b, 0x16),
+            }),
+            _ => None,
+        }
+    }
+
+    /// Color of a job's state in the job list and details
+    pub fn state(&self, state: &JobState) -> Color {
+        match state {
+            JobState::Running => self.success,
+            JobState::Pending => self.warning,
+            JobState::Failed => self.error,
+            JobState::Cancelled => self.dim,
+            _ => self.text,
+        }
+    }
+}
@@ -0,0 +1,152 @@
+use ratatui::text::{Line, Span};
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+fn char_width(c: char) -> usize {
+    c.width().unwrap_or(0)
+}
+
+/// Rows `text` takes when wrapped at `width` cells. Lines break at any character rather
+/// than at words, so every row but the last is full and scroll math stays exact.
+pub fn wrapped_rows(text: &str, width: usize) -> usize {
+    let mut rows = 1;
+    let mut used = 0;
+    for c in text.chars() {
+        let w = char_width(c);
+        if used + w > width && used > 0 {
+            rows += 1;
+            used = 0;
+        }
+        used += w;
+    }
+    rows
+}
+
+/// Split a styled line into rows of at most `width` cells, breaking where `wrapped_rows` does
+pub fn wrap_line(line: Line<'static>, width: usize) -> Vec<Line<'static>> {
+    let style = line.style;
+    let mut rows = Vec::new();
+    let mut row: Vec<Span<'static>> = Vec::new();
+    let mut used = 0;
+    for span in line.spans {
+        let mut piece = String::new();
+        for c in span.content.chars() {
+            let w = char_width(c);
+            if used + w > width && used > 0 {
+                if !piece.is_empty() {
+                    row.push(Span::styled(std::mem::take(&mut piece), span.style));
+                }
+                rows.push(Line::from(std::mem::take(&mut row)).style(style));
+                used = 0;
+            }
+            piece.push(c);
+            used += w;
+        }
+        if !piece.is_empty() {
+            row.push(Span::styled(piece, span.style));
+        }
+    }
+    rows.push(Line::from(row).style(style));
+    rows
+}
+
+/// Fit `text` in `width` cells by replacing its middle with `…`, keeping both ends
+/// (the start of a path and its file name)
+pub fn ellipsize_middle(text: &str, width: usize) -> String {
+    if text.width() <= width {
+        return text.to_string();
+    }
+    if width == 0 {
+        return String::new();
+    }
+    let budget = width - 1;
+    let mut head = String::new();
+    let mut used = 0;
+    for c in text.chars() {
+        if used + char_width(c) > budget.div_ceil(2) {
+            break;
+        }
+        used += char_width(c);
+        head.push(c);
+    }
+    let mut tail = Vec::new();
+    for c in text.chars().rev() {
+        if used + char_width(c) > budget {
+            break;
+        }
+        used += char_width(c);
+        tail.push(c);
+    }
+    head.push('…');
+    head.extend(tail.into_iter().rev());
+    head
+}
+
+/// Cut a styled line at `width` cells, ending it with `…` when anything was dropped
+pub fn truncate_line<'a>(line: Line<'a>, width: usize) -> Line<'a> {
+    if line.width() <= width {
+        return line;
+    }
+    let style = line.style;
+    let mut spans = Vec::new();
+    let mut used = 0;
+    for span in line.spans {
+        let mut piece = String::new();
+        for c in span.content.chars() {
+            if used + char_width(c) + 1 > width {
+                break;
+            }
+            used += char_width(c);
+            piece.push(c);
+        }
+        let cut = piece.len() < span.content.len();
+        if !piece.is_empty() {
+            spans.push(Span::styled(piece, span.style));
+        }
+        if cut {
+            break;
+        }
+    }
+    if width > 0 {
+        spans.push(Span::raw("…"));
+    }
+    Line::from(spans).style(style)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::style::{Color, Style};
+
+    #[test]
+    fn wrap_line_matches_wrapped_rows() {
+        let red = Style::default().fg(Color::Red);
+        let line = Line::from(vec![
+            Span::raw("abcd"),
+            Span::styled("efgh", red),
+            Span::raw("ij"),
+        ]);
+        let rows = wrap_line(line, 3);
+        assert_eq!(rows.len(), wrapped_rows("abcdefghij", 3));
+        let text: Vec<String> = rows.iter().map(|r| r.to_string()).collect();
+        assert_eq!(text, vec!["abc", "def", "ghi", "j"]);
+        assert_eq!(rows[1].spans[1].style, red);
+
+        // A wide character that doesn't fit moves to the next row
+        assert_eq!(wrapped_rows("ab漢", 3), 2);
+        assert_eq!(wrapped_rows("", 3), 1);
+    }
+
+    #[test]
+    fn ellipsis_truncation() {
+        assert_eq!(
+            ellipsize_middle("/nfs/home/user/runs/job.out", 15),
+            "/nfs/ho…job.out"
+        );
+        assert_eq!(ellipsize_middle("short", 15), "short");
+        assert_eq!(ellipsize_middle("abc", 1), "…");
+
+        let line = Line::from(vec![Span::raw("q quit"), Span::raw("  j/k navigate")]);
+        assert_eq!(truncate_line(line.clone(), 20), line);
+        assert_eq!(truncate_line(line, 10).to_string(), "q quit  j…");
+    }
+}